@@ -7,7 +7,15 @@ const json = JSON.parse(file);
 const characters = json;
 
 const csv = {
-  headers: ["literal", "ja_on", "ja_kun", "heisig", "heisig6"],
+  headers: [
+    "literal",
+    "ja_on",
+    "ja_kun",
+    "heisig",
+    "heisig6",
+    "radical",
+    "stroke_count",
+  ],
   rows: [],
 };
 
@@ -26,8 +34,19 @@ for (const char of characters) {
   const references = char.dictionaryReferences;
   const heisig = references.find((r) => r.type === "heisig")?.value || "";
   const heisig6 = references.find((r) => r.type === "heisig6")?.value || "";
-
-  csv.rows.push([char.literal, ja_on, ja_kun, heisig, heisig6]);
+  const radical =
+    char.radicals.find((r) => r.type === "classical")?.value ?? "";
+  const stroke_count = char.misc.strokeCounts[0] ?? "";
+
+  csv.rows.push([
+    char.literal,
+    ja_on,
+    ja_kun,
+    heisig,
+    heisig6,
+    radical,
+    stroke_count,
+  ]);
 }
 
 const csvContent = [
@@ -1,42 +1,366 @@
-use lindera::LinderaResult;
-use lindera::dictionary::{DictionaryKind, load_embedded_dictionary};
-use lindera::mode::Mode;
-use lindera::segmenter::Segmenter;
-use lindera::tokenizer::Tokenizer;
+use crate::word::MyToken;
+use crate::word::token::PartOfSpeechCategory;
 
-fn katakana_to_hiragana(input: &str) -> String {
+pub mod romaji;
+
+/// Offset between the katakana (U+30A1..U+30F6) and hiragana (U+3041..U+3096) blocks.
+const KANA_OFFSET: u32 = 0x60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum ConversionTarget {
+    Hiragana,
+    Katakana,
+    Romaji(RomajiSystem),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum RomajiSystem {
+    Hepburn,
+    Kunrei,
+}
+
+/// How lengthened vowels (`おう`, `ー`, ...) are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum LongVowels {
+    /// Spelled out: `toukyou`, `raamen`; `ー` is kept in kana output.
+    AsWritten,
+    /// Macrons for Hepburn, circumflexes for Kunrei: `tōkyō`, `tôkyô`. In kana
+    /// output `ー` is replaced by the vowel it lengthens.
+    Marked,
+    /// Dropped: `tokyo`, `ramen`. In kana output `ー` is replaced by the vowel
+    /// it lengthens.
+    Omitted,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct ConversionOptions {
+    pub target: ConversionTarget,
+    pub long_vowels: LongVowels,
+    /// Keep katakana words (mostly loanwords) as written in kana output, `ー`
+    /// included.
+    pub preserve_katakana: bool,
+}
+
+impl Default for ConversionOptions {
+    fn default() -> Self {
+        ConversionOptions {
+            target: ConversionTarget::Hiragana,
+            long_vowels: LongVowels::AsWritten,
+            preserve_katakana: false,
+        }
+    }
+}
+
+pub fn katakana_to_hiragana(input: &str) -> String {
     input
         .chars()
-        .map(|c| {
-            if ('ァ'..='ン').contains(&c) {
+        .map(|c| match c {
+            'ァ'..='ヶ' | 'ヽ' | 'ヾ' => {
                 // Shift Unicode value from Katakana to Hiragana block
-                std::char::from_u32(c as u32 - 0x60).unwrap()
-            } else {
-                c
+                std::char::from_u32(c as u32 - KANA_OFFSET).unwrap_or(c)
             }
+            _ => c,
         })
         .collect()
 }
 
-pub fn convert(text: &String) -> Result<String, String> {
-    let dictionary = load_embedded_dictionary(DictionaryKind::IPADIC).map_err(|e| e.to_string())?;
-    let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
-    let tokenizer = Tokenizer::new(segmenter);
+pub fn hiragana_to_katakana(input: &str) -> String {
+    input
+        .chars()
+        .map(|c| match c {
+            'ぁ'..='ゖ' | 'ゝ' | 'ゞ' => {
+                std::char::from_u32(c as u32 + KANA_OFFSET).unwrap_or(c)
+            }
+            _ => c,
+        })
+        .collect()
+}
 
-    let mut tokens = tokenizer.tokenize(text).map_err(|e| e.to_string())?;
+pub fn is_katakana_word(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| matches!(c, 'ァ'..='ヺ' | 'ー' | 'ヽ' | 'ヾ'))
+}
 
-    let mut hiragana_output = String::new();
-    for token in tokens.iter_mut() {
-        let details = token.details();
-        if details.len() > 7 {
-            let katakana = &details[7];
-            let hira = katakana_to_hiragana(katakana);
-            hiragana_output.push_str(&hira);
+/// Replaces `ー` with the vowel of the preceding kana.
+pub fn expand_long_vowel_mark(kana: &str) -> String {
+    let mut output = String::new();
+    for c in kana.chars() {
+        if c == 'ー' {
+            let vowel = output
+                .chars()
+                .last()
+                .map(|last| {
+                    romaji::kana_to_romaji(
+                        &last.to_string(),
+                        RomajiSystem::Hepburn,
+                        LongVowels::AsWritten,
+                    )
+                })
+                .and_then(|romaji| romaji.chars().last())
+                .and_then(vowel_kana);
+            match vowel {
+                Some(vowel) => output.push(vowel),
+                None => output.push(c),
+            }
         } else {
-            // fallback to surface form if no reading
-            hiragana_output.push_str(&token.text);
+            output.push(c);
+        }
+    }
+    output
+}
+
+fn vowel_kana(vowel: char) -> Option<char> {
+    match vowel {
+        'a' => Some('あ'),
+        'i' => Some('い'),
+        'u' => Some('う'),
+        'e' => Some('え'),
+        'o' => Some('お'),
+        _ => None,
+    }
+}
+
+/// Hiragana reading of a token: the dictionary reading when the surface form
+/// contains kanji, the surface form itself otherwise.
+fn token_kana(token: &MyToken) -> String {
+    let has_kanji = token.text().chars().any(wana_kana::utils::is_char_kanji);
+    match token.reading() {
+        Some(reading) if has_kanji => katakana_to_hiragana(reading),
+        _ => token.text().to_string(),
+    }
+}
+
+/// `は` and `へ` used as particles are pronounced, and romanized, as `wa` and `e`.
+fn particle_romaji(token: &MyToken) -> Option<&'static str> {
+    if token.category() != Some(&PartOfSpeechCategory::Particle) {
+        return None;
+    }
+    match token.text() {
+        "は" => Some("wa"),
+        "へ" => Some("e"),
+        _ => None,
+    }
+}
+
+/// Kana output of a token for the hiragana and katakana targets.
+fn token_to_kana(token: &MyToken, kana: &str, options: &ConversionOptions) -> String {
+    if options.preserve_katakana && is_katakana_word(token.text()) {
+        return token.text().to_string();
+    }
+    let hiragana = match options.long_vowels {
+        LongVowels::AsWritten => katakana_to_hiragana(kana),
+        LongVowels::Marked | LongVowels::Omitted => {
+            expand_long_vowel_mark(&katakana_to_hiragana(kana))
+        }
+    };
+    match options.target {
+        ConversionTarget::Katakana => hiragana_to_katakana(&hiragana),
+        _ => hiragana,
+    }
+}
+
+pub fn convert_tokens(tokens: &[MyToken], options: &ConversionOptions) -> String {
+    let mut output = String::new();
+    for token in tokens {
+        let kana = token_kana(token);
+        match options.target {
+            ConversionTarget::Hiragana | ConversionTarget::Katakana => {
+                output.push_str(&token_to_kana(token, &kana, options));
+            }
+            ConversionTarget::Romaji(system) => {
+                let romaji = match particle_romaji(token) {
+                    Some(particle) => particle.to_string(),
+                    None => romaji::kana_to_romaji(&kana, system, options.long_vowels),
+                };
+                // Words are separated by spaces, punctuation sticks to the
+                // word before it.
+                let starts_word = romaji.chars().next().is_some_and(|c| c.is_alphanumeric());
+                if starts_word && !output.is_empty() && !output.ends_with(' ') {
+                    output.push(' ');
+                }
+                output.push_str(&romaji);
+            }
         }
     }
 
-    Ok(hiragana_output)
+    match options.target {
+        ConversionTarget::Romaji(_) => output.trim_end().to_string(),
+        _ => output,
+    }
+}
+
+pub async fn convert_with(
+    app_handle: &tauri::AppHandle,
+    text: &str,
+    options: &ConversionOptions,
+) -> Result<String, String> {
+    let tokens = crate::tokenizer::tokenize(app_handle, text).await?;
+    Ok(convert_tokens(&tokens, options))
+}
+
+/// Hiragana reading of `text`, with kanji read by lindera.
+pub async fn convert(app_handle: &tauri::AppHandle, text: &String) -> Result<String, String> {
+    convert_with(app_handle, text, &ConversionOptions::default()).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn convert_text(
+    app_handle: tauri::AppHandle,
+    text: String,
+    options: ConversionOptions,
+) -> Result<String, String> {
+    convert_with(&app_handle, &text, &options).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::word::token::PartOfSpeechCategory;
+
+    fn noun(surface: &str, reading: &str) -> MyToken {
+        MyToken::new(surface, Some(PartOfSpeechCategory::Noun), Some(reading))
+    }
+
+    fn particle(surface: &str) -> MyToken {
+        MyToken::new(surface, Some(PartOfSpeechCategory::Particle), Some(surface))
+    }
+
+    /// 東京はラーメン
+    fn sentence() -> Vec<MyToken> {
+        vec![
+            noun("東京", "トウキョウ"),
+            particle("は"),
+            noun("ラーメン", "ラーメン"),
+        ]
+    }
+
+    fn convert(
+        tokens: &[MyToken],
+        target: ConversionTarget,
+        long_vowels: LongVowels,
+        preserve_katakana: bool,
+    ) -> String {
+        let options = ConversionOptions {
+            target,
+            long_vowels,
+            preserve_katakana,
+        };
+        convert_tokens(tokens, &options)
+    }
+
+    #[test]
+    fn kana_blocks_are_shifted() {
+        assert_eq!(katakana_to_hiragana("ヴヵヶヽヾ"), "ゔゕゖゝゞ");
+        assert_eq!(hiragana_to_katakana("ゔゕゖゝゞ"), "ヴヵヶヽヾ");
+        // No hiragana counterpart.
+        assert_eq!(katakana_to_hiragana("ヷー"), "ヷー");
+    }
+
+    #[test]
+    fn long_vowel_mark_takes_the_previous_vowel() {
+        assert_eq!(expand_long_vowel_mark("らーめん"), "らあめん");
+        assert_eq!(expand_long_vowel_mark("こーひー"), "こおひい");
+        assert_eq!(expand_long_vowel_mark("ちぇー"), "ちぇえ");
+        assert_eq!(expand_long_vowel_mark("ーん"), "ーん");
+    }
+
+    #[test]
+    fn hiragana_target() {
+        let tokens = sentence();
+        let hiragana = ConversionTarget::Hiragana;
+
+        assert_eq!(
+            convert(&tokens, hiragana, LongVowels::AsWritten, false),
+            "とうきょうはらーめん"
+        );
+        assert_eq!(
+            convert(&tokens, hiragana, LongVowels::Marked, false),
+            "とうきょうはらあめん"
+        );
+        assert_eq!(
+            convert(&tokens, hiragana, LongVowels::Omitted, false),
+            "とうきょうはらあめん"
+        );
+        assert_eq!(
+            convert(&tokens, hiragana, LongVowels::Omitted, true),
+            "とうきょうはラーメン"
+        );
+    }
+
+    #[test]
+    fn katakana_target() {
+        let tokens = sentence();
+        let katakana = ConversionTarget::Katakana;
+
+        assert_eq!(
+            convert(&tokens, katakana, LongVowels::AsWritten, false),
+            "トウキョウハラーメン"
+        );
+        assert_eq!(
+            convert(&tokens, katakana, LongVowels::Marked, false),
+            "トウキョウハラアメン"
+        );
+        assert_eq!(
+            convert(&tokens, katakana, LongVowels::Omitted, false),
+            "トウキョウハラアメン"
+        );
+        assert_eq!(
+            convert(&tokens, katakana, LongVowels::Omitted, true),
+            "トウキョウハラーメン"
+        );
+    }
+
+    #[test]
+    fn romaji_target() {
+        let tokens = sentence();
+        let hepburn = ConversionTarget::Romaji(RomajiSystem::Hepburn);
+        let kunrei = ConversionTarget::Romaji(RomajiSystem::Kunrei);
+
+        assert_eq!(
+            convert(&tokens, hepburn, LongVowels::AsWritten, false),
+            "toukyou wa raamen"
+        );
+        assert_eq!(
+            convert(&tokens, hepburn, LongVowels::Marked, false),
+            "tōkyō wa rāmen"
+        );
+        assert_eq!(
+            convert(&tokens, hepburn, LongVowels::Omitted, false),
+            "tokyo wa ramen"
+        );
+        assert_eq!(
+            convert(&tokens, kunrei, LongVowels::Marked, false),
+            "tôkyô wa râmen"
+        );
+    }
+
+    #[test]
+    fn particles_are_romanized_as_pronounced() {
+        let tokens = vec![
+            noun("学校", "ガッコウ"),
+            particle("へ"),
+            noun("母", "ハハ"),
+            particle("は"),
+            MyToken::new("。", Some(PartOfSpeechCategory::Symbol), None),
+        ];
+        let hepburn = ConversionTarget::Romaji(RomajiSystem::Hepburn);
+
+        assert_eq!(
+            convert(&tokens, hepburn, LongVowels::AsWritten, false),
+            "gakkou e haha wa."
+        );
+        // Only the particles, not every は and へ.
+        assert_eq!(
+            convert(
+                &[noun("部屋", "ヘヤ")],
+                hepburn,
+                LongVowels::AsWritten,
+                false
+            ),
+            "heya"
+        );
+    }
 }
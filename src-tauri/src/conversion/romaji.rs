@@ -0,0 +1,423 @@
+use super::{LongVowels, RomajiSystem};
+
+/// `(kana, hepburn, kunrei)`. Hiragana only, katakana is converted first. The
+/// `ヷ`..`ヺ` row has no hiragana counterpart and is listed as is.
+const MORAE: [(char, &str, &str); 96] = [
+    ('あ', "a", "a"),
+    ('い', "i", "i"),
+    ('う', "u", "u"),
+    ('え', "e", "e"),
+    ('お', "o", "o"),
+    ('か', "ka", "ka"),
+    ('き', "ki", "ki"),
+    ('く', "ku", "ku"),
+    ('け', "ke", "ke"),
+    ('こ', "ko", "ko"),
+    ('が', "ga", "ga"),
+    ('ぎ', "gi", "gi"),
+    ('ぐ', "gu", "gu"),
+    ('げ', "ge", "ge"),
+    ('ご', "go", "go"),
+    ('さ', "sa", "sa"),
+    ('し', "shi", "si"),
+    ('す', "su", "su"),
+    ('せ', "se", "se"),
+    ('そ', "so", "so"),
+    ('ざ', "za", "za"),
+    ('じ', "ji", "zi"),
+    ('ず', "zu", "zu"),
+    ('ぜ', "ze", "ze"),
+    ('ぞ', "zo", "zo"),
+    ('た', "ta", "ta"),
+    ('ち', "chi", "ti"),
+    ('つ', "tsu", "tu"),
+    ('て', "te", "te"),
+    ('と', "to", "to"),
+    ('だ', "da", "da"),
+    ('ぢ', "ji", "zi"),
+    ('づ', "zu", "zu"),
+    ('で', "de", "de"),
+    ('ど', "do", "do"),
+    ('な', "na", "na"),
+    ('に', "ni", "ni"),
+    ('ぬ', "nu", "nu"),
+    ('ね', "ne", "ne"),
+    ('の', "no", "no"),
+    ('は', "ha", "ha"),
+    ('ひ', "hi", "hi"),
+    ('ふ', "fu", "hu"),
+    ('へ', "he", "he"),
+    ('ほ', "ho", "ho"),
+    ('ば', "ba", "ba"),
+    ('び', "bi", "bi"),
+    ('ぶ', "bu", "bu"),
+    ('べ', "be", "be"),
+    ('ぼ', "bo", "bo"),
+    ('ぱ', "pa", "pa"),
+    ('ぴ', "pi", "pi"),
+    ('ぷ', "pu", "pu"),
+    ('ぺ', "pe", "pe"),
+    ('ぽ', "po", "po"),
+    ('ま', "ma", "ma"),
+    ('み', "mi", "mi"),
+    ('む', "mu", "mu"),
+    ('め', "me", "me"),
+    ('も', "mo", "mo"),
+    ('や', "ya", "ya"),
+    ('ゆ', "yu", "yu"),
+    ('よ', "yo", "yo"),
+    ('ら', "ra", "ra"),
+    ('り', "ri", "ri"),
+    ('る', "ru", "ru"),
+    ('れ', "re", "re"),
+    ('ろ', "ro", "ro"),
+    ('わ', "wa", "wa"),
+    ('ゐ', "i", "i"),
+    ('ゑ', "e", "e"),
+    ('を', "o", "o"),
+    ('ゔ', "vu", "vu"),
+    ('ぁ', "a", "a"),
+    ('ぃ', "i", "i"),
+    ('ぅ', "u", "u"),
+    ('ぇ', "e", "e"),
+    ('ぉ', "o", "o"),
+    ('ゃ', "ya", "ya"),
+    ('ゅ', "yu", "yu"),
+    ('ょ', "yo", "yo"),
+    ('ゎ', "wa", "wa"),
+    ('ゕ', "ka", "ka"),
+    ('ゖ', "ke", "ke"),
+    ('ヷ', "va", "va"),
+    ('ヸ', "vi", "vi"),
+    ('ヹ', "ve", "ve"),
+    ('ヺ', "vo", "vo"),
+    ('ゝ', "", ""),
+    ('ゞ', "", ""),
+    ('、', ", ", ", "),
+    ('。', ". ", ". "),
+    ('「', "\"", "\""),
+    ('」', "\"", "\""),
+    ('！', "!", "!"),
+    ('？', "?", "?"),
+];
+
+const SMALL_Y: [char; 3] = ['ゃ', 'ゅ', 'ょ'];
+const SMALL_VOWELS: [char; 5] = ['ぁ', 'ぃ', 'ぅ', 'ぇ', 'ぉ'];
+const VOWELS: [char; 5] = ['a', 'i', 'u', 'e', 'o'];
+
+/// Marks the long vowel sign in the intermediate mora list.
+const LONG_VOWEL_MARK: &str = "ー";
+
+fn mora(c: char, system: RomajiSystem) -> Option<&'static str> {
+    MORAE
+        .iter()
+        .find(|(kana, _, _)| *kana == c)
+        .map(|(_, hepburn, kunrei)| match system {
+            RomajiSystem::Hepburn => *hepburn,
+            RomajiSystem::Kunrei => *kunrei,
+        })
+}
+
+/// Two-kana morae: `きゃ`-style yōon and `ファ`-style loanword combinations.
+fn digraph(c: char, small: char, system: RomajiSystem) -> Option<String> {
+    let base = mora(c, system)?;
+    let small_romaji = mora(small, system)?;
+    let consonant = base.strip_suffix(|v: char| VOWELS.contains(&v))?;
+
+    if SMALL_Y.contains(&small) {
+        if !base.ends_with('i') || consonant.is_empty() {
+            return None;
+        }
+        let vowel = &small_romaji[1..];
+        let hepburn_palatal =
+            system == RomajiSystem::Hepburn && matches!(consonant, "sh" | "ch" | "j");
+        if hepburn_palatal {
+            Some(format!("{}{}", consonant, vowel))
+        } else {
+            Some(format!("{}y{}", consonant, vowel))
+        }
+    } else if SMALL_VOWELS.contains(&small) {
+        let consonant = match c {
+            'う' => "w",
+            'ゔ' => "v",
+            _ if consonant.is_empty() => return None,
+            _ => consonant,
+        };
+        Some(format!("{}{}", consonant, small_romaji))
+    } else {
+        None
+    }
+}
+
+fn with_mark(vowel: char, system: RomajiSystem) -> char {
+    match (system, vowel) {
+        (RomajiSystem::Hepburn, 'a') => 'ā',
+        (RomajiSystem::Hepburn, 'i') => 'ī',
+        (RomajiSystem::Hepburn, 'u') => 'ū',
+        (RomajiSystem::Hepburn, 'e') => 'ē',
+        (RomajiSystem::Hepburn, 'o') => 'ō',
+        (RomajiSystem::Kunrei, 'a') => 'â',
+        (RomajiSystem::Kunrei, 'i') => 'î',
+        (RomajiSystem::Kunrei, 'u') => 'û',
+        (RomajiSystem::Kunrei, 'e') => 'ê',
+        (RomajiSystem::Kunrei, 'o') => 'ô',
+        (_, c) => c,
+    }
+}
+
+/// Splits hiragana into romanized morae. Gemination is resolved here, long
+/// vowels are left to `join_morae`.
+fn to_morae(kana: &str, system: RomajiSystem) -> Vec<String> {
+    let chars = kana.chars().collect::<Vec<_>>();
+    let mut morae = Vec::new();
+    let mut geminate = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            'っ' => {
+                geminate = true;
+                i += 1;
+                continue;
+            }
+            'ん' => {
+                let next_romaji = chars
+                    .get(i + 1)
+                    .and_then(|next| mora(*next, system))
+                    .unwrap_or_default();
+                let needs_apostrophe = next_romaji
+                    .chars()
+                    .next()
+                    .is_some_and(|first| VOWELS.contains(&first) || first == 'y');
+                morae.push(if needs_apostrophe { "n'" } else { "n" }.to_string());
+                i += 1;
+                continue;
+            }
+            'ー' => {
+                morae.push(LONG_VOWEL_MARK.to_string());
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        let (romaji, length) = match chars.get(i + 1).and_then(|n| digraph(c, *n, system)) {
+            Some(romaji) => (romaji, 2),
+            None => match mora(c, system) {
+                Some(romaji) => (romaji.to_string(), 1),
+                None => (c.to_string(), 1),
+            },
+        };
+        i += length;
+
+        if geminate {
+            geminate = false;
+            let doubled = if system == RomajiSystem::Hepburn && romaji.starts_with("ch") {
+                format!("t{}", romaji)
+            } else {
+                match romaji.chars().next() {
+                    Some(first) if !VOWELS.contains(&first) && first.is_ascii_alphabetic() => {
+                        format!("{}{}", first, romaji)
+                    }
+                    _ => romaji,
+                }
+            };
+            morae.push(doubled);
+        } else {
+            morae.push(romaji);
+        }
+    }
+
+    morae
+}
+
+fn lengthens(previous: char, vowel: &str) -> bool {
+    matches!(
+        (previous, vowel),
+        ('a', "a") | ('u', "u") | ('e', "e") | ('o', "o") | ('o', "u")
+    )
+}
+
+fn join_morae(morae: Vec<String>, system: RomajiSystem, long_vowels: LongVowels) -> String {
+    let mut output = String::new();
+
+    for mora in morae {
+        let last_vowel = output.chars().last().filter(|c| VOWELS.contains(c));
+
+        if mora == LONG_VOWEL_MARK {
+            match (long_vowels, last_vowel) {
+                (LongVowels::AsWritten, Some(vowel)) => output.push(vowel),
+                (LongVowels::Marked, Some(vowel)) => {
+                    output.pop();
+                    output.push(with_mark(vowel, system));
+                }
+                (_, None) | (LongVowels::Omitted, _) => {}
+            }
+            continue;
+        }
+
+        match (long_vowels, last_vowel) {
+            (LongVowels::Marked, Some(vowel)) if lengthens(vowel, &mora) => {
+                output.pop();
+                output.push(with_mark(vowel, system));
+            }
+            (LongVowels::Omitted, Some(vowel)) if lengthens(vowel, &mora) => {}
+            _ => output.push_str(&mora),
+        }
+    }
+
+    output
+}
+
+/// Romanizes hiragana or katakana.
+pub fn kana_to_romaji(kana: &str, system: RomajiSystem, long_vowels: LongVowels) -> String {
+    let hiragana = super::katakana_to_hiragana(kana);
+    join_morae(to_morae(&hiragana, system), system, long_vowels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hepburn(kana: &str) -> String {
+        kana_to_romaji(kana, RomajiSystem::Hepburn, LongVowels::AsWritten)
+    }
+
+    fn kunrei(kana: &str) -> String {
+        kana_to_romaji(kana, RomajiSystem::Kunrei, LongVowels::AsWritten)
+    }
+
+    fn morae(morae: &[&str]) -> Vec<String> {
+        morae.iter().map(|m| m.to_string()).collect()
+    }
+
+    #[test]
+    fn hepburn_and_kunrei_differ_where_expected() {
+        let cases = [
+            ("し", "shi", "si"),
+            ("ち", "chi", "ti"),
+            ("つ", "tsu", "tu"),
+            ("ふ", "fu", "hu"),
+            ("じ", "ji", "zi"),
+            ("ぢ", "ji", "zi"),
+            ("しゃしん", "shashin", "syasin"),
+            ("ちゅうい", "chuui", "tyuui"),
+            ("じょし", "joshi", "zyosi"),
+            ("きょう", "kyou", "kyou"),
+            ("を", "o", "o"),
+        ];
+        for (kana, expected_hepburn, expected_kunrei) in cases {
+            assert_eq!(hepburn(kana), expected_hepburn, "{}", kana);
+            assert_eq!(kunrei(kana), expected_kunrei, "{}", kana);
+        }
+    }
+
+    #[test]
+    fn small_tsu_doubles_the_next_consonant() {
+        assert_eq!(hepburn("きって"), "kitte");
+        assert_eq!(hepburn("まっちゃ"), "matcha");
+        assert_eq!(kunrei("まっちゃ"), "mattya");
+        // Nothing to double at the end of a word.
+        assert_eq!(hepburn("あっ"), "a");
+    }
+
+    #[test]
+    fn syllabic_n_before_vowels_and_y() {
+        assert_eq!(hepburn("きんえん"), "kin'en");
+        assert_eq!(hepburn("こんや"), "kon'ya");
+        assert_eq!(hepburn("せんぱい"), "senpai");
+        assert_eq!(hepburn("ほん"), "hon");
+    }
+
+    #[test]
+    fn loanword_kana() {
+        assert_eq!(hepburn("ヴ"), "vu");
+        assert_eq!(hepburn("ヴァイオリン"), "vaiorin");
+        assert_eq!(hepburn("ファン"), "fan");
+        assert_eq!(hepburn("ウィキ"), "wiki");
+        assert_eq!(hepburn("ティー"), "tii");
+        // The small ヵ and ヶ counters.
+        assert_eq!(hepburn("ヵ"), "ka");
+        assert_eq!(hepburn("ヶ"), "ke");
+    }
+
+    #[test]
+    fn long_vowel_mark_in_each_mode() {
+        let ramen = "ラーメン";
+        let cases = [
+            (LongVowels::AsWritten, "raamen", "raamen"),
+            (LongVowels::Marked, "rāmen", "râmen"),
+            (LongVowels::Omitted, "ramen", "ramen"),
+        ];
+        for (long_vowels, expected_hepburn, expected_kunrei) in cases {
+            assert_eq!(
+                kana_to_romaji(ramen, RomajiSystem::Hepburn, long_vowels),
+                expected_hepburn
+            );
+            assert_eq!(
+                kana_to_romaji(ramen, RomajiSystem::Kunrei, long_vowels),
+                expected_kunrei
+            );
+        }
+    }
+
+    #[test]
+    fn spelled_out_long_vowels_in_each_mode() {
+        let cases = [
+            ("とうきょう", LongVowels::AsWritten, "toukyou"),
+            ("とうきょう", LongVowels::Marked, "tōkyō"),
+            ("とうきょう", LongVowels::Omitted, "tokyo"),
+            ("おかあさん", LongVowels::Marked, "okāsan"),
+            ("すうがく", LongVowels::Omitted, "sugaku"),
+            // えい and いい are not lengthened.
+            ("せんせい", LongVowels::Marked, "sensei"),
+            ("おにいさん", LongVowels::Marked, "oniisan"),
+        ];
+        for (kana, long_vowels, expected) in cases {
+            assert_eq!(
+                kana_to_romaji(kana, RomajiSystem::Hepburn, long_vowels),
+                expected,
+                "{}",
+                kana
+            );
+        }
+    }
+
+    #[test]
+    fn join_morae_merges_long_vowels() {
+        let tokyo = morae(&["to", "u", "kyo", "u"]);
+        assert_eq!(
+            join_morae(tokyo.clone(), RomajiSystem::Hepburn, LongVowels::AsWritten),
+            "toukyou"
+        );
+        assert_eq!(
+            join_morae(tokyo.clone(), RomajiSystem::Hepburn, LongVowels::Marked),
+            "tōkyō"
+        );
+        assert_eq!(
+            join_morae(tokyo, RomajiSystem::Kunrei, LongVowels::Marked),
+            "tôkyô"
+        );
+
+        // A mark without a vowel before it has nothing to lengthen.
+        let orphan_mark = morae(&[LONG_VOWEL_MARK, "n"]);
+        assert_eq!(
+            join_morae(orphan_mark, RomajiSystem::Hepburn, LongVowels::AsWritten),
+            "n"
+        );
+        assert_eq!(
+            join_morae(
+                morae(&["n", LONG_VOWEL_MARK]),
+                RomajiSystem::Hepburn,
+                LongVowels::Marked
+            ),
+            "n"
+        );
+    }
+
+    #[test]
+    fn punctuation() {
+        assert_eq!(hepburn("「はい」、どうぞ。"), "\"hai\", douzo. ");
+    }
+}
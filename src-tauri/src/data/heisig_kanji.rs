@@ -1,52 +1,372 @@
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::Arc;
 
 const HEISIG_JSON: &str = include_str!("./heisig_kanji.json");
 
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+/// Tags that survive sanitization of the story/comment fields. Everything else
+/// is dropped (its text content is kept) and attributes are always stripped.
+const ALLOWED_TAGS: [&str; 7] = ["b", "i", "em", "strong", "br", "sup", "sub"];
+
+/// Shape of a single record in `heisig_kanji.json`, kept private so that the
+/// stringly-typed source format never leaks out of this module. The
+/// `strokeDiagram` column points at images that are not shipped and is
+/// skipped, stroke order comes from KanjiVG instead.
+#[allow(non_snake_case)]
+#[derive(Debug, serde::Deserialize)]
+struct RawHeisigKanji {
+    id: String,
+    frameNoV4: String,
+    frameNoV6: String,
+    keyword: String,
+    kanji: String,
+    hint: String,
+    constituent: String,
+    strokeCount: String,
+    lessonNo: String,
+    myStory: String,
+    heisigStory: String,
+    heisigComment: String,
+    koohiiStory1: String,
+    koohiiStory2: String,
+    jouYou: String,
+    jlpt: String,
+    onYomi: String,
+    kunYomi: String,
+    words: String,
+    readingExamples: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub enum JouYou {
+    Grade(u8),
+    JuniorHigh,
+    Names,
+}
+
+/// A single on'yomi or kun'yomi. `stem` is the part written with the kanji,
+/// `okurigana` the kana that follow it (the part after the dot in the source
+/// data). `prefix`/`suffix` mark readings that only occur in compounds
+/// (`ひと-`, `-ばら`).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Reading {
+    pub stem: String,
+    pub okurigana: Option<String>,
+    pub prefix: bool,
+    pub suffix: bool,
+}
+
+impl Reading {
+    fn parse(input: &str) -> Option<Self> {
+        let text = strip_html(input);
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+
+        let suffix = text.starts_with('-');
+        let prefix = text.ends_with('-');
+        let text = text.trim_matches('-');
+
+        let (stem, okurigana) = match text.split_once('.') {
+            Some((stem, okurigana)) => (stem.to_string(), Some(okurigana.to_string())),
+            None => (text.to_string(), None),
+        };
+
+        Some(Reading {
+            stem,
+            okurigana,
+            prefix,
+            suffix,
+        })
+    }
+
+    fn parse_list(input: &str) -> Vec<Self> {
+        input.split('、').filter_map(Reading::parse).collect()
+    }
+
+    /// The reading as it is pronounced, without the okurigana dot and affix dashes.
+    pub fn to_kana(&self) -> String {
+        match &self.okurigana {
+            Some(okurigana) => format!("{}{}", self.stem, okurigana),
+            None => self.stem.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Reading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.suffix {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", self.stem)?;
+        if let Some(okurigana) = &self.okurigana {
+            write!(f, ".{}", okurigana)?;
+        }
+        if self.prefix {
+            write!(f, "-")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ExampleWord {
+    pub word: String,
+    pub reading: String,
+    pub meaning: String,
+}
+
+impl ExampleWord {
+    /// Parses a `word(reading): meaning` line from the `words` field.
+    fn parse(input: &str) -> Option<Self> {
+        let text = strip_html(input);
+        let (head, meaning) = text.split_once("):")?;
+        let (word, reading) = head.split_once('(')?;
+        let word = word.trim();
+        let reading = reading.trim();
+        if word.is_empty() || reading.is_empty() {
+            return None;
+        }
+
+        Some(ExampleWord {
+            word: word.to_string(),
+            reading: reading.to_string(),
+            meaning: meaning.trim().to_string(),
+        })
+    }
+}
+
+/// An entry of the `readingExamples` field, e.g. `一つ (ひと.つ)`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ReadingExample {
+    pub word: String,
+    pub reading: Reading,
+}
+
+impl ReadingExample {
+    fn parse(input: &str) -> Option<Self> {
+        let text = strip_html(input);
+        let (word, reading) = text.trim().strip_suffix(')')?.split_once('(')?;
+        let word = word.trim();
+        if word.is_empty() {
+            return None;
+        }
+
+        Some(ReadingExample {
+            word: word.to_string(),
+            reading: Reading::parse(reading)?,
+        })
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
 pub struct HeisigKanji {
     pub id: String,
-    pub frameNoV4: String,
-    pub frameNoV6: String,
+    pub frame_no_v4: u32,
+    pub frame_no_v6: u32,
     pub keyword: String,
     pub kanji: String,
-    pub strokeDiagram: String,
     pub hint: String,
-    pub constituent: String,
-    pub strokeCount: String,
-    pub lessonNo: String,
-    pub myStory: String,
-    pub heisigStory: String,
-    pub heisigComment: String,
-    pub koohiiStory1: String,
-    pub koohiiStory2: String,
-    pub jouYou: String,
-    pub jlpt: String,
-    pub onYomi: String,
-    pub kunYomi: String,
-    pub words: String,
-    pub readingExamples: String,
+    pub constituents: Vec<String>,
+    pub stroke_count: Option<u32>,
+    pub lesson_no: Option<u32>,
+    pub my_story: String,
+    pub heisig_story: String,
+    pub heisig_comment: String,
+    pub koohii_story_1: String,
+    pub koohii_story_2: String,
+    pub jou_you: Option<JouYou>,
+    pub jlpt: Option<u8>,
+    pub on_yomi: Vec<Reading>,
+    pub kun_yomi: Vec<Reading>,
+    pub words: Vec<ExampleWord>,
+    pub reading_examples: Vec<ReadingExample>,
 }
 
+impl HeisigKanji {
+    pub fn has_reading(&self, reading: &str) -> bool {
+        self.on_yomi
+            .iter()
+            .chain(self.kun_yomi.iter())
+            .any(|r| r.to_kana().contains(reading))
+    }
+}
+
+impl TryFrom<RawHeisigKanji> for HeisigKanji {
+    type Error = String;
+
+    fn try_from(raw: RawHeisigKanji) -> Result<Self, Self::Error> {
+        let context = |field: &str, value: &str| {
+            format!(
+                "Invalid {} {:?} for Heisig kanji {} (id {})",
+                field, value, raw.kanji, raw.id
+            )
+        };
+
+        let frame_no_v4 = parse_number(&raw.frameNoV4)
+            .map_err(|_| context("frameNoV4", &raw.frameNoV4))?
+            .ok_or_else(|| context("frameNoV4", &raw.frameNoV4))?;
+        let frame_no_v6 = parse_number(&raw.frameNoV6)
+            .map_err(|_| context("frameNoV6", &raw.frameNoV6))?
+            .ok_or_else(|| context("frameNoV6", &raw.frameNoV6))?;
+        let stroke_count =
+            parse_number(&raw.strokeCount).map_err(|_| context("strokeCount", &raw.strokeCount))?;
+        let lesson_no =
+            parse_number(&raw.lessonNo).map_err(|_| context("lessonNo", &raw.lessonNo))?;
+        let jlpt = parse_number(&raw.jlpt)
+            .map_err(|_| context("jlpt", &raw.jlpt))?
+            .map(|n| n as u8);
+        let jou_you = match raw.jouYou.trim() {
+            "" => None,
+            "JH" => Some(JouYou::JuniorHigh),
+            "Names" => Some(JouYou::Names),
+            grade => Some(JouYou::Grade(
+                grade
+                    .parse::<u8>()
+                    .map_err(|_| context("jouYou", &raw.jouYou))?,
+            )),
+        };
+
+        Ok(HeisigKanji {
+            frame_no_v4,
+            frame_no_v6,
+            stroke_count,
+            lesson_no,
+            jlpt,
+            jou_you,
+            constituents: raw
+                .constituent
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            my_story: sanitize_html(&raw.myStory),
+            heisig_story: sanitize_html(&raw.heisigStory),
+            heisig_comment: sanitize_html(&raw.heisigComment),
+            koohii_story_1: sanitize_html(&raw.koohiiStory1),
+            koohii_story_2: sanitize_html(&raw.koohiiStory2),
+            hint: sanitize_html(&raw.hint),
+            on_yomi: Reading::parse_list(&raw.onYomi),
+            kun_yomi: Reading::parse_list(&raw.kunYomi),
+            words: raw
+                .words
+                .split("<br>")
+                .filter_map(ExampleWord::parse)
+                .collect(),
+            reading_examples: raw
+                .readingExamples
+                .split(", ")
+                .filter_map(ReadingExample::parse)
+                .collect(),
+            id: raw.id,
+            keyword: raw.keyword,
+            kanji: raw.kanji,
+        })
+    }
+}
+
+fn parse_number(input: &str) -> Result<Option<u32>, std::num::ParseIntError> {
+    let input = input.trim();
+    if input.is_empty() {
+        Ok(None)
+    } else {
+        input.parse::<u32>().map(Some)
+    }
+}
+
+/// Removes every tag, keeping only the text content.
+pub fn strip_html(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut in_tag = false;
+    for c in input.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => output.push(c),
+            _ => {}
+        }
+    }
+    output
+}
+
+/// Keeps the tags from `ALLOWED_TAGS` (without attributes) and drops the rest.
+fn sanitize_html(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            // Unterminated tag, treat the remainder as text.
+            output.push_str(&rest[start..].replace('<', "&lt;"));
+            return output;
+        };
+
+        let tag = rest[start + 1..start + end].trim();
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        if ALLOWED_TAGS.contains(&name.as_str()) {
+            if name == "br" {
+                output.push_str("<br>");
+            } else if closing {
+                output.push_str(&format!("</{}>", name));
+            } else {
+                output.push_str(&format!("<{}>", name));
+            }
+        }
+
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+
+    output
+}
+
+/// A keyword or primitive name the way stories write it: `II (two)` → `ii`,
+/// `vice-` → `vice`, `moon2` → `moon`.
+pub fn story_form(name: &str) -> String {
+    let name = name.split('(').next().unwrap_or_default();
+    name.trim()
+        .trim_end_matches(|c: char| c.is_ascii_digit())
+        .trim_matches('-')
+        .trim()
+        .to_lowercase()
+}
+
+#[derive(Default)]
 pub struct HeisigKanjis {
     vec: Vec<Arc<HeisigKanji>>,
     id_map: HashMap<String, Arc<HeisigKanji>>,
     kanji_map: HashMap<String, Arc<HeisigKanji>>,
+    /// By `story_form` of the keyword.
+    keyword_map: HashMap<String, Arc<HeisigKanji>>,
 }
 
 impl HeisigKanjis {
     fn new(vec: Vec<Arc<HeisigKanji>>) -> Self {
         let mut id_map = HashMap::new();
         let mut kanji_map = HashMap::new();
+        let mut keyword_map = HashMap::new();
 
         for kanji in vec.iter() {
             id_map.insert(kanji.id.clone(), kanji.clone());
             kanji_map.insert(kanji.kanji.clone(), kanji.clone());
+            keyword_map
+                .entry(story_form(&kanji.keyword))
+                .or_insert_with(|| kanji.clone());
         }
         Self {
             vec,
             id_map,
             kanji_map,
+            keyword_map,
         }
     }
 
@@ -59,10 +379,15 @@ impl HeisigKanjis {
         if let Some(k) = res { vec![k] } else { vec![] }
     }
 
+    /// The kanji a primitive is named after, `name` as written in a story.
+    pub fn get_by_keyword(&self, name: &str) -> Option<Arc<HeisigKanji>> {
+        self.keyword_map.get(&story_form(name)).cloned()
+    }
+
     pub fn get_by_reading(&self, reading: &String) -> Vec<Arc<HeisigKanji>> {
         self.vec
             .iter()
-            .filter(|k| k.onYomi.contains(reading) || k.kunYomi.contains(reading))
+            .filter(|k| k.has_reading(reading))
             .cloned()
             .collect()
     }
@@ -80,7 +405,145 @@ impl HeisigKanjis {
     }
 }
 
-pub fn get_heisig_kanjis() -> HeisigKanjis {
-    let vec = serde_json::from_str::<Vec<Arc<HeisigKanji>>>(HEISIG_JSON).unwrap();
-    HeisigKanjis::new(vec)
+pub fn get_heisig_kanjis() -> Result<HeisigKanjis, String> {
+    let raw = serde_json::from_str::<Vec<RawHeisigKanji>>(HEISIG_JSON)
+        .map_err(|e| format!("Failed to parse heisig_kanji.json: {}", e))?;
+    let vec = raw
+        .into_iter()
+        .map(|k| HeisigKanji::try_from(k).map(Arc::new))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(HeisigKanjis::new(vec))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw() -> RawHeisigKanji {
+        RawHeisigKanji {
+            id: "1".to_string(),
+            frameNoV4: "1".to_string(),
+            frameNoV6: "1".to_string(),
+            keyword: "one".to_string(),
+            kanji: "一".to_string(),
+            hint: String::new(),
+            constituent: "One".to_string(),
+            strokeCount: "1".to_string(),
+            lessonNo: "1".to_string(),
+            myStory: String::new(),
+            heisigStory: String::new(),
+            heisigComment: String::new(),
+            koohiiStory1: String::new(),
+            koohiiStory2: String::new(),
+            jouYou: "1".to_string(),
+            jlpt: "5".to_string(),
+            onYomi: "イチ、イツ".to_string(),
+            kunYomi: "ひと-、ひと.つ".to_string(),
+            words: String::new(),
+            readingExamples: String::new(),
+        }
+    }
+
+    #[test]
+    fn sanitize_html_keeps_only_allowed_tags_without_attributes() {
+        assert_eq!(
+            sanitize_html("<b onclick=\"steal()\">one</b><script>alert(1)</script>"),
+            "<b>one</b>alert(1)"
+        );
+        assert_eq!(
+            sanitize_html("<img src=x onerror=alert(1)>a<BR/>b <em>c</em>"),
+            "a<br>b <em>c</em>"
+        );
+        assert_eq!(sanitize_html("<a href=\"javascript:x\">link</a>"), "link");
+        assert_eq!(sanitize_html("1 < 2 <i"), "1 &lt; 2 &lt;i");
+    }
+
+    #[test]
+    fn kun_yomi_split_at_the_okurigana_dot() {
+        let readings = Reading::parse_list("ひと.つ、ひと-、-び");
+
+        assert_eq!(
+            readings,
+            [
+                Reading {
+                    stem: "ひと".to_string(),
+                    okurigana: Some("つ".to_string()),
+                    prefix: false,
+                    suffix: false,
+                },
+                Reading {
+                    stem: "ひと".to_string(),
+                    okurigana: None,
+                    prefix: true,
+                    suffix: false,
+                },
+                Reading {
+                    stem: "び".to_string(),
+                    okurigana: None,
+                    prefix: false,
+                    suffix: true,
+                },
+            ]
+        );
+        assert_eq!(readings[0].to_kana(), "ひとつ");
+        assert_eq!(readings[0].to_string(), "ひと.つ");
+    }
+
+    #[test]
+    fn words_are_separated_by_br() {
+        let mut raw = raw();
+        raw.words =
+            "一々(いちいち): one by one<br>一見(<b>いちげん</b>): unfamiliar<br>broken".to_string();
+        raw.readingExamples = "一つ (ひと.つ), 一 (イチ)".to_string();
+        let kanji = HeisigKanji::try_from(raw).unwrap();
+
+        assert_eq!(
+            kanji.words,
+            [
+                ExampleWord {
+                    word: "一々".to_string(),
+                    reading: "いちいち".to_string(),
+                    meaning: "one by one".to_string(),
+                },
+                ExampleWord {
+                    word: "一見".to_string(),
+                    reading: "いちげん".to_string(),
+                    meaning: "unfamiliar".to_string(),
+                },
+            ]
+        );
+        assert_eq!(kanji.reading_examples.len(), 2);
+        assert_eq!(kanji.reading_examples[0].reading.to_kana(), "ひとつ");
+        assert_eq!(kanji.jou_you, Some(JouYou::Grade(1)));
+        assert_eq!(kanji.constituents, ["One"]);
+    }
+
+    #[test]
+    fn bad_numbers_are_errors() {
+        let mut bad_frame = raw();
+        bad_frame.frameNoV4 = "one".to_string();
+        let mut missing_frame = raw();
+        missing_frame.frameNoV6 = String::new();
+        let mut bad_strokes = raw();
+        bad_strokes.strokeCount = "-1".to_string();
+        let mut bad_grade = raw();
+        bad_grade.jouYou = "first".to_string();
+
+        for raw in [bad_frame, missing_frame, bad_strokes, bad_grade] {
+            assert!(HeisigKanji::try_from(raw).is_err());
+        }
+    }
+
+    #[test]
+    fn empty_optional_numbers_are_none() {
+        let mut raw = raw();
+        raw.strokeCount = " ".to_string();
+        raw.jlpt = String::new();
+        raw.jouYou = String::new();
+        let kanji = HeisigKanji::try_from(raw).unwrap();
+
+        assert_eq!(kanji.stroke_count, None);
+        assert_eq!(kanji.jlpt, None);
+        assert_eq!(kanji.jou_you, None);
+    }
 }
@@ -35,6 +35,10 @@ impl Kanjidic2 {
         Kanjidic2 { entries }
     }
 
+    pub fn entries(&self) -> &[Kanjidic2Entry] {
+        &self.entries
+    }
+
     pub fn find_by_kanji(&self, kanji: &String) -> Option<Kanjidic2Entry> {
         for entry in self.entries.iter() {
             if &entry.literal == kanji {
@@ -52,4 +56,10 @@ pub struct Kanjidic2Entry {
     pub ja_kun: String,
     pub heisig: String,
     pub heisig6: String,
+    /// Classical (Kangxi) radical number. Missing in CSVs exported before the
+    /// column was added.
+    #[serde(default)]
+    pub radical: Option<u32>,
+    #[serde(default)]
+    pub stroke_count: Option<u32>,
 }
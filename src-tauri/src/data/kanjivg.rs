@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+use tauri::path::BaseDirectory;
+
+/// KanjiVG SVGs, one file per kanji named after its zero padded lowercase
+/// codepoint (`04e00.svg` for 一).
+const KANJIVG_DIR: &str = "data/kanjivg";
+/// KanjiVG draws on a 109x109 canvas.
+pub const KANJIVG_SIZE: u32 = 109;
+
+pub fn setup(app: &tauri::App) {
+    let dir = app
+        .path()
+        .resolve(KANJIVG_DIR, BaseDirectory::Resource)
+        .ok()
+        .filter(|dir| dir.is_dir());
+    if dir.is_none() {
+        eprintln!("KanjiVG data not found, stroke order is unavailable");
+    }
+    app.manage(KanjiVg {
+        dir,
+        cache: Mutex::new(HashMap::new()),
+    });
+}
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct KanjiVgStroke {
+    /// SVG path data in KanjiVG coordinates.
+    pub path: String,
+    /// Where KanjiVG places the stroke number label.
+    pub number_position: Option<(f64, f64)>,
+}
+
+/// Files are read on first use, there are thousands of them and a session
+/// only looks at a few.
+pub struct KanjiVg {
+    dir: Option<PathBuf>,
+    cache: Mutex<HashMap<char, Arc<Vec<KanjiVgStroke>>>>,
+}
+
+impl KanjiVg {
+    /// Strokes of `kanji` in writing order.
+    pub fn strokes(&self, kanji: char) -> Result<Arc<Vec<KanjiVgStroke>>, String> {
+        if let Some(strokes) = self.cache.lock().ok().and_then(|c| c.get(&kanji).cloned()) {
+            return Ok(strokes);
+        }
+
+        let strokes = Arc::new(self.read_strokes(kanji)?);
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(kanji, strokes.clone());
+        }
+        Ok(strokes)
+    }
+
+    /// Like `strokes`, but bypassing the cache, for reading every file once.
+    pub fn read_strokes(&self, kanji: char) -> Result<Vec<KanjiVgStroke>, String> {
+        let dir = self
+            .dir
+            .as_ref()
+            .ok_or("KanjiVG data is not available".to_string())?;
+        let path = dir.join(format!("{:05x}.svg", kanji as u32));
+        let svg = std::fs::read_to_string(&path)
+            .map_err(|_| format!("No stroke order data for {}", kanji))?;
+        let strokes = parse_strokes(&svg);
+        if strokes.is_empty() {
+            return Err(format!("No strokes found in KanjiVG data for {}", kanji));
+        }
+        Ok(strokes)
+    }
+}
+
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let start = element.find(&format!(" {}=\"", name))? + name.len() + 3;
+    let length = element[start..].find('"')?;
+    Some(&element[start..start + length])
+}
+
+/// Stroke paths have ids like `kvg:04e00-s1`, the number after `-s` being the
+/// stroke's position in writing order.
+fn stroke_number(id: &str) -> Option<u32> {
+    id.rsplit_once("-s")?.1.parse().ok()
+}
+
+/// `matrix(1 0 0 1 4.25 50.13)` → `(4.25, 50.13)`.
+fn translation(transform: &str) -> Option<(f64, f64)> {
+    let values = transform
+        .strip_prefix("matrix(")?
+        .strip_suffix(')')?
+        .split_whitespace()
+        .map(|v| v.parse::<f64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    match values.as_slice() {
+        [_, _, _, _, x, y] => Some((*x, *y)),
+        _ => None,
+    }
+}
+
+/// Pulls the stroke paths and number labels out of a KanjiVG file. The files
+/// are machine generated and regular enough not to need an XML parser.
+pub fn parse_strokes(svg: &str) -> Vec<KanjiVgStroke> {
+    let mut paths = Vec::new();
+    for element in svg.split("<path").skip(1) {
+        let element = element.split('>').next().unwrap_or_default();
+        let (Some(number), Some(d)) = (
+            attribute(element, "id").and_then(stroke_number),
+            attribute(element, "d"),
+        ) else {
+            continue;
+        };
+        paths.push((number, d.to_string()));
+    }
+    paths.sort_by_key(|(number, _)| *number);
+
+    // Labels are `<text transform="...">n</text>`, n counting from 1.
+    let mut number_positions = HashMap::new();
+    for element in svg.split("<text").skip(1) {
+        let Some((tag, rest)) = element.split_once('>') else {
+            continue;
+        };
+        let label = rest.split('<').next().unwrap_or_default().trim();
+        if let (Ok(number), Some(position)) = (
+            label.parse::<u32>(),
+            attribute(tag, "transform").and_then(translation),
+        ) {
+            number_positions.insert(number, position);
+        }
+    }
+
+    paths
+        .into_iter()
+        .map(|(number, path)| KanjiVgStroke {
+            path,
+            number_position: number_positions.get(&number).copied(),
+        })
+        .collect()
+}
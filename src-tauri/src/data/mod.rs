@@ -2,9 +2,24 @@ use tauri::Manager;
 
 pub mod heisig_kanji;
 pub mod kanjidic2;
+pub mod kanjivg;
+pub mod pitch_accent;
+pub mod sentences;
+pub mod similar_kanji;
+pub mod word_frequency;
 
 pub fn setup(tauri_app: &tauri::App) {
-    tauri_app.manage(heisig_kanji::get_heisig_kanjis());
+    match heisig_kanji::get_heisig_kanjis() {
+        Ok(heisig_kanjis) => {
+            tauri_app.manage(heisig_kanjis);
+        }
+        Err(e) => {
+            // Managed empty anyway, the commands using it look it up
+            // unconditionally.
+            eprintln!("Failed to load Heisig kanji: {}", e);
+            tauri_app.manage(heisig_kanji::HeisigKanjis::default());
+        }
+    }
     let res = kanjidic2::parse_kanjidic2(tauri_app);
     if let Ok(kanjidic2_reader) = res {
         println!("Successfully parsed kanjidic2.csv");
@@ -12,6 +27,11 @@ pub fn setup(tauri_app: &tauri::App) {
     } else if let Err(e) = res {
         eprintln!("Failed to parse kanjidic2.csv: {}", e);
     }
+    similar_kanji::setup(tauri_app);
+    kanjivg::setup(tauri_app);
+    pitch_accent::setup(tauri_app);
+    word_frequency::setup(tauri_app);
+    sentences::setup(tauri_app);
 }
 
 pub fn get_heisig_kanjis(
@@ -25,3 +45,31 @@ pub fn get_kanjidic2_entries_reader(
 ) -> tauri::State<kanjidic2::Kanjidic2State> {
     tauri_app_handle.state::<kanjidic2::Kanjidic2State>()
 }
+
+pub fn get_kanjivg(tauri_app_handle: &tauri::AppHandle) -> tauri::State<kanjivg::KanjiVg> {
+    tauri_app_handle.state::<kanjivg::KanjiVg>()
+}
+
+pub fn get_sentence_corpus(
+    tauri_app_handle: &tauri::AppHandle,
+) -> tauri::State<sentences::SentenceCorpusState> {
+    tauri_app_handle.state::<sentences::SentenceCorpusState>()
+}
+
+pub fn get_pitch_accents(
+    tauri_app_handle: &tauri::AppHandle,
+) -> tauri::State<pitch_accent::PitchAccents> {
+    tauri_app_handle.state::<pitch_accent::PitchAccents>()
+}
+
+pub fn get_word_frequencies(
+    tauri_app_handle: &tauri::AppHandle,
+) -> tauri::State<word_frequency::WordFrequencies> {
+    tauri_app_handle.state::<word_frequency::WordFrequencies>()
+}
+
+pub fn get_similar_kanji(
+    tauri_app_handle: &tauri::AppHandle,
+) -> tauri::State<similar_kanji::SimilarKanjiIndex> {
+    tauri_app_handle.state::<similar_kanji::SimilarKanjiIndex>()
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use tauri::Manager;
+use tauri::path::BaseDirectory;
+
+/// Kanjium `accents.txt`: tab separated `word, reading, accents` rows where
+/// `accents` is a comma separated list of downstep positions, optionally
+/// prefixed with a part of speech in parentheses (`(名)0,(副)1`).
+const PITCH_ACCENTS_FILE: &str = "data/accents.txt";
+
+pub fn setup(app: &tauri::App) {
+    match parse_pitch_accents(app) {
+        Ok(pitch_accents) => {
+            println!("Successfully parsed accents.txt");
+            app.manage(pitch_accents);
+        }
+        Err(e) => {
+            eprintln!("Failed to parse accents.txt: {}", e);
+            app.manage(PitchAccents::default());
+        }
+    }
+}
+
+fn parse_pitch_accents(app: &tauri::App) -> Result<PitchAccents, String> {
+    let pitch_accents_path = app
+        .path()
+        .resolve(PITCH_ACCENTS_FILE, BaseDirectory::Resource)
+        .map_err(|e| e.to_string())?;
+    let file = File::open(&pitch_accents_path).map_err(|e| e.to_string())?;
+    Ok(PitchAccents::new(file))
+}
+
+#[derive(Default)]
+pub struct PitchAccents {
+    word_to_accents: HashMap<String, Vec<PitchAccent>>,
+}
+
+impl PitchAccents {
+    pub fn new(file: File) -> Self {
+        let mut word_to_accents: HashMap<String, Vec<PitchAccent>> = HashMap::new();
+        for line in BufReader::new(file).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    eprintln!("Error reading accents.txt: {}", e);
+                    continue;
+                }
+            };
+            let mut columns = line.split('\t');
+            let (Some(word), Some(reading), Some(accents)) =
+                (columns.next(), columns.next(), columns.next())
+            else {
+                continue;
+            };
+            // Kana-only words leave the reading column empty.
+            let reading = if reading.is_empty() { word } else { reading };
+
+            let word_accents = word_to_accents.entry(word.to_string()).or_default();
+            for accent in accents_from_str(accents) {
+                word_accents.push(PitchAccent {
+                    reading: reading.to_string(),
+                    accent,
+                });
+            }
+        }
+        PitchAccents { word_to_accents }
+    }
+
+    pub fn find_by_word(&self, word: &str) -> Vec<PitchAccent> {
+        self.word_to_accents.get(word).cloned().unwrap_or_default()
+    }
+
+    pub fn find(&self, word: &str, reading: &str) -> Vec<PitchAccent> {
+        let reading = wana_kana::ConvertJapanese::to_hiragana(reading);
+        self.find_by_word(word)
+            .into_iter()
+            .filter(|a| wana_kana::ConvertJapanese::to_hiragana(a.reading.as_str()) == reading)
+            .collect()
+    }
+}
+
+fn accents_from_str(accents: &str) -> Vec<u8> {
+    let mut result = Vec::new();
+    for accent in accents.split(',') {
+        let accent = match accent.rsplit_once(')') {
+            Some((_, accent)) => accent,
+            None => accent,
+        };
+        if let Ok(accent) = accent.trim().parse::<u8>() {
+            if !result.contains(&accent) {
+                result.push(accent);
+            }
+        }
+    }
+    result
+}
+
+/// `accent` is the mora after which the pitch drops, 0 meaning no drop (heiban).
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+pub struct PitchAccent {
+    pub reading: String,
+    pub accent: u8,
+}
@@ -0,0 +1,176 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use tauri::Manager;
+use tauri::async_runtime::RwLock;
+use tauri::path::BaseDirectory;
+
+use crate::tokenizer::TokenizerState;
+
+/// Tatoeba (Tanaka corpus) Japanese–English pairs, exported as tab separated
+/// `jpn_id, japanese, eng_id, english` rows.
+const SENTENCES_FILE: &str = "data/sentences.tsv";
+
+pub fn setup(app: &tauri::App) {
+    app.manage(SentenceCorpusState(RwLock::new(SentenceCorpus::default())));
+
+    // Tokenizing the whole corpus takes a while, so the index is built in the
+    // background and lookups return nothing until it is ready.
+    let app_handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let loader_handle = app_handle.clone();
+        let corpus = tauri::async_runtime::spawn_blocking(move || parse_sentences(&loader_handle))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|corpus| corpus);
+        match corpus {
+            Ok(corpus) => {
+                println!("Indexed {} example sentences", corpus.len());
+                let state = app_handle.state::<SentenceCorpusState>();
+                *state.0.write().await = corpus;
+            }
+            Err(e) => eprintln!("Failed to load example sentences: {}", e),
+        }
+    });
+}
+
+fn parse_sentences(app: &tauri::AppHandle) -> Result<SentenceCorpus, String> {
+    let sentences_path = app
+        .path()
+        .resolve(SENTENCES_FILE, BaseDirectory::Resource)
+        .map_err(|e| e.to_string())?;
+    let file = File::open(&sentences_path).map_err(|e| e.to_string())?;
+    let tokenizer = crate::tokenizer::get_tokenizer(app);
+    SentenceCorpus::new(file, &tokenizer)
+}
+
+pub struct SentenceCorpusState(pub RwLock<SentenceCorpus>);
+
+#[derive(Default)]
+pub struct SentenceCorpus {
+    sentences: Vec<IndexedSentence>,
+    lemma_index: HashMap<String, Vec<usize>>,
+}
+
+struct IndexedSentence {
+    sentence: ExampleSentence,
+    lemmas: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct SentencePairRecord {
+    japanese_id: String,
+    japanese: String,
+    english_id: String,
+    english: String,
+}
+
+impl SentenceCorpus {
+    pub fn new(file: File, tokenizer: &TokenizerState) -> Result<Self, String> {
+        let mut rdr = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(false)
+            .quoting(false)
+            .from_reader(file);
+
+        let mut corpus = SentenceCorpus::default();
+        let mut seen = HashSet::new();
+        for result in rdr.deserialize::<SentencePairRecord>() {
+            let record = match result {
+                Ok(record) => record,
+                Err(e) => {
+                    eprintln!("Error reading sentences TSV: {}", e);
+                    continue;
+                }
+            };
+            // The export lists a Japanese sentence once per English translation,
+            // the first one is enough.
+            if !seen.insert(record.japanese_id.clone()) {
+                continue;
+            }
+
+            let tokens = match tokenizer.blocking_tokenize_uncached(&record.japanese) {
+                Ok(tokens) => tokens,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    continue;
+                }
+            };
+            let lemmas = tokens
+                .iter()
+                .filter(|t| !t.is_symbol())
+                .map(|t| t.lemma_or_text().to_string())
+                .collect::<Vec<_>>();
+
+            let index = corpus.sentences.len();
+            for lemma in lemmas.iter().collect::<HashSet<_>>() {
+                corpus
+                    .lemma_index
+                    .entry(lemma.clone())
+                    .or_default()
+                    .push(index);
+            }
+            corpus.sentences.push(IndexedSentence {
+                sentence: ExampleSentence {
+                    id: record.japanese_id,
+                    japanese: record.japanese,
+                    english_id: record.english_id,
+                    english: record.english,
+                },
+                lemmas,
+            });
+        }
+
+        Ok(corpus)
+    }
+
+    pub fn len(&self) -> usize {
+        self.sentences.len()
+    }
+
+    /// Returns up to `limit` sentences containing any of `lemmas`. Sentences
+    /// with the fewest words outside of `known_words` come first, shorter ones
+    /// win ties.
+    pub fn find(
+        &self,
+        lemmas: &[String],
+        known_words: &HashSet<String>,
+        limit: usize,
+    ) -> Vec<ExampleSentence> {
+        let mut indices = lemmas
+            .iter()
+            .filter_map(|lemma| self.lemma_index.get(lemma))
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut candidates = indices
+            .into_iter()
+            .map(|i| &self.sentences[i])
+            .map(|s| {
+                let unknown = s
+                    .lemmas
+                    .iter()
+                    .filter(|l| !lemmas.contains(*l) && !known_words.contains(*l))
+                    .count();
+                (unknown, s.lemmas.len(), s)
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(unknown, length, _)| (*unknown, *length));
+
+        candidates
+            .into_iter()
+            .take(limit)
+            .map(|(_, _, s)| s.sentence.clone())
+            .collect()
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, specta::Type)]
+pub struct ExampleSentence {
+    pub id: String,
+    pub japanese: String,
+    pub english_id: String,
+    pub english: String,
+}
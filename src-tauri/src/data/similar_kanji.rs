@@ -0,0 +1,248 @@
+use std::collections::{HashMap, HashSet};
+
+use tauri::Manager;
+
+use super::heisig_kanji::{HeisigKanji, HeisigKanjis, story_form};
+use super::kanjidic2::{Kanjidic2, Kanjidic2State};
+
+/// Per kanji, only the most confusable ones are kept.
+const MAX_SIMILAR: usize = 8;
+const MIN_SCORE: f64 = 0.5;
+/// Kanji further apart than this are told apart at a glance.
+const MAX_STROKE_DIFFERENCE: u32 = 3;
+const SAME_RADICAL_BONUS: f64 = 0.25;
+const STROKE_DIFFERENCE_PENALTY: f64 = 0.1;
+/// Look-alikes Heisig builds from differently named primitives, so they share
+/// no component.
+const LOOK_ALIKES: [&[&str]; 16] = [
+    &["土", "士"],
+    &["己", "巳"],
+    &["人", "入", "八"],
+    &["大", "犬", "太"],
+    &["王", "玉", "主"],
+    &["刀", "力"],
+    &["千", "干"],
+    &["牛", "午"],
+    &["日", "曰"],
+    &["天", "夫"],
+    &["矢", "失"],
+    &["鳥", "烏"],
+    &["貝", "見"],
+    &["白", "自"],
+    &["田", "由", "甲", "申"],
+    &["右", "石"],
+];
+const LOOK_ALIKE_SCORE: f64 = 1.0;
+
+pub fn setup(app: &tauri::App) {
+    let index = match app.try_state::<HeisigKanjis>() {
+        Some(heisig_kanjis) => {
+            let kanjidic2 = app.try_state::<Kanjidic2State>();
+            // Nothing else holds the lock during setup.
+            let kanjidic2 = kanjidic2.as_ref().and_then(|state| state.0.try_read().ok());
+            SimilarKanjiIndex::new(&heisig_kanjis, kanjidic2.as_deref())
+        }
+        None => SimilarKanjiIndex::default(),
+    };
+    app.manage(index);
+}
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct SimilarKanji {
+    pub kanji: String,
+    pub score: f64,
+}
+
+struct Shape {
+    kanji: String,
+    components: HashSet<String>,
+    radical: Option<u32>,
+    stroke_count: Option<u32>,
+}
+
+/// Confusable kanji (未/末, 待/持) computed from shared Heisig components,
+/// KANJIDIC2 radicals and stroke counts.
+#[derive(Default)]
+pub struct SimilarKanjiIndex {
+    similar: HashMap<String, Vec<SimilarKanji>>,
+}
+
+impl SimilarKanjiIndex {
+    pub fn new(heisig_kanjis: &HeisigKanjis, kanjidic2: Option<&Kanjidic2>) -> Self {
+        let kanjidic2_entries = kanjidic2
+            .map(|k| {
+                k.entries()
+                    .iter()
+                    .map(|e| (e.literal.as_str(), e))
+                    .collect::<HashMap<_, _>>()
+            })
+            .unwrap_or_default();
+
+        let shapes = heisig_kanjis
+            .get_all()
+            .iter()
+            .map(|k| {
+                let entry = kanjidic2_entries.get(k.kanji.as_str());
+                Shape {
+                    kanji: k.kanji.clone(),
+                    components: components(k),
+                    radical: entry.and_then(|e| e.radical),
+                    stroke_count: k.stroke_count.or(entry.and_then(|e| e.stroke_count)),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut by_component: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, shape) in shapes.iter().enumerate() {
+            for component in &shape.components {
+                by_component.entry(component.as_str()).or_default().push(i);
+            }
+        }
+
+        let mut similar: HashMap<String, Vec<SimilarKanji>> = HashMap::new();
+        for (i, shape) in shapes.iter().enumerate() {
+            let candidates = shape
+                .components
+                .iter()
+                .flat_map(|c| by_component[c.as_str()].iter().copied())
+                .filter(|j| *j != i)
+                .collect::<HashSet<_>>();
+            let scored = candidates
+                .into_iter()
+                .filter_map(|j| {
+                    similarity(shape, &shapes[j]).map(|score| SimilarKanji {
+                        kanji: shapes[j].kanji.clone(),
+                        score,
+                    })
+                })
+                .collect::<Vec<_>>();
+            if !scored.is_empty() {
+                similar.insert(shape.kanji.clone(), scored);
+            }
+        }
+
+        let known = shapes
+            .iter()
+            .map(|s| s.kanji.as_str())
+            .collect::<HashSet<_>>();
+        for group in LOOK_ALIKES {
+            for kanji in group.iter().filter(|k| known.contains(*k)) {
+                let scored = similar.entry(kanji.to_string()).or_default();
+                for other in group.iter().filter(|o| *o != kanji && known.contains(*o)) {
+                    match scored.iter_mut().find(|s| s.kanji == *other) {
+                        Some(s) => s.score = s.score.max(LOOK_ALIKE_SCORE),
+                        None => scored.push(SimilarKanji {
+                            kanji: other.to_string(),
+                            score: LOOK_ALIKE_SCORE,
+                        }),
+                    }
+                }
+            }
+        }
+
+        for scored in similar.values_mut() {
+            scored.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.kanji.cmp(&b.kanji)));
+            scored.truncate(MAX_SIMILAR);
+        }
+
+        SimilarKanjiIndex { similar }
+    }
+
+    /// Most similar first.
+    pub fn get(&self, kanji: &str) -> &[SimilarKanji] {
+        self.similar.get(kanji).map(|s| s.as_slice()).unwrap_or(&[])
+    }
+
+    /// Zero when `other` is not among the kanji similar to `kanji`.
+    pub fn score(&self, kanji: &str, other: &str) -> f64 {
+        self.get(kanji)
+            .iter()
+            .find(|s| s.kanji == other)
+            .map(|s| s.score)
+            .unwrap_or(0.0)
+    }
+}
+
+/// The primitives a kanji is built from, by Heisig name. The constituents
+/// start with the kanji's own keyword, which says nothing about its shape and
+/// is left out, unless the kanji is a primitive itself. Names are compared
+/// lowercased and without the digits numbering variants (moon2).
+pub fn components(kanji: &HeisigKanji) -> HashSet<String> {
+    let keyword = story_form(&kanji.keyword);
+    let components = kanji
+        .constituents
+        .iter()
+        .map(|c| story_form(c))
+        .filter(|c| !c.is_empty() && *c != keyword)
+        .collect::<HashSet<_>>();
+    if components.is_empty() {
+        HashSet::from([keyword])
+    } else {
+        components
+    }
+}
+
+/// Share of the smaller component set found in the other one, so 待 and 持
+/// (street + temple, fingers + temple) score 0.5 before radical and stroke
+/// adjustments.
+fn similarity(a: &Shape, b: &Shape) -> Option<f64> {
+    let stroke_difference = match (a.stroke_count, b.stroke_count) {
+        (Some(a), Some(b)) => a.abs_diff(b),
+        _ => 0,
+    };
+    if stroke_difference > MAX_STROKE_DIFFERENCE {
+        return None;
+    }
+
+    let shared = a.components.intersection(&b.components).count();
+    let smaller = a.components.len().min(b.components.len());
+    if shared == 0 || smaller == 0 {
+        return None;
+    }
+
+    let mut score = shared as f64 / smaller as f64;
+    if a.radical.is_some() && a.radical == b.radical {
+        score += SAME_RADICAL_BONUS;
+    }
+    score -= STROKE_DIFFERENCE_PENALTY * stroke_difference as f64;
+
+    (score >= MIN_SCORE).then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::heisig_kanji::get_heisig_kanjis;
+
+    fn similar_to(index: &SimilarKanjiIndex, kanji: &str) -> Vec<String> {
+        index.get(kanji).iter().map(|s| s.kanji.clone()).collect()
+    }
+
+    #[test]
+    fn finds_confusable_kanji() {
+        let heisig_kanjis = get_heisig_kanjis().unwrap();
+        let index = SimilarKanjiIndex::new(&heisig_kanjis, None);
+
+        assert!(similar_to(&index, "土").contains(&"士".to_string()));
+        assert!(similar_to(&index, "士").contains(&"土".to_string()));
+        assert!(similar_to(&index, "未").contains(&"末".to_string()));
+        assert!(similar_to(&index, "末").contains(&"未".to_string()));
+    }
+
+    #[test]
+    fn own_keyword_is_not_a_component() {
+        let heisig_kanjis = get_heisig_kanjis().unwrap();
+        let not_yet = heisig_kanjis
+            .get_all()
+            .iter()
+            .find(|k| k.kanji == "未")
+            .unwrap();
+        let extremity = heisig_kanjis
+            .get_all()
+            .iter()
+            .find(|k| k.kanji == "末")
+            .unwrap();
+
+        assert_eq!(components(not_yet), components(extremity));
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use tauri::Manager;
+use tauri::path::BaseDirectory;
+
+/// Optional corpus frequency list, one word per line (optionally followed by
+/// a tab and its count), most frequent first.
+const WORD_FREQUENCY_FILE: &str = "data/word_frequency.tsv";
+
+pub fn setup(app: &tauri::App) {
+    match parse_word_frequencies(app) {
+        Ok(word_frequencies) => {
+            println!("Successfully parsed word_frequency.tsv");
+            app.manage(word_frequencies);
+        }
+        Err(e) => {
+            // The list is optional, JMdict priority tags are used on their own.
+            eprintln!("Failed to parse word_frequency.tsv: {}", e);
+            app.manage(WordFrequencies::default());
+        }
+    }
+}
+
+fn parse_word_frequencies(app: &tauri::App) -> Result<WordFrequencies, String> {
+    let word_frequency_path = app
+        .path()
+        .resolve(WORD_FREQUENCY_FILE, BaseDirectory::Resource)
+        .map_err(|e| e.to_string())?;
+    let file = File::open(&word_frequency_path).map_err(|e| e.to_string())?;
+    Ok(WordFrequencies::new(file))
+}
+
+#[derive(Default)]
+pub struct WordFrequencies {
+    ranks: HashMap<String, u32>,
+}
+
+impl WordFrequencies {
+    pub fn new(file: File) -> Self {
+        let mut ranks = HashMap::new();
+        let mut rank = 0;
+        for line in BufReader::new(file).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    eprintln!("Error reading word_frequency.tsv: {}", e);
+                    continue;
+                }
+            };
+            let word = line.split('\t').next().unwrap_or_default().trim();
+            if word.is_empty() || word.starts_with('#') {
+                continue;
+            }
+            rank += 1;
+            ranks.entry(word.to_string()).or_insert(rank);
+        }
+        WordFrequencies { ranks }
+    }
+
+    /// 1-based rank of `word` in the corpus list.
+    pub fn corpus_rank(&self, word: &str) -> Option<u32> {
+        self.ranks.get(word).copied()
+    }
+}
@@ -0,0 +1,324 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use tauri::{Manager, async_runtime::RwLock};
+
+use crate::data::heisig_kanji::{HeisigKanji, HeisigKanjis};
+use crate::data::kanjivg::{KanjiVg, KanjiVgStroke};
+
+pub mod recognizer;
+pub mod svg_path;
+
+use recognizer::{CharacterFeatures, Point};
+
+/// Kanji whose stroke count differs more than this from the drawing are not
+/// compared, users rarely miss or add more than a stroke or two.
+const MAX_STROKE_COUNT_DIFFERENCE: usize = 2;
+
+pub fn setup(app: &tauri::App) {
+    app.manage(HandwritingState(RwLock::new(TemplateIndex::default())));
+
+    // Reading and resampling every KanjiVG file takes a moment, nothing is
+    // recognized until it is done.
+    let app_handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let loader_handle = app_handle.clone();
+        let index = tauri::async_runtime::spawn_blocking(move || {
+            let heisig_kanjis = crate::data::get_heisig_kanjis(&loader_handle);
+            let kanjivg = crate::data::get_kanjivg(&loader_handle);
+            TemplateIndex::new(&heisig_kanjis, &kanjivg)
+        })
+        .await;
+        match index {
+            Ok(index) => {
+                let state = app_handle.state::<HandwritingState>();
+                *state.0.write().await = index;
+            }
+            Err(e) => eprintln!("Failed to build handwriting templates: {}", e),
+        }
+    });
+}
+
+pub struct HandwritingState(pub RwLock<TemplateIndex>);
+
+/// Features of every Heisig kanji KanjiVG has, by their stroke count.
+#[derive(Default)]
+pub struct TemplateIndex {
+    by_stroke_count: BTreeMap<usize, Vec<(Arc<HeisigKanji>, CharacterFeatures)>>,
+}
+
+impl TemplateIndex {
+    pub fn new(heisig_kanjis: &HeisigKanjis, kanjivg: &KanjiVg) -> Self {
+        let mut index = TemplateIndex::default();
+        for kanji in heisig_kanjis.get_all() {
+            let Some(character) = kanji.kanji.chars().next() else {
+                continue;
+            };
+            // Kanji missing from KanjiVG simply can't be recognized.
+            if let Ok(strokes) = kanjivg.read_strokes(character) {
+                index.insert(
+                    kanji.clone(),
+                    CharacterFeatures::new(&template_strokes(&strokes)),
+                );
+            }
+        }
+        index
+    }
+
+    pub fn insert(&mut self, kanji: Arc<HeisigKanji>, features: CharacterFeatures) {
+        self.by_stroke_count
+            .entry(features.stroke_count())
+            .or_default()
+            .push((kanji, features));
+    }
+
+    /// Templates with a stroke count close enough to `stroke_count` to be
+    /// worth comparing.
+    pub fn near(
+        &self,
+        stroke_count: usize,
+    ) -> impl Iterator<Item = (&Arc<HeisigKanji>, &CharacterFeatures)> {
+        let range = stroke_count.saturating_sub(MAX_STROKE_COUNT_DIFFERENCE)
+            ..=stroke_count + MAX_STROKE_COUNT_DIFFERENCE;
+        self.by_stroke_count
+            .range(range)
+            .flat_map(|(_, templates)| templates.iter().map(|(kanji, features)| (kanji, features)))
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct HandwritingCandidate {
+    pub kanji: String,
+    pub keyword: String,
+    /// Lower is closer.
+    pub distance: f64,
+}
+
+/// KanjiVG strokes as point lists in KanjiVG coordinates. These are the
+/// templates drawings are matched against, and fed back into the recognizer
+/// they make perfect synthetic drawings.
+pub fn template_strokes(strokes: &[KanjiVgStroke]) -> Vec<Vec<Point>> {
+    strokes
+        .iter()
+        .map(|s| svg_path::path_points(&s.path))
+        .collect()
+}
+
+/// Kanji matching a drawing, best first. `strokes` are point lists in canvas
+/// coordinates, in the order they were drawn.
+#[tauri::command]
+#[specta::specta]
+pub async fn recognize_handwriting(
+    state: tauri::State<'_, HandwritingState>,
+    strokes: Vec<Vec<Point>>,
+    limit: u32,
+) -> Result<Vec<HandwritingCandidate>, String> {
+    let drawing = CharacterFeatures::new(&strokes);
+    if drawing.stroke_count() == 0 {
+        return Ok(vec![]);
+    }
+
+    let templates = state.0.read().await;
+    Ok(
+        recognizer::rank(&drawing, templates.near(drawing.stroke_count()))
+            .into_iter()
+            .take(limit as usize)
+            .map(|(kanji, distance)| HandwritingCandidate {
+                kanji: kanji.kanji.clone(),
+                keyword: kanji.keyword.clone(),
+                distance,
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::heisig_kanji::get_heisig_kanjis;
+    use crate::data::kanjivg::parse_strokes;
+
+    /// Strokes hand traced in KanjiVG's format and 109x109 coordinates, for
+    /// kanji that are easily mistaken for one another.
+    const TEMPLATES: [(&str, &[&str]); 11] = [
+        ("一", &["M12,55c20,-1,60,-2,85,-1"]),
+        (
+            "二",
+            &["M26,34c15,0,45,-1,57,-2", "M14,79c25,0,60,-1,81,-2"],
+        ),
+        (
+            "三",
+            &[
+                "M22,22c20,0,45,-1,63,-2",
+                "M29,53c15,0,35,-1,49,-2",
+                "M13,88c25,0,60,-1,83,-2",
+            ],
+        ),
+        ("十", &["M12,52c25,0,60,-1,85,-2", "M53,14c0,25,0,60,0,85"]),
+        (
+            "土",
+            &[
+                "M26,42c15,0,40,-1,57,-2",
+                "M54,14c0,25,0,55,0,70",
+                "M12,85c25,0,60,-1,85,-2",
+            ],
+        ),
+        (
+            "士",
+            &[
+                "M12,42c25,0,60,-1,85,-2",
+                "M54,14c0,25,0,55,0,70",
+                "M28,85c15,0,35,-1,52,-2",
+            ],
+        ),
+        (
+            "干",
+            &[
+                "M22,24c20,0,45,-1,63,-2",
+                "M12,55c25,0,60,-1,85,-2",
+                "M54,24c0,25,0,55,0,72",
+            ],
+        ),
+        (
+            "王",
+            &[
+                "M20,23c20,0,48,-1,68,-2",
+                "M25,54c15,0,38,-1,55,-2",
+                "M54,23c0,22,0,50,0,64",
+                "M13,88c25,0,60,-1,84,-2",
+            ],
+        ),
+        (
+            "木",
+            &[
+                "M14,38c25,0,60,-1,82,-2",
+                "M54,12c0,30,0,65,0,85",
+                "M53,39c-8,18,-22,35,-38,45",
+                "M56,40c8,16,20,30,36,40",
+            ],
+        ),
+        (
+            "口",
+            &[
+                "M22,26c0,20,0,45,0,62",
+                "M22,27c20,-1,45,-2,64,-2c1,20,1,45,0,60",
+                "M24,86c20,0,40,0,60,-1",
+            ],
+        ),
+        (
+            "日",
+            &[
+                "M27,17c0,28,0,60,0,78",
+                "M28,18c18,-1,36,-2,53,-2c1,28,1,58,0,76",
+                "M29,54c16,0,34,0,50,-1",
+                "M29,92c16,0,34,0,50,-1",
+            ],
+        ),
+    ];
+
+    fn kanjivg_svg(kanji: &str, paths: &[&str]) -> String {
+        let codepoint = kanji.chars().next().unwrap() as u32;
+        paths
+            .iter()
+            .enumerate()
+            .map(|(i, d)| {
+                format!(
+                    "<path id=\"kvg:{:05x}-s{}\" d=\"{}\"/>\n",
+                    codepoint,
+                    i + 1,
+                    d
+                )
+            })
+            .collect()
+    }
+
+    fn templates() -> Vec<(&'static str, Vec<Vec<Point>>)> {
+        TEMPLATES
+            .iter()
+            .map(|(kanji, paths)| {
+                let strokes = parse_strokes(&kanjivg_svg(kanji, paths));
+                assert_eq!(strokes.len(), paths.len());
+                (*kanji, template_strokes(&strokes))
+            })
+            .collect()
+    }
+
+    /// The template as a user would draw it on a canvas: three times as big,
+    /// shifted and with a shaky hand.
+    fn drawing(strokes: &[Vec<Point>]) -> Vec<Vec<Point>> {
+        strokes
+            .iter()
+            .enumerate()
+            .map(|(s, stroke)| {
+                stroke
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| {
+                        let wobble = (i * 7 + s * 3) as f64;
+                        Point {
+                            x: p.x * 3.0 + 40.0 + 2.0 * (wobble * 1.3).sin(),
+                            y: p.y * 3.2 + 25.0 + 2.0 * (wobble * 0.7).cos(),
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn template_index(templates: &[(&'static str, Vec<Vec<Point>>)]) -> TemplateIndex {
+        let heisig_kanjis = get_heisig_kanjis().unwrap();
+        let mut index = TemplateIndex::default();
+        for (kanji, strokes) in templates {
+            let heisig_kanji = heisig_kanjis
+                .get_by_kanji(&kanji.to_string())
+                .into_iter()
+                .next()
+                .unwrap();
+            index.insert(heisig_kanji, CharacterFeatures::new(strokes));
+        }
+        index
+    }
+
+    fn best_match(index: &TemplateIndex, drawn: &[Vec<Point>]) -> String {
+        let drawing = CharacterFeatures::new(drawn);
+        let ranked = recognizer::rank(&drawing, index.near(drawing.stroke_count()));
+        ranked[0].0.kanji.clone()
+    }
+
+    #[test]
+    fn recognizes_drawn_templates() {
+        let templates = templates();
+        let index = template_index(&templates);
+        for (kanji, strokes) in &templates {
+            assert_eq!(best_match(&index, &drawing(strokes)), *kanji);
+        }
+    }
+
+    #[test]
+    fn recognizes_drawing_missing_a_stroke() {
+        let templates = templates();
+        let index = template_index(&templates);
+        let (_, tree) = templates.iter().find(|(kanji, _)| *kanji == "木").unwrap();
+        let drawn = drawing(&tree[..tree.len() - 1]);
+
+        assert_eq!(best_match(&index, &drawn), "木");
+    }
+
+    #[test]
+    fn only_close_stroke_counts_are_compared() {
+        let index = template_index(&templates());
+
+        let near = |stroke_count| {
+            index
+                .near(stroke_count)
+                .map(|(kanji, _)| kanji.kanji.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(near(1).contains(&"三"));
+        assert!(!near(1).contains(&"木"));
+        assert!(near(2).contains(&"木"));
+        assert!(near(6).contains(&"木"));
+        assert!(near(7).is_empty());
+    }
+}
@@ -0,0 +1,182 @@
+/// Every stroke is resampled to this many evenly spaced points.
+const STROKE_POINTS: usize = 16;
+/// Added to the distance for every stroke the drawing has too many or too few.
+/// Kept below the distance between unlike strokes, or a drawing missing a
+/// stroke would lose to any kanji with the drawn stroke count.
+const STROKE_COUNT_PENALTY: f64 = 0.15;
+/// Weight of the difference in overall stroke direction, 0..1 before weighting.
+const DIRECTION_WEIGHT: f64 = 0.3;
+/// Weight of the difference in stroke length, relative to the character size.
+const LENGTH_WEIGHT: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    fn distance(&self, other: &Point) -> f64 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+}
+
+/// A stroke reduced to what the matcher compares.
+struct StrokeFeatures {
+    points: Vec<Point>,
+    /// Unit vector from the first to the last point.
+    direction: (f64, f64),
+    length: f64,
+}
+
+/// Strokes of one character, scaled into the unit square.
+pub struct CharacterFeatures {
+    strokes: Vec<StrokeFeatures>,
+}
+
+fn polyline_length(points: &[Point]) -> f64 {
+    points.windows(2).map(|w| w[0].distance(&w[1])).sum()
+}
+
+fn resample(points: &[Point]) -> Vec<Point> {
+    let Some(first) = points.first() else {
+        return vec![];
+    };
+    let length = polyline_length(points);
+    if length == 0.0 {
+        return vec![*first; STROKE_POINTS];
+    }
+
+    let step = length / (STROKE_POINTS - 1) as f64;
+    let mut resampled = vec![*first];
+    let mut walked = 0.0;
+    let mut next = step;
+    for w in points.windows(2) {
+        let segment = w[0].distance(&w[1]);
+        while segment > 0.0 && walked + segment >= next && resampled.len() < STROKE_POINTS {
+            let t = (next - walked) / segment;
+            resampled.push(Point {
+                x: w[0].x + t * (w[1].x - w[0].x),
+                y: w[0].y + t * (w[1].y - w[0].y),
+            });
+            next += step;
+        }
+        walked += segment;
+    }
+    // Rounding can leave the last point out.
+    while resampled.len() < STROKE_POINTS {
+        resampled.push(*points.last().unwrap_or(first));
+    }
+    resampled
+}
+
+impl CharacterFeatures {
+    /// `strokes` in any coordinate system with y pointing down, like a canvas
+    /// or KanjiVG. The character is centered and scaled by its larger side so
+    /// the aspect ratio is kept.
+    pub fn new(strokes: &[Vec<Point>]) -> Self {
+        let all_points = strokes.iter().flatten();
+        let (mut min_x, mut min_y) = (f64::MAX, f64::MAX);
+        let (mut max_x, mut max_y) = (f64::MIN, f64::MIN);
+        for p in all_points {
+            min_x = min_x.min(p.x);
+            min_y = min_y.min(p.y);
+            max_x = max_x.max(p.x);
+            max_y = max_y.max(p.y);
+        }
+        let size = (max_x - min_x).max(max_y - min_y);
+        let scale = if size > 0.0 { size } else { 1.0 };
+        let center = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+
+        let strokes = strokes
+            .iter()
+            .filter(|s| !s.is_empty())
+            .map(|stroke| {
+                let normalized = stroke
+                    .iter()
+                    .map(|p| Point {
+                        x: (p.x - center.0) / scale + 0.5,
+                        y: (p.y - center.1) / scale + 0.5,
+                    })
+                    .collect::<Vec<_>>();
+                let points = resample(&normalized);
+                let (first, last) = (points[0], points[points.len() - 1]);
+                let span = first.distance(&last);
+                let direction = if span > 0.0 {
+                    ((last.x - first.x) / span, (last.y - first.y) / span)
+                } else {
+                    (0.0, 0.0)
+                };
+                StrokeFeatures {
+                    length: polyline_length(&normalized),
+                    points,
+                    direction,
+                }
+            })
+            .collect();
+
+        CharacterFeatures { strokes }
+    }
+
+    pub fn stroke_count(&self) -> usize {
+        self.strokes.len()
+    }
+
+    /// Lower is more alike. Strokes are compared in writing order, so a
+    /// character drawn in the wrong order scores worse.
+    pub fn distance(&self, other: &CharacterFeatures) -> f64 {
+        let compared = self.strokes.len().min(other.strokes.len());
+        let extra = self.strokes.len().abs_diff(other.strokes.len());
+        if compared == 0 {
+            return f64::MAX;
+        }
+        let stroke_distance = self
+            .strokes
+            .iter()
+            .zip(&other.strokes)
+            .map(|(a, b)| stroke_distance(a, b))
+            .sum::<f64>()
+            / compared as f64;
+        stroke_distance + STROKE_COUNT_PENALTY * extra as f64
+    }
+}
+
+/// Dynamic time warping over the resampled points, so a stroke drawn faster at
+/// one end still lines up with the template.
+fn dtw(a: &[Point], b: &[Point]) -> f64 {
+    let (n, m) = (a.len(), b.len());
+    let mut costs = vec![vec![f64::MAX; m + 1]; n + 1];
+    costs[0][0] = 0.0;
+    for i in 1..=n {
+        for j in 1..=m {
+            let cost = a[i - 1].distance(&b[j - 1]);
+            let best_previous = costs[i - 1][j]
+                .min(costs[i][j - 1])
+                .min(costs[i - 1][j - 1]);
+            costs[i][j] = cost + best_previous;
+        }
+    }
+    costs[n][m] / (n + m) as f64
+}
+
+fn stroke_distance(a: &StrokeFeatures, b: &StrokeFeatures) -> f64 {
+    let cosine = a.direction.0 * b.direction.0 + a.direction.1 * b.direction.1;
+    let direction_difference = (1.0 - cosine) / 2.0;
+    let length_difference = (a.length - b.length).abs();
+    dtw(&a.points, &b.points)
+        + DIRECTION_WEIGHT * direction_difference
+        + LENGTH_WEIGHT * length_difference
+}
+
+/// `templates` ranked by distance to `drawing`, closest first.
+pub fn rank<'a, T>(
+    drawing: &CharacterFeatures,
+    templates: impl IntoIterator<Item = (T, &'a CharacterFeatures)>,
+) -> Vec<(T, f64)> {
+    let mut ranked = templates
+        .into_iter()
+        .map(|(key, template)| (key, drawing.distance(template)))
+        .collect::<Vec<_>>();
+    ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+    ranked
+}
@@ -0,0 +1,136 @@
+use super::recognizer::Point;
+
+/// Points sampled along every Bézier segment.
+const CURVE_SAMPLES: usize = 8;
+
+enum PathToken {
+    Command(char),
+    Number(f64),
+}
+
+/// Splits SVG path data into commands and numbers. KanjiVG leaves out
+/// separators wherever it can (`c0.95-0.41,2.72.5`), so a minus sign or a
+/// second decimal point also starts a new number.
+fn tokenize(d: &str) -> Vec<PathToken> {
+    let mut tokens = Vec::new();
+    let mut number = String::new();
+    let flush = |number: &mut String, tokens: &mut Vec<PathToken>| {
+        if let Ok(value) = number.parse::<f64>() {
+            tokens.push(PathToken::Number(value));
+        }
+        number.clear();
+    };
+
+    for c in d.chars() {
+        match c {
+            'e' | 'E' if !number.is_empty() => number.push(c),
+            c if c.is_ascii_alphabetic() => {
+                flush(&mut number, &mut tokens);
+                tokens.push(PathToken::Command(c));
+            }
+            '-' if !number.ends_with(['e', 'E']) => {
+                flush(&mut number, &mut tokens);
+                number.push(c);
+            }
+            '.' if number.contains('.') => {
+                flush(&mut number, &mut tokens);
+                number.push(c);
+            }
+            c if c.is_ascii_digit() || c == '.' || c == '-' => number.push(c),
+            _ => flush(&mut number, &mut tokens),
+        }
+    }
+    flush(&mut number, &mut tokens);
+    tokens
+}
+
+fn cubic(p0: Point, p1: Point, p2: Point, p3: Point, t: f64) -> Point {
+    let u = 1.0 - t;
+    Point {
+        x: u * u * u * p0.x + 3.0 * u * u * t * p1.x + 3.0 * u * t * t * p2.x + t * t * t * p3.x,
+        y: u * u * u * p0.y + 3.0 * u * u * t * p1.y + 3.0 * u * t * t * p2.y + t * t * t * p3.y,
+    }
+}
+
+/// Polyline approximation of the path data of a single KanjiVG stroke. Only
+/// the commands KanjiVG uses are supported: moves, lines and cubic curves.
+pub fn path_points(d: &str) -> Vec<Point> {
+    let mut points = Vec::new();
+    let mut current = Point { x: 0.0, y: 0.0 };
+    // Second control point of the last curve, reflected by `S`.
+    let mut last_control: Option<Point> = None;
+    let mut command = 'M';
+    let mut numbers = Vec::new();
+
+    for token in tokenize(d) {
+        match token {
+            PathToken::Command(c) => {
+                command = c;
+                continue;
+            }
+            PathToken::Number(value) => numbers.push(value),
+        }
+        let arity = match command.to_ascii_uppercase() {
+            'M' | 'L' => 2,
+            'S' => 4,
+            'C' => 6,
+            _ => {
+                numbers.clear();
+                continue;
+            }
+        };
+        if numbers.len() < arity {
+            continue;
+        }
+
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative {
+            current
+        } else {
+            Point { x: 0.0, y: 0.0 }
+        };
+        let absolute = |i: usize| Point {
+            x: origin.x + numbers[i],
+            y: origin.y + numbers[i + 1],
+        };
+
+        match command.to_ascii_uppercase() {
+            'M' => {
+                current = absolute(0);
+                points.push(current);
+                last_control = None;
+                // Further pairs after a move are implicit lines.
+                command = if relative { 'l' } else { 'L' };
+            }
+            'L' => {
+                current = absolute(0);
+                points.push(current);
+                last_control = None;
+            }
+            'C' | 'S' => {
+                let (control_1, control_2, end) = if arity == 6 {
+                    (absolute(0), absolute(2), absolute(4))
+                } else {
+                    let reflected = match last_control {
+                        Some(control) => Point {
+                            x: 2.0 * current.x - control.x,
+                            y: 2.0 * current.y - control.y,
+                        },
+                        None => current,
+                    };
+                    (reflected, absolute(0), absolute(2))
+                };
+                for i in 1..=CURVE_SAMPLES {
+                    let t = i as f64 / CURVE_SAMPLES as f64;
+                    points.push(cubic(current, control_1, control_2, end, t));
+                }
+                current = end;
+                last_control = Some(control_2);
+            }
+            _ => {}
+        }
+        numbers.clear();
+    }
+
+    points
+}
@@ -1,5 +1,10 @@
 use crate::data::kanjidic2::*;
+use crate::data::similar_kanji::{SimilarKanji, SimilarKanjiIndex};
+use crate::kanji::furigana_format::{self, FuriganaFormat};
+use crate::knowledge_base::entity::EntitiesState;
+use crate::knowledge_base::kanjis::KanjisState;
 use specta::Type;
+use std::collections::HashSet;
 use std::sync::Arc;
 
 trait EmptyToNone {
@@ -25,29 +30,41 @@ pub struct HeisigKanjiPayload {
     heisig_mnemonic: Option<String>,
     koohii_mnemonic_1: Option<String>,
     koohii_mnemonic_2: Option<String>,
+    /// Easily confused kanji, most similar first.
+    similar_kanji: Vec<SimilarKanji>,
 }
 impl HeisigKanjiPayload {
-    pub fn from_heisig_kanji(heisig_kanji: Arc<crate::data::heisig_kanji::HeisigKanji>) -> Self {
-        let pronunciation =
-            vec![heisig_kanji.kunYomi.clone(), heisig_kanji.onYomi.clone()].join(", ");
+    pub fn from_heisig_kanji(
+        heisig_kanji: Arc<crate::data::heisig_kanji::HeisigKanji>,
+        similar_kanji: &SimilarKanjiIndex,
+    ) -> Self {
+        let join_readings = |readings: &Vec<crate::data::heisig_kanji::Reading>| {
+            readings
+                .iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<_>>()
+                .join("、")
+        };
+        let pronunciation = vec![
+            join_readings(&heisig_kanji.kun_yomi),
+            join_readings(&heisig_kanji.on_yomi),
+        ]
+        .join(", ");
         HeisigKanjiPayload {
             id: heisig_kanji.id.clone(),
             kanji: heisig_kanji.kanji.clone(),
             pronunciation,
-            primitives: heisig_kanji
-                .constituent
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .collect(),
+            primitives: heisig_kanji.constituents.clone(),
             words: heisig_kanji
                 .words
-                .split("<br>")
-                .map(|s| s.trim().to_string())
+                .iter()
+                .map(|w| format!("{}({}): {}", w.word, w.reading, w.meaning))
                 .collect(),
-            jlpt_level: heisig_kanji.jlpt.parse::<u8>().ok(),
-            heisig_mnemonic: heisig_kanji.heisigStory.clone().empty_to_none(),
-            koohii_mnemonic_1: heisig_kanji.koohiiStory1.clone().empty_to_none(),
-            koohii_mnemonic_2: heisig_kanji.koohiiStory2.clone().empty_to_none(),
+            jlpt_level: heisig_kanji.jlpt,
+            heisig_mnemonic: heisig_kanji.heisig_story.clone().empty_to_none(),
+            koohii_mnemonic_1: heisig_kanji.koohii_story_1.clone().empty_to_none(),
+            koohii_mnemonic_2: heisig_kanji.koohii_story_2.clone().empty_to_none(),
+            similar_kanji: similar_kanji.get(&heisig_kanji.kanji).to_vec(),
         }
     }
 }
@@ -80,6 +97,7 @@ pub async fn search_heisig_kanji(
     app_handle: tauri::AppHandle,
 ) -> Result<Vec<HeisigKanjiPayload>, String> {
     let kanjis = crate::data::get_heisig_kanjis(&app_handle);
+    let similar_kanji = crate::data::get_similar_kanji(&app_handle);
 
     let kanjis = match query {
         HeisigKanjiQuery::Kanji(char) => kanjis.get_by_kanji(&char),
@@ -89,17 +107,18 @@ pub async fn search_heisig_kanji(
 
     Ok(kanjis
         .into_iter()
-        .map(|k| HeisigKanjiPayload::from_heisig_kanji(k))
+        .map(|k| HeisigKanjiPayload::from_heisig_kanji(k, &similar_kanji))
         .collect())
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn get_heisig_kanjis(app_handle: tauri::AppHandle) -> Vec<HeisigKanjiPayload> {
+    let similar_kanji = crate::data::get_similar_kanji(&app_handle);
     crate::data::get_heisig_kanjis(&app_handle)
         .get_all()
         .iter()
-        .map(|k| HeisigKanjiPayload::from_heisig_kanji(k.clone()))
+        .map(|k| HeisigKanjiPayload::from_heisig_kanji(k.clone(), &similar_kanji))
         .collect()
 }
 
@@ -110,19 +129,91 @@ pub async fn search_heisig_kanjis(
     app_handle: tauri::AppHandle,
 ) -> Result<Vec<HeisigKanjiPayload>, String> {
     let kanjis = crate::data::get_heisig_kanjis(&app_handle);
+    let similar_kanji = crate::data::get_similar_kanji(&app_handle);
 
     let mut result = vec![];
 
     for ch in chars {
         let kanji = kanjis.get_by_kanji(&ch);
         if let Some(kanji) = kanji.get(0) {
-            result.push(HeisigKanjiPayload::from_heisig_kanji(kanji.clone()));
+            result.push(HeisigKanjiPayload::from_heisig_kanji(
+                kanji.clone(),
+                &similar_kanji,
+            ));
         }
     }
 
     Ok(result)
 }
 
+#[derive(Debug, Clone, serde::Serialize, Type)]
+pub struct ContrastPair {
+    pub kanji: String,
+    pub keyword: String,
+    pub similar_kanji: String,
+    pub similar_keyword: String,
+    pub score: f64,
+    /// Both kanji are saved, so the user is learning both of them.
+    pub both_in_deck: bool,
+}
+
+/// Pairs of similar kanji for contrast review, at least one of them saved in
+/// the deck. Pairs the user is learning both sides of come first.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_contrast_pairs(
+    app_handle: tauri::AppHandle,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    limit: u32,
+) -> Result<Vec<ContrastPair>, String> {
+    let heisig_kanjis = crate::data::get_heisig_kanjis(&app_handle);
+    let similar_kanji = crate::data::get_similar_kanji(&app_handle);
+    let deck = kanjis_state
+        .get_entities()
+        .await
+        .into_iter()
+        .map(|k| k.kanji)
+        .collect::<HashSet<_>>();
+    let keyword = |kanji: &String| {
+        heisig_kanjis
+            .get_by_kanji(kanji)
+            .first()
+            .map(|k| k.keyword.clone())
+            .unwrap_or_default()
+    };
+
+    let mut seen = HashSet::new();
+    let mut pairs = Vec::new();
+    for kanji in &deck {
+        for similar in similar_kanji.get(kanji) {
+            let key = if *kanji < similar.kanji {
+                (kanji.clone(), similar.kanji.clone())
+            } else {
+                (similar.kanji.clone(), kanji.clone())
+            };
+            if !seen.insert(key) {
+                continue;
+            }
+            pairs.push(ContrastPair {
+                kanji: kanji.clone(),
+                keyword: keyword(kanji),
+                similar_kanji: similar.kanji.clone(),
+                similar_keyword: keyword(&similar.kanji),
+                score: similar.score,
+                both_in_deck: deck.contains(&similar.kanji),
+            });
+        }
+    }
+    pairs.sort_by(|a, b| {
+        b.both_in_deck
+            .cmp(&a.both_in_deck)
+            .then(b.score.total_cmp(&a.score))
+    });
+    pairs.truncate(limit as usize);
+
+    Ok(pairs)
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn get_kanjidic2_by_kanji(
@@ -149,6 +240,83 @@ pub async fn parse_word(
     crate::kanji::parser::parse_word(&app_handle, &word, None).await
 }
 
+/// Furigana for `text` in `format`. With `hide_known_kanji`, kanji saved in the
+/// kanji deck are rendered without a reading.
+#[tauri::command]
+#[specta::specta]
+pub async fn render_furigana(
+    app_handle: tauri::AppHandle,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    text: String,
+    reading: Option<String>,
+    format: FuriganaFormat,
+    hide_known_kanji: bool,
+) -> Result<String, String> {
+    let furigana_string = crate::kanji::parser::parse_word(&app_handle, &text, reading).await?;
+    let known_kanji = if hide_known_kanji {
+        kanjis_state
+            .get_entities()
+            .await
+            .iter()
+            .flat_map(|k| k.kanji.chars())
+            .collect()
+    } else {
+        HashSet::new()
+    };
+    Ok(furigana_format::render(
+        &furigana_string,
+        format,
+        &known_kanji,
+    ))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn parse_furigana(
+    app_handle: tauri::AppHandle,
+    input: String,
+    format: FuriganaFormat,
+) -> Result<crate::kanji::parser::FuriganaString, String> {
+    furigana_format::parse(&app_handle, &input, format).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_stroke_order(
+    app_handle: tauri::AppHandle,
+    kanji: String,
+) -> Result<crate::kanji::stroke_order::StrokeOrder, String> {
+    let mut chars = kanji.chars();
+    let (Some(character), None) = (chars.next(), chars.next()) else {
+        return Err(format!("Expected a single kanji, got {}", kanji));
+    };
+    let strokes = crate::data::get_kanjivg(&app_handle).strokes(character)?;
+    Ok(crate::kanji::stroke_order::stroke_order(
+        character, &strokes,
+    ))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_pitch_accents(
+    app_handle: tauri::AppHandle,
+    word: String,
+) -> Result<Vec<crate::data::pitch_accent::PitchAccent>, String> {
+    Ok(crate::data::get_pitch_accents(&app_handle).find_by_word(&word))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn render_pitch_accent(
+    app_handle: tauri::AppHandle,
+    word: String,
+    reading: Option<String>,
+    accent: u8,
+) -> Result<String, String> {
+    let furigana_string = crate::kanji::parser::parse_word(&app_handle, &word, reading).await?;
+    Ok(furigana_string.to_pitch_html(accent))
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn validate_dictionary(app_handle: tauri::AppHandle) -> Result<(), String> {
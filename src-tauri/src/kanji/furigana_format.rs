@@ -0,0 +1,547 @@
+use std::collections::HashSet;
+
+use tauri::AppHandle;
+
+use crate::kanji::parser::{Furigana, FuriganaString};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum FuriganaFormat {
+    /// `<ruby><rb>漢</rb><rt>かん</rt></ruby>`
+    Html,
+    /// `漢[かん] 字[じ]`, Anki's `{{furigana:...}}` syntax.
+    Anki,
+    /// `｜漢《かん》｜字《じ》`, Aozora Bunko ruby.
+    Aozora,
+    /// `{漢|かん}{字|じ}`, the markdown-it-ruby / DenDenMarkdown syntax.
+    Markdown,
+    /// `かんじ`, the reading without any kanji.
+    Kana,
+}
+
+/// A run of text with an optional reading for all of it, as found in ruby
+/// markup. Group ruby (`漢字[かんじ]`) is split per kanji only later.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RubySegment {
+    base: String,
+    reading: Option<String>,
+}
+
+impl RubySegment {
+    fn plain(base: String) -> Self {
+        RubySegment {
+            base,
+            reading: None,
+        }
+    }
+
+    fn ruby(base: String, reading: String) -> Self {
+        RubySegment {
+            base,
+            reading: Some(reading),
+        }
+    }
+}
+
+/// Renders `furigana` in `format`. Kanji in `known_kanji` lose their reading,
+/// and stay kanji even in the `Kana` format.
+pub fn render(
+    furigana: &FuriganaString,
+    format: FuriganaFormat,
+    known_kanji: &HashSet<char>,
+) -> String {
+    let visible = FuriganaString::new(
+        furigana
+            .furigana()
+            .iter()
+            .map(|f| match f {
+                Furigana::Kanji { character, .. } if known_kanji.contains(character) => {
+                    Furigana::Other(*character)
+                }
+                other => other.clone(),
+            })
+            .collect(),
+    );
+
+    match format {
+        FuriganaFormat::Html => visible.to_html(),
+        FuriganaFormat::Kana => visible.reading(),
+        FuriganaFormat::Anki => {
+            let mut output = String::new();
+            for f in visible.furigana() {
+                match f {
+                    Furigana::Kanji { character, reading } => {
+                        // Anki takes everything back to the previous space as
+                        // the base text.
+                        if !output.is_empty() {
+                            output.push(' ');
+                        }
+                        output.push_str(&format!("{}[{}]", character, reading));
+                    }
+                    Furigana::Other(c) => output.push(*c),
+                }
+            }
+            output
+        }
+        FuriganaFormat::Aozora => visible
+            .furigana()
+            .iter()
+            .map(|f| match f {
+                Furigana::Kanji { character, reading } => {
+                    format!("｜{}《{}》", character, reading)
+                }
+                Furigana::Other(c) => c.to_string(),
+            })
+            .collect(),
+        FuriganaFormat::Markdown => visible
+            .furigana()
+            .iter()
+            .map(|f| match f {
+                Furigana::Kanji { character, reading } => {
+                    format!("{{{}|{}}}", character, reading)
+                }
+                Furigana::Other(c) => c.to_string(),
+            })
+            .collect(),
+    }
+}
+
+fn push_plain(segments: &mut Vec<RubySegment>, text: &mut String) {
+    if !text.is_empty() {
+        segments.push(RubySegment::plain(std::mem::take(text)));
+    }
+}
+
+fn parse_anki(input: &str) -> Vec<RubySegment> {
+    let mut segments = Vec::new();
+    // Text since the last space or ruby, with the separating space kept at the
+    // front so it can be dropped if the text turns out to be a ruby base.
+    let mut pending = String::new();
+    let mut rest = input;
+
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            ' ' => {
+                push_plain(&mut segments, &mut pending);
+                pending.push(' ');
+            }
+            '[' => match rest.split_once(']') {
+                Some((reading, after)) if !pending.trim_start_matches(' ').is_empty() => {
+                    let base = pending.trim_start_matches(' ').to_string();
+                    pending.clear();
+                    segments.push(RubySegment::ruby(base, reading.to_string()));
+                    rest = after;
+                }
+                _ => pending.push(c),
+            },
+            _ => pending.push(c),
+        }
+    }
+    push_plain(&mut segments, &mut pending);
+    segments
+}
+
+fn is_aozora_base_char(c: char) -> bool {
+    wana_kana::utils::is_char_kanji(c) || c == '々'
+}
+
+fn parse_aozora(input: &str) -> Vec<RubySegment> {
+    let mut segments = Vec::new();
+    let mut pending = String::new();
+    // Set after `｜`, which marks where the base text starts.
+    let mut explicit_base: Option<String> = None;
+    let mut rest = input;
+
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            '｜' => {
+                if let Some(base) = explicit_base.take() {
+                    pending.push('｜');
+                    pending.push_str(&base);
+                }
+                push_plain(&mut segments, &mut pending);
+                explicit_base = Some(String::new());
+            }
+            '《' => {
+                let Some((reading, after)) = rest.split_once('》') else {
+                    explicit_base.as_mut().unwrap_or(&mut pending).push(c);
+                    continue;
+                };
+                let base = match explicit_base.take() {
+                    Some(base) => base,
+                    // Without `｜`, the base is the run of kanji before `《`.
+                    None => {
+                        let plain_length = pending
+                            .char_indices()
+                            .rev()
+                            .take_while(|(_, c)| is_aozora_base_char(*c))
+                            .last()
+                            .map(|(i, _)| i)
+                            .unwrap_or(pending.len());
+                        let base = pending.split_off(plain_length);
+                        push_plain(&mut segments, &mut pending);
+                        base
+                    }
+                };
+                if base.is_empty() {
+                    pending.push(c);
+                    continue;
+                }
+                segments.push(RubySegment::ruby(base, reading.to_string()));
+                rest = after;
+            }
+            _ => match explicit_base.as_mut() {
+                Some(base) => base.push(c),
+                None => pending.push(c),
+            },
+        }
+    }
+    if let Some(base) = explicit_base {
+        pending.push('｜');
+        pending.push_str(&base);
+    }
+    push_plain(&mut segments, &mut pending);
+    segments
+}
+
+fn parse_markdown(input: &str) -> Vec<RubySegment> {
+    let mut segments = Vec::new();
+    let mut pending = String::new();
+    let mut rest = input;
+
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        if c != '{' {
+            pending.push(c);
+            continue;
+        }
+        let Some((inner, after)) = rest.split_once('}') else {
+            pending.push(c);
+            continue;
+        };
+        let mut parts = inner.split('|');
+        let base = parts.next().unwrap_or_default();
+        let readings = parts.collect::<Vec<_>>();
+        if base.is_empty() || readings.is_empty() {
+            pending.push(c);
+            continue;
+        }
+
+        push_plain(&mut segments, &mut pending);
+        let base_chars = base.chars().collect::<Vec<_>>();
+        // `{漢字|かん|じ}` gives one reading per character.
+        if readings.len() > 1 && readings.len() == base_chars.len() {
+            for (character, reading) in base_chars.iter().zip(readings) {
+                segments.push(RubySegment::ruby(
+                    character.to_string(),
+                    reading.to_string(),
+                ));
+            }
+        } else {
+            segments.push(RubySegment::ruby(base.to_string(), readings.concat()));
+        }
+        rest = after;
+    }
+    push_plain(&mut segments, &mut pending);
+    segments
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut output = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => output.push(c),
+            _ => {}
+        }
+    }
+    output
+}
+
+/// Removes `<rp>...</rp>` fallback parentheses.
+fn strip_rp(html: &str) -> String {
+    let mut output = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("<rp>") {
+        output.push_str(&rest[..start]);
+        rest = match rest[start..].find("</rp>") {
+            Some(end) => &rest[start + end + "</rp>".len()..],
+            None => "",
+        };
+    }
+    output.push_str(rest);
+    output
+}
+
+fn parse_html(input: &str) -> Vec<RubySegment> {
+    let mut segments = Vec::new();
+    let mut rest = input;
+
+    while let Some(start) = rest.find("<ruby>") {
+        let before = strip_tags(&rest[..start]);
+        if !before.is_empty() {
+            segments.push(RubySegment::plain(before));
+        }
+        let after_open = &rest[start + "<ruby>".len()..];
+        let (inner, after) = after_open.split_once("</ruby>").unwrap_or((after_open, ""));
+
+        // `漢<rt>かん</rt>字<rt>じ</rt>` pairs every base with the next `<rt>`.
+        let inner = strip_rp(inner);
+        let mut inner_rest = inner.as_str();
+        while let Some(rt_start) = inner_rest.find("<rt>") {
+            let base = strip_tags(&inner_rest[..rt_start]);
+            let after_rt = &inner_rest[rt_start + "<rt>".len()..];
+            let (reading, after_reading) = after_rt.split_once("</rt>").unwrap_or((after_rt, ""));
+            if base.is_empty() {
+                segments.push(RubySegment::plain(strip_tags(reading)));
+            } else {
+                segments.push(RubySegment::ruby(base, strip_tags(reading)));
+            }
+            inner_rest = after_reading;
+        }
+        let trailing = strip_tags(inner_rest);
+        if !trailing.is_empty() {
+            segments.push(RubySegment::plain(trailing));
+        }
+        rest = after;
+    }
+    let trailing = strip_tags(rest);
+    if !trailing.is_empty() {
+        segments.push(RubySegment::plain(trailing));
+    }
+    segments
+}
+
+fn parse_segments(input: &str, format: FuriganaFormat) -> Vec<RubySegment> {
+    match format {
+        FuriganaFormat::Html => parse_html(input),
+        FuriganaFormat::Anki => parse_anki(input),
+        FuriganaFormat::Aozora => parse_aozora(input),
+        FuriganaFormat::Markdown => parse_markdown(input),
+        FuriganaFormat::Kana => vec![RubySegment::plain(input.to_string())],
+    }
+}
+
+/// The furigana of a segment, `None` for a reading covering several
+/// characters, which has to be split over them first.
+fn segment_furigana(segment: &RubySegment) -> Option<Vec<Furigana>> {
+    let mut base_chars = segment.base.chars();
+    match (&segment.reading, base_chars.next(), base_chars.next()) {
+        (None, _, _) => Some(segment.base.chars().map(Furigana::Other).collect()),
+        (Some(reading), Some(character), None) => Some(vec![Furigana::Kanji {
+            character,
+            reading: reading.clone(),
+        }]),
+        (Some(_), _, _) => None,
+    }
+}
+
+/// Parses `input` written in `format`. Readings covering several characters
+/// are split per kanji like `parse_word` does.
+pub async fn parse(
+    app: &AppHandle,
+    input: &str,
+    format: FuriganaFormat,
+) -> Result<FuriganaString, String> {
+    let mut furigana = Vec::new();
+    for segment in parse_segments(input, format) {
+        if let Some(segment_furigana) = segment_furigana(&segment) {
+            furigana.extend(segment_furigana);
+            continue;
+        }
+        let reading = segment.reading.unwrap_or_default();
+        let parsed = crate::kanji::parser::parse_word(app, &segment.base, Some(reading.clone()))
+            .await
+            .map_err(|_| format!("Could not split reading {} over {}", reading, segment.base))?;
+        furigana.extend(parsed.furigana().iter().cloned());
+    }
+    Ok(FuriganaString::new(furigana))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `("食", "た")` is a kanji with its reading, `("べる", "")` plain text.
+    fn furigana(parts: &[(&str, &str)]) -> FuriganaString {
+        let mut furigana = Vec::new();
+        for (base, reading) in parts {
+            if reading.is_empty() {
+                furigana.extend(base.chars().map(Furigana::Other));
+            } else {
+                furigana.push(Furigana::Kanji {
+                    character: base.chars().next().unwrap(),
+                    reading: reading.to_string(),
+                });
+            }
+        }
+        FuriganaString::new(furigana)
+    }
+
+    /// `parse` without the app, for markup with one reading per kanji.
+    fn parse_per_kanji(input: &str, format: FuriganaFormat) -> FuriganaString {
+        FuriganaString::new(
+            parse_segments(input, format)
+                .iter()
+                .flat_map(|segment| segment_furigana(segment).expect("one reading per kanji"))
+                .collect(),
+        )
+    }
+
+    fn examples() -> Vec<FuriganaString> {
+        vec![
+            // Okurigana.
+            furigana(&[("食", "た"), ("べる", "")]),
+            // Adjacent kanji, each with its own reading.
+            furigana(&[("漢", "かん"), ("字", "じ")]),
+            furigana(&[
+                ("日", "に"),
+                ("本", "ほん"),
+                ("語", "ご"),
+                ("を", ""),
+                ("勉", "べん"),
+                ("強", "きょう"),
+                ("する", ""),
+            ]),
+            // Kana right before a kanji, which Aozora needs `｜` for.
+            furigana(&[
+                ("お", ""),
+                ("茶", "ちゃ"),
+                ("と", ""),
+                ("人", "ひと"),
+                ("々", "びと"),
+            ]),
+            // Spaces and latin text around the ruby.
+            furigana(&[("A ", ""), ("時", "じ"), ("間", "かん"), (" B", "")]),
+        ]
+    }
+
+    #[test]
+    fn round_trips() {
+        let no_known_kanji = HashSet::new();
+        for format in [
+            FuriganaFormat::Anki,
+            FuriganaFormat::Aozora,
+            FuriganaFormat::Markdown,
+            FuriganaFormat::Html,
+        ] {
+            for example in examples() {
+                let rendered = render(&example, format, &no_known_kanji);
+                assert_eq!(
+                    parse_per_kanji(&rendered, format),
+                    example,
+                    "{:?}: {}",
+                    format,
+                    rendered
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_with_known_kanji_hidden() {
+        let known_kanji = HashSet::from(['本', '字', '間']);
+        let hide = |furigana: &FuriganaString| {
+            FuriganaString::new(
+                furigana
+                    .furigana()
+                    .iter()
+                    .map(|f| match f {
+                        Furigana::Kanji { character, .. } if known_kanji.contains(character) => {
+                            Furigana::Other(*character)
+                        }
+                        other => other.clone(),
+                    })
+                    .collect(),
+            )
+        };
+        for format in [
+            FuriganaFormat::Anki,
+            FuriganaFormat::Aozora,
+            FuriganaFormat::Markdown,
+        ] {
+            for example in examples() {
+                let rendered = render(&example, format, &known_kanji);
+                assert_eq!(
+                    parse_per_kanji(&rendered, format),
+                    hide(&example),
+                    "{:?}: {}",
+                    format,
+                    rendered
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn renders_each_format() {
+        let example = furigana(&[("お", ""), ("茶", "ちゃ"), ("漢", "かん"), ("字", "じ")]);
+        let none = HashSet::new();
+
+        assert_eq!(
+            render(&example, FuriganaFormat::Anki, &none),
+            "お 茶[ちゃ] 漢[かん] 字[じ]"
+        );
+        assert_eq!(
+            render(&example, FuriganaFormat::Aozora, &none),
+            "お｜茶《ちゃ》｜漢《かん》｜字《じ》"
+        );
+        assert_eq!(
+            render(&example, FuriganaFormat::Markdown, &none),
+            "お{茶|ちゃ}{漢|かん}{字|じ}"
+        );
+        assert_eq!(
+            render(&example, FuriganaFormat::Kana, &none),
+            "おちゃかんじ"
+        );
+        assert_eq!(
+            render(&example, FuriganaFormat::Kana, &HashSet::from(['字'])),
+            "おちゃかん字"
+        );
+    }
+
+    #[test]
+    fn aozora_base_without_delimiter() {
+        assert_eq!(
+            parse_segments("お茶《ちゃ》と漢字《かんじ》", FuriganaFormat::Aozora),
+            [
+                RubySegment::plain("お".to_string()),
+                RubySegment::ruby("茶".to_string(), "ちゃ".to_string()),
+                RubySegment::plain("と".to_string()),
+                RubySegment::ruby("漢字".to_string(), "かんじ".to_string()),
+            ]
+        );
+        // `｜` moves the start of the base, e.g. to cover kana too.
+        assert_eq!(
+            parse_segments("｜お茶《おちゃ》", FuriganaFormat::Aozora),
+            [RubySegment::ruby("お茶".to_string(), "おちゃ".to_string())]
+        );
+        // A stray `｜` is kept as text.
+        assert_eq!(
+            parse_segments("a｜b", FuriganaFormat::Aozora),
+            [
+                RubySegment::plain("a".to_string()),
+                RubySegment::plain("｜b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn group_ruby_is_left_for_parse_word() {
+        let segments = parse_segments("漢字[かんじ]", FuriganaFormat::Anki);
+        assert_eq!(
+            segments,
+            [RubySegment::ruby("漢字".to_string(), "かんじ".to_string())]
+        );
+        assert_eq!(segment_furigana(&segments[0]), None);
+
+        // One reading per character splits right away.
+        assert_eq!(
+            parse_per_kanji("{漢字|かん|じ}", FuriganaFormat::Markdown),
+            furigana(&[("漢", "かん"), ("字", "じ")])
+        );
+    }
+}
@@ -1,5 +1,8 @@
 pub mod commands;
+pub mod furigana_format;
 pub mod parser;
+pub mod pitch;
+pub mod stroke_order;
 
 pub struct Word {
     vec: Vec<Char>,
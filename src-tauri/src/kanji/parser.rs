@@ -15,6 +15,14 @@ pub enum Furigana {
 pub struct FuriganaString(Vec<Furigana>);
 
 impl FuriganaString {
+    pub fn new(furigana: Vec<Furigana>) -> Self {
+        FuriganaString(furigana)
+    }
+
+    pub fn furigana(&self) -> &[Furigana] {
+        &self.0
+    }
+
     pub fn to_html(&self) -> String {
         self.0
             .iter()
@@ -27,6 +35,68 @@ impl FuriganaString {
             .collect()
     }
 
+    /// A string without any kanji, e.g. a kana-only token.
+    pub fn from_plain(text: &str) -> Self {
+        FuriganaString(text.chars().map(Furigana::Other).collect())
+    }
+
+    /// Readings of the kanji in order, the shape `WordCreatePayload` expects.
+    pub fn kanji_readings(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .filter_map(|f| match f {
+                Furigana::Kanji { reading, .. } => Some(reading.clone()),
+                Furigana::Other(_) => None,
+            })
+            .collect()
+    }
+
+    /// The kana reading of the whole string.
+    pub fn reading(&self) -> String {
+        self.0
+            .iter()
+            .map(|f| match f {
+                Furigana::Kanji { reading, .. } => reading.clone(),
+                Furigana::Other(s) => s.to_string(),
+            })
+            .collect()
+    }
+
+    /// Like `to_html`, but every mora (in the ruby text for kanji) is wrapped
+    /// in a span marking it high or low for the given accent.
+    pub fn to_pitch_html(&self, accent: u8) -> String {
+        let mut mora_index = 0;
+        let mut kana_to_pitch_html = |kana: &str| -> String {
+            let mut html = String::new();
+            for mora in crate::kanji::pitch::split_morae(kana) {
+                let starts_with_continuation = mora
+                    .chars()
+                    .next()
+                    .is_some_and(crate::kanji::pitch::is_mora_continuation);
+                if starts_with_continuation && mora_index > 0 {
+                    mora_index -= 1;
+                }
+                html.push_str(&crate::kanji::pitch::mora_to_html(
+                    &mora, mora_index, accent,
+                ));
+                mora_index += 1;
+            }
+            html
+        };
+
+        self.0
+            .iter()
+            .map(|f| match f {
+                Furigana::Kanji { character, reading } => format!(
+                    "<ruby><rb>{}</rb><rt>{}</rt></ruby>",
+                    character,
+                    kana_to_pitch_html(reading)
+                ),
+                Furigana::Other(s) => kana_to_pitch_html(&s.to_string()),
+            })
+            .collect()
+    }
+
     pub fn from_furigana_parser(input: furigana_parser::FuriganaString) -> Self {
         FuriganaString(
             input
@@ -55,9 +125,10 @@ pub async fn parse_word(
         return Err("Empty word".to_string());
     }
 
-    let reading = reading
-        .ok_or("Reading not provided".to_string())
-        .or(crate::conversion::convert(word))?;
+    let reading = match reading {
+        Some(reading) => reading,
+        None => crate::conversion::convert(app, word).await?,
+    };
 
     let mut kanji_readings = HashMap::new();
     let mut prev = None;
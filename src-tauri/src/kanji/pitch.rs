@@ -0,0 +1,133 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum Pitch {
+    High,
+    Low,
+}
+
+/// Small kana that merge with the preceding kana into a single mora.
+/// `っ` and `ッ` are deliberately missing, they are morae of their own.
+const SMALL_KANA: [char; 18] = [
+    'ゃ', 'ゅ', 'ょ', 'ぁ', 'ぃ', 'ぅ', 'ぇ', 'ぉ', 'ゎ', 'ャ', 'ュ', 'ョ', 'ァ', 'ィ', 'ゥ', 'ェ',
+    'ォ', 'ヮ',
+];
+
+pub fn is_mora_continuation(c: char) -> bool {
+    SMALL_KANA.contains(&c)
+}
+
+pub fn split_morae(reading: &str) -> Vec<String> {
+    let mut morae: Vec<String> = Vec::new();
+    for c in reading.chars() {
+        match morae.last_mut() {
+            Some(last) if is_mora_continuation(c) => last.push(c),
+            _ => morae.push(c.to_string()),
+        }
+    }
+    morae
+}
+
+/// Pitch of the `index`-th mora of a word with `accent` downstep (Tokyo dialect).
+pub fn mora_pitch(index: usize, accent: u8) -> Pitch {
+    let accent = accent as usize;
+    match accent {
+        0 if index == 0 => Pitch::Low,
+        0 => Pitch::High,
+        1 if index == 0 => Pitch::High,
+        1 => Pitch::Low,
+        _ if index == 0 => Pitch::Low,
+        _ if index < accent => Pitch::High,
+        _ => Pitch::Low,
+    }
+}
+
+/// Wraps a mora in a span carrying its pitch. The mora right before the
+/// downstep also gets `pitch-drop`, so it can be drawn with a falling edge.
+pub fn mora_to_html(mora: &str, index: usize, accent: u8) -> String {
+    let pitch = match mora_pitch(index, accent) {
+        Pitch::High => "pitch-high",
+        Pitch::Low => "pitch-low",
+    };
+    if accent as usize == index + 1 {
+        format!("<span class=\"{} pitch-drop\">{}</span>", pitch, mora)
+    } else {
+        format!("<span class=\"{}\">{}</span>", pitch, mora)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pitches(reading: &str, accent: u8) -> Vec<Pitch> {
+        (0..split_morae(reading).len())
+            .map(|index| mora_pitch(index, accent))
+            .collect()
+    }
+
+    #[test]
+    fn small_kana_join_the_previous_mora() {
+        assert_eq!(split_morae("きょう"), ["きょ", "う"]);
+        assert_eq!(split_morae("しゃちょう"), ["しゃ", "ちょ", "う"]);
+        assert_eq!(split_morae("ティッシュ"), ["ティ", "ッ", "シュ"]);
+    }
+
+    #[test]
+    fn long_vowel_mark_and_sokuon_are_morae() {
+        assert_eq!(split_morae("コーヒー"), ["コ", "ー", "ヒ", "ー"]);
+        assert_eq!(split_morae("きって"), ["き", "っ", "て"]);
+        assert_eq!(split_morae("ゃあ"), ["ゃ", "あ"]);
+    }
+
+    #[test]
+    fn heiban_rises_and_stays_high() {
+        use Pitch::*;
+        // 端 (はし⓪)
+        assert_eq!(pitches("はし", 0), [Low, High]);
+        // 桜 (さくら⓪)
+        assert_eq!(pitches("さくら", 0), [Low, High, High]);
+    }
+
+    #[test]
+    fn atamadaka_drops_after_the_first_mora() {
+        use Pitch::*;
+        // 箸 (はし①)
+        assert_eq!(pitches("はし", 1), [High, Low]);
+        // 今日 (きょう①)
+        assert_eq!(pitches("きょう", 1), [High, Low]);
+    }
+
+    #[test]
+    fn nakadaka_and_odaka_drop_after_the_accent() {
+        use Pitch::*;
+        // 橋 (はし②), the drop falls on the following particle.
+        assert_eq!(pitches("はし", 2), [Low, High]);
+        // 心 (こころ②)
+        assert_eq!(pitches("こころ", 2), [Low, High, Low]);
+        // 弟 (おとうと④)
+        assert_eq!(pitches("おとうと", 4), [Low, High, High, High]);
+    }
+
+    #[test]
+    fn mora_before_the_downstep_is_marked() {
+        let html = |reading: &str, accent| {
+            split_morae(reading)
+                .iter()
+                .enumerate()
+                .map(|(index, mora)| mora_to_html(mora, index, accent))
+                .collect::<String>()
+        };
+
+        assert_eq!(
+            html("はし", 0),
+            "<span class=\"pitch-low\">は</span><span class=\"pitch-high\">し</span>"
+        );
+        assert_eq!(
+            html("きょう", 1),
+            "<span class=\"pitch-high pitch-drop\">きょ</span><span class=\"pitch-low\">う</span>"
+        );
+        assert_eq!(
+            html("はし", 2),
+            "<span class=\"pitch-low\">は</span><span class=\"pitch-high pitch-drop\">し</span>"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use crate::data::kanjivg::{KANJIVG_SIZE, KanjiVgStroke};
+
+const STROKE_STYLE: &str = "fill:none;stroke-width:3;stroke-linecap:round;stroke-linejoin:round";
+const NUMBER_STYLE: &str = "font-size:8px;fill:#808080";
+const DONE_COLOR: &str = "#000000";
+const CURRENT_COLOR: &str = "#d03030";
+const PENDING_COLOR: &str = "#d0d0d0";
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct StrokeOrder {
+    pub kanji: String,
+    /// In writing order.
+    pub strokes: Vec<KanjiVgStroke>,
+    /// All strokes with their numbers.
+    pub numbered_svg: String,
+    /// One SVG per stroke: the strokes written so far, the current one
+    /// highlighted and the remaining ones greyed out.
+    pub frames: Vec<String>,
+}
+
+fn svg_document(body: &str) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {size} {size}">{body}</svg>"#,
+        size = KANJIVG_SIZE,
+        body = body
+    )
+}
+
+fn path_element(stroke: &KanjiVgStroke, class: &str, color: &str) -> String {
+    format!(
+        r#"<path class="{}" d="{}" style="{};stroke:{}"/>"#,
+        class, stroke.path, STROKE_STYLE, color
+    )
+}
+
+/// The `M x,y` a KanjiVG path starts with, for strokes without a label.
+fn start_point(path: &str) -> Option<(f64, f64)> {
+    let coordinates = path.trim_start().strip_prefix(['M', 'm'])?;
+    let mut numbers = coordinates
+        .split(|c: char| c == ',' || c.is_whitespace() || c.is_ascii_alphabetic())
+        .filter(|n| !n.is_empty())
+        .map(|n| n.parse::<f64>().ok());
+    Some((numbers.next()??, numbers.next()??))
+}
+
+fn numbered_svg(strokes: &[KanjiVgStroke]) -> String {
+    let paths = strokes
+        .iter()
+        .map(|s| path_element(s, "stroke", DONE_COLOR))
+        .collect::<String>();
+    let numbers = strokes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, s)| {
+            let (x, y) = s.number_position.or_else(|| start_point(&s.path))?;
+            Some(format!(r#"<text x="{}" y="{}">{}</text>"#, x, y, i + 1))
+        })
+        .collect::<String>();
+    svg_document(&format!(
+        r#"<g>{}</g><g class="stroke-numbers" style="{}">{}</g>"#,
+        paths, NUMBER_STYLE, numbers
+    ))
+}
+
+fn frame_svg(strokes: &[KanjiVgStroke], current: usize) -> String {
+    let paths = strokes
+        .iter()
+        .enumerate()
+        .map(|(i, s)| match i.cmp(&current) {
+            std::cmp::Ordering::Less => path_element(s, "stroke-done", DONE_COLOR),
+            std::cmp::Ordering::Equal => path_element(s, "stroke-current", CURRENT_COLOR),
+            std::cmp::Ordering::Greater => path_element(s, "stroke-pending", PENDING_COLOR),
+        })
+        .collect::<String>();
+    svg_document(&paths)
+}
+
+pub fn stroke_order(kanji: char, strokes: &[KanjiVgStroke]) -> StrokeOrder {
+    StrokeOrder {
+        kanji: kanji.to_string(),
+        strokes: strokes.to_vec(),
+        numbered_svg: numbered_svg(strokes),
+        frames: (0..strokes.len()).map(|i| frame_svg(strokes, i)).collect(),
+    }
+}
@@ -0,0 +1,365 @@
+use std::sync::Arc;
+
+use super::entity::*;
+use super::history::{CardKind, HistoryState};
+use super::kanjis::{Kanji, KanjisState};
+use super::tags::{is_within, normalize_tags};
+use super::words::{Word, WordsState};
+use serde::{Deserialize, Serialize};
+use tauri::{Wry, async_runtime::RwLock};
+use tauri_plugin_store::Store;
+use tokio::sync::RwLockWriteGuard;
+
+pub struct CollectionsState(Arc<RwLock<Collections>>);
+impl EntitiesState for CollectionsState {
+    type Entities = Collections;
+
+    fn new(collections: Collections) -> Self {
+        Self(Arc::new(RwLock::new(collections)))
+    }
+
+    async fn get_entities(&self) -> Vec<Collection> {
+        let collections = self.0.read().await;
+        collections.get_entities()
+    }
+
+    async fn add_entity(&self, payload: CollectionCreatePayload) -> Result<Collection, String> {
+        let mut collections = self.0.write().await;
+        collections.add_entity(payload)
+    }
+
+    async fn has_entity(&self, identifier: &String) -> bool {
+        let collections = self.0.read().await;
+        collections.has_entity(identifier)
+    }
+
+    async fn update_entity(&self, collection: Collection) -> Result<Collection, String> {
+        let mut collections = self.0.write().await;
+        collections.update_entity(collection)
+    }
+
+    async fn remove_entity(&self, identifier: &String) -> Result<Collection, String> {
+        let mut collections = self.0.write().await;
+        collections.remove_entity(identifier)
+    }
+
+    async fn replace(&self, collections: Collections) {
+        *self.0.write().await = collections;
+    }
+
+    async fn lock(&self) -> RwLockWriteGuard<'_, Collections> {
+        self.0.write().await
+    }
+}
+
+impl CollectionsState {
+    /// Applies `edit` to every collection under one lock and saves them if it
+    /// changed any.
+    pub async fn edit_collections(
+        &self,
+        mut edit: impl FnMut(&mut Collection) -> bool,
+    ) -> Result<(), String> {
+        let mut collections = self.0.write().await;
+        let mut changed = false;
+        for collection in collections.borrow_entities_mut() {
+            changed |= edit(collection);
+        }
+        if changed {
+            collections.store_entities()?;
+        }
+        Ok(())
+    }
+
+    /// Drops a deleted card from the collections listing it.
+    pub async fn remove_card(&self, card_kind: CardKind, card: &str) -> Result<(), String> {
+        self.edit_collections(|collection| {
+            let members = match card_kind {
+                CardKind::Word => &mut collection.words,
+                CardKind::Kanji => &mut collection.kanjis,
+            };
+            let before = members.len();
+            members.retain(|member| member != card);
+            members.len() != before
+        })
+        .await
+    }
+
+    async fn get_collection(&self, id: &String) -> Result<Collection, String> {
+        self.get_entities()
+            .await
+            .into_iter()
+            .find(|c| &c.id == id)
+            .ok_or("Collection does not exist".to_string())
+    }
+}
+
+pub struct Collections {
+    store: Arc<Store<Wry>>,
+    collections: Vec<Collection>,
+}
+impl Entities for Collections {
+    type Entity = Collection;
+    type EntityCreatePayload = CollectionCreatePayload;
+
+    fn new(store: Arc<Store<Wry>>) -> Self {
+        Self {
+            store,
+            collections: Vec::new(),
+        }
+    }
+    fn get_store(&self) -> &Arc<Store<Wry>> {
+        &self.store
+    }
+    fn borrow_entities(&self) -> &Vec<Self::Entity> {
+        &self.collections
+    }
+    fn borrow_entities_mut(&mut self) -> &mut Vec<Self::Entity> {
+        &mut self.collections
+    }
+}
+
+/// A user defined deck, e.g. "Genki ch.5". Cards belong to it when listed
+/// explicitly or when tagged with one of its tags.
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub words: Vec<String>,
+    pub kanjis: Vec<String>,
+    /// Tags below these count too.
+    pub tags: Vec<String>,
+    pub created_at: u32,
+}
+impl Entity for Collection {
+    /// Names can be changed, so collections are kept apart by id.
+    fn identifier(&self) -> String {
+        self.id.clone()
+    }
+}
+
+impl Collection {
+    fn matches_tags(&self, card_tags: &[String]) -> bool {
+        card_tags
+            .iter()
+            .any(|tag| self.tags.iter().any(|t| is_within(tag, t)))
+    }
+
+    pub fn contains_word(&self, word: &Word) -> bool {
+        self.words.contains(&word.word) || self.matches_tags(&word.tags)
+    }
+
+    pub fn contains_kanji(&self, kanji: &Kanji) -> bool {
+        self.kanjis.contains(&kanji.kanji) || self.matches_tags(&kanji.tags)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+pub struct CollectionCreatePayload {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+impl EntityCreatePayload<Collection> for CollectionCreatePayload {
+    fn to_entity(&self) -> Collection {
+        Collection {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: self.name.trim().to_string(),
+            description: self.description.clone(),
+            words: vec![],
+            kanjis: vec![],
+            tags: normalize_tags(&self.tags),
+            created_at: super::unix_timestamp(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, specta::Type)]
+pub struct CollectionCards {
+    pub words: Vec<Word>,
+    pub kanjis: Vec<Kanji>,
+}
+
+#[derive(Serialize, Debug, Clone, specta::Type)]
+pub struct StudyCard {
+    pub card_kind: CardKind,
+    pub card: String,
+    /// Never reviewed.
+    pub new: bool,
+    pub due: u32,
+}
+
+async fn collection_cards(
+    collection: &Collection,
+    words_state: &WordsState,
+    kanjis_state: &KanjisState,
+) -> CollectionCards {
+    CollectionCards {
+        words: words_state
+            .get_words()
+            .await
+            .into_iter()
+            .filter(|w| collection.contains_word(w))
+            .collect(),
+        kanjis: kanjis_state
+            .get_entities()
+            .await
+            .into_iter()
+            .filter(|k| collection.contains_kanji(k))
+            .collect(),
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_collections(
+    state: tauri::State<'_, CollectionsState>,
+) -> Result<Vec<Collection>, String> {
+    Ok(state.get_entities().await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn add_collection(
+    state: tauri::State<'_, CollectionsState>,
+    payload: CollectionCreatePayload,
+) -> Result<Collection, String> {
+    if payload.name.trim().is_empty() {
+        return Err("Collection name is empty".to_string());
+    }
+    let name_taken = state
+        .get_entities()
+        .await
+        .iter()
+        .any(|c| c.name == payload.name.trim());
+    if name_taken {
+        return Err("Collection already exists".to_string());
+    }
+    state.add_entity(payload).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_collection(
+    state: tauri::State<'_, CollectionsState>,
+    mut collection: Collection,
+) -> Result<Collection, String> {
+    collection.tags = normalize_tags(&collection.tags);
+    state.update_entity(collection).await
+}
+
+/// Only the collection goes, its cards are kept.
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_collection(
+    state: tauri::State<'_, CollectionsState>,
+    id: String,
+) -> Result<Collection, String> {
+    state.remove_entity(&id).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn add_to_collection(
+    state: tauri::State<'_, CollectionsState>,
+    id: String,
+    card_kind: CardKind,
+    cards: Vec<String>,
+) -> Result<Collection, String> {
+    let mut collection = state.get_collection(&id).await?;
+    let members = match card_kind {
+        CardKind::Word => &mut collection.words,
+        CardKind::Kanji => &mut collection.kanjis,
+    };
+    for card in cards {
+        if !members.contains(&card) {
+            members.push(card);
+        }
+    }
+    state.update_entity(collection).await
+}
+
+/// Cards that belong through a tag stay in the collection, the tag has to be
+/// removed from them or from the collection instead.
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_from_collection(
+    state: tauri::State<'_, CollectionsState>,
+    id: String,
+    card_kind: CardKind,
+    cards: Vec<String>,
+) -> Result<Collection, String> {
+    let mut collection = state.get_collection(&id).await?;
+    let members = match card_kind {
+        CardKind::Word => &mut collection.words,
+        CardKind::Kanji => &mut collection.kanjis,
+    };
+    members.retain(|card| !cards.contains(card));
+    state.update_entity(collection).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_collection_cards(
+    state: tauri::State<'_, CollectionsState>,
+    words_state: tauri::State<'_, WordsState>,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    id: String,
+) -> Result<CollectionCards, String> {
+    let collection = state.get_collection(&id).await?;
+    Ok(collection_cards(&collection, &words_state, &kanjis_state).await)
+}
+
+/// Cards of the collection due for review now: reviewed cards first, most
+/// overdue first, then new ones in the order they were added.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_collection_study_queue(
+    state: tauri::State<'_, CollectionsState>,
+    words_state: tauri::State<'_, WordsState>,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    history: tauri::State<'_, HistoryState>,
+    id: String,
+    limit: u32,
+) -> Result<Vec<StudyCard>, String> {
+    let collection = state.get_collection(&id).await?;
+    let cards = collection_cards(&collection, &words_state, &kanjis_state).await;
+    let events = history.get_events().await;
+    let progress = super::stats::replay(&events);
+    let now = super::unix_timestamp();
+
+    let candidates = cards
+        .words
+        .iter()
+        .map(|w| (CardKind::Word, &w.word, w.created_at))
+        .chain(
+            cards
+                .kanjis
+                .iter()
+                .map(|k| (CardKind::Kanji, &k.kanji, k.created_at)),
+        );
+    let mut queue = candidates
+        .map(|(card_kind, card, created_at)| {
+            match progress.get(&(card_kind, card.as_str())) {
+                Some(p) => StudyCard {
+                    card_kind,
+                    card: card.clone(),
+                    new: p.reviews == 0,
+                    due: p.due,
+                },
+                // Saved before the history was recorded.
+                None => StudyCard {
+                    card_kind,
+                    card: card.clone(),
+                    new: true,
+                    due: created_at.unwrap_or_default(),
+                },
+            }
+        })
+        .filter(|c| c.due <= now)
+        .collect::<Vec<_>>();
+    queue.sort_by_key(|c| (c.new, c.due));
+    queue.truncate(limit as usize);
+    Ok(queue)
+}
@@ -2,13 +2,15 @@ use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 use tauri::{Manager, Wry};
-use tauri_plugin_store::{Store, StoreExt};
+use tauri_plugin_store::Store;
+use tokio::sync::RwLockWriteGuard;
 
 pub trait EntitiesState: Sized + Send + Sync + 'static {
     type Entities: Entities;
 
+    /// `store_file` is looked up in the active profile.
     fn setup(app: &mut tauri::App, store_file: &str) {
-        let store = app.store(store_file);
+        let store = crate::profile::store(app, store_file);
 
         if let Ok(store) = store {
             let mut entities = Self::Entities::new(store);
@@ -23,6 +25,24 @@ pub trait EntitiesState: Sized + Send + Sync + 'static {
         payload: <Self::Entities as Entities>::EntityCreatePayload,
     ) -> Result<<Self::Entities as Entities>::Entity, String>;
     async fn has_entity(&self, identifier: &String) -> bool;
+    async fn update_entity(
+        &self,
+        entity: <Self::Entities as Entities>::Entity,
+    ) -> Result<<Self::Entities as Entities>::Entity, String>;
+    async fn remove_entity(
+        &self,
+        identifier: &String,
+    ) -> Result<<Self::Entities as Entities>::Entity, String>;
+    async fn replace(&self, entities: Self::Entities);
+    /// Held to keep the entities, and their store, from changing meanwhile.
+    async fn lock(&self) -> RwLockWriteGuard<'_, Self::Entities>;
+
+    /// Swaps in the entities saved in `store`, e.g. after it was overwritten.
+    async fn reload(&self, store: Arc<Store<Wry>>) {
+        let mut entities = Self::Entities::new(store);
+        entities.load_entities();
+        self.replace(entities).await;
+    }
 }
 
 pub trait Entities {
@@ -72,6 +92,28 @@ pub trait Entities {
         self.store_entities()?;
         Ok(entity)
     }
+    /// Replaces the entity with the same identifier.
+    fn update_entity(&mut self, entity: Self::Entity) -> Result<Self::Entity, String> {
+        let identifier = entity.identifier();
+        let existing = self
+            .borrow_entities_mut()
+            .iter_mut()
+            .find(|e| e.identifier() == identifier)
+            .ok_or("Entity does not exist".to_string())?;
+        *existing = entity.clone();
+        self.store_entities()?;
+        Ok(entity)
+    }
+    fn remove_entity(&mut self, identifier: &String) -> Result<Self::Entity, String> {
+        let index = self
+            .borrow_entities()
+            .iter()
+            .position(|e| &e.identifier() == identifier)
+            .ok_or("Entity does not exist".to_string())?;
+        let entity = self.borrow_entities_mut().remove(index);
+        self.store_entities()?;
+        Ok(entity)
+    }
 }
 
 pub trait Entity: Sized + Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> {
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, Wry, async_runtime::RwLock};
+use tauri_plugin_store::Store;
+
+pub const HISTORY_STORE_FILE: &str = "history.json";
+
+pub fn setup(app: &mut tauri::App) {
+    let store = crate::profile::store(app, HISTORY_STORE_FILE);
+
+    if let Ok(store) = store {
+        let mut history = History {
+            store,
+            events: Vec::new(),
+        };
+        history.load_events();
+        app.manage(HistoryState::new(history));
+    }
+}
+
+pub struct HistoryState(pub Arc<RwLock<History>>);
+impl HistoryState {
+    pub fn new(history: History) -> Self {
+        Self(Arc::new(RwLock::new(history)))
+    }
+
+    pub async fn get_events(&self) -> Vec<HistoryEvent> {
+        let history = self.0.read().await;
+        history.get_events()
+    }
+
+    /// Swaps in the events saved in `store`, e.g. after it was overwritten.
+    pub async fn reload(&self, store: Arc<Store<Wry>>) {
+        let mut history = History {
+            store,
+            events: Vec::new(),
+        };
+        history.load_events();
+        *self.0.write().await = history;
+    }
+
+    pub async fn record(
+        &self,
+        card_kind: CardKind,
+        card: &str,
+        kind: HistoryEventKind,
+    ) -> Result<HistoryEvent, String> {
+        let mut history = self.0.write().await;
+        history.append(HistoryEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: super::unix_timestamp(),
+            card_kind,
+            card: card.to_string(),
+            kind,
+        })
+    }
+}
+
+/// Append-only, events are never edited or removed.
+pub struct History {
+    store: Arc<Store<Wry>>,
+    events: Vec<HistoryEvent>,
+}
+
+impl History {
+    pub fn load_events(&mut self) -> Option<Vec<HistoryEvent>> {
+        let loaded_events = self.store.get("events").and_then(|data| {
+            serde_json::from_value::<Vec<HistoryEvent>>(data)
+                .map_err(|e| e.to_string())
+                .ok()
+        });
+
+        if let Some(events) = loaded_events {
+            self.events = events.clone();
+            return Some(events);
+        }
+        None
+    }
+
+    pub fn get_events(&self) -> Vec<HistoryEvent> {
+        self.events.clone()
+    }
+
+    fn append(&mut self, event: HistoryEvent) -> Result<HistoryEvent, String> {
+        self.events.push(event.clone());
+        let events_value = serde_json::to_value(&self.events).map_err(|e| e.to_string())?;
+        self.store.set("events", events_value);
+        Ok(event)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, specta::Type)]
+pub enum CardKind {
+    Word,
+    Kanji,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, specta::Type)]
+pub enum ReviewGrade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, specta::Type)]
+pub enum HistoryEventKind {
+    Added,
+    Reviewed { grade: ReviewGrade },
+    Edited,
+    Deleted,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+pub struct HistoryEvent {
+    pub id: String,
+    pub timestamp: u32,
+    pub card_kind: CardKind,
+    /// The word or kanji itself, ids don't survive deleting and re-adding.
+    pub card: String,
+    pub kind: HistoryEventKind,
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_history(
+    state: tauri::State<'_, HistoryState>,
+) -> Result<Vec<HistoryEvent>, String> {
+    Ok(state.get_events().await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn record_review(
+    state: tauri::State<'_, HistoryState>,
+    card_kind: CardKind,
+    card: String,
+    grade: ReviewGrade,
+) -> Result<HistoryEvent, String> {
+    state
+        .record(card_kind, &card, HistoryEventKind::Reviewed { grade })
+        .await
+}
@@ -1,9 +1,14 @@
 use std::sync::Arc;
 
+use super::collections::CollectionsState;
 use super::entity::*;
+use super::history::{CardKind, HistoryEventKind, HistoryState};
+use crate::data::heisig_kanji::{HeisigKanji, JouYou};
+use crate::data::kanjidic2::Kanjidic2Entry;
 use serde::{Deserialize, Serialize};
-use tauri::{Wry, async_runtime::RwLock};
+use tauri::{Manager, Wry, async_runtime::RwLock};
 use tauri_plugin_store::Store;
+use tokio::sync::RwLockWriteGuard;
 
 #[derive()]
 pub struct KanjisState(Arc<RwLock<Kanjis>>);
@@ -28,6 +33,24 @@ impl EntitiesState for KanjisState {
         let kanjis = self.0.read().await;
         kanjis.has_entity(identifier)
     }
+
+    async fn update_entity(&self, kanji: Kanji) -> Result<Kanji, String> {
+        let mut kanjis = self.0.write().await;
+        kanjis.update_entity(kanji)
+    }
+
+    async fn remove_entity(&self, identifier: &String) -> Result<Kanji, String> {
+        let mut kanjis = self.0.write().await;
+        kanjis.remove_entity(identifier)
+    }
+
+    async fn replace(&self, kanjis: Kanjis) {
+        *self.0.write().await = kanjis;
+    }
+
+    async fn lock(&self) -> RwLockWriteGuard<'_, Kanjis> {
+        self.0.write().await
+    }
 }
 
 pub struct Kanjis {
@@ -63,6 +86,9 @@ pub struct Kanji {
     pub tags: Vec<String>,
     pub writing_mnemonic: Option<String>,
     pub reading_mnemonic: Option<String>,
+    /// Missing on kanji saved before it was recorded.
+    #[serde(default)]
+    pub created_at: Option<u32>,
 }
 impl Entity for Kanji {
     fn identifier(&self) -> String {
@@ -84,11 +110,104 @@ impl EntityCreatePayload<Kanji> for KanjiCreatePayload {
             id: uuid::Uuid::new_v4().to_string(),
             kanji: self.kanji.clone(),
             readings: self.readings.clone(),
-            tags: self.tags.clone(),
+            tags: super::tags::normalize_tags(&self.tags),
             writing_mnemonic: self.writing_mnemonic.clone(),
             reading_mnemonic: self.reading_mnemonic.clone(),
+            created_at: Some(super::unix_timestamp()),
+        }
+    }
+}
+
+impl KanjiCreatePayload {
+    /// Pre-filled from the bundled data: readings from kanjidic2, falling
+    /// back to Heisig's, the Heisig story as writing mnemonic and the JLPT
+    /// level and Jōyō grade as tags.
+    pub fn from_data(
+        kanji: &str,
+        kanjidic2: Option<&Kanjidic2Entry>,
+        heisig: Option<&HeisigKanji>,
+    ) -> Self {
+        let split = |readings: &str| {
+            readings
+                .split(';')
+                .map(|r| r.trim().to_string())
+                .filter(|r| !r.is_empty())
+                .collect::<Vec<_>>()
+        };
+        let mut readings = kanjidic2
+            .map(|entry| [split(&entry.ja_on), split(&entry.ja_kun)].concat())
+            .unwrap_or_default();
+        if readings.is_empty() {
+            readings = heisig
+                .map(|heisig| {
+                    heisig
+                        .on_yomi
+                        .iter()
+                        .chain(heisig.kun_yomi.iter())
+                        .map(|r| r.to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+
+        let writing_mnemonic = heisig.and_then(|heisig| {
+            [&heisig.heisig_story, &heisig.koohii_story_1]
+                .into_iter()
+                .find(|story| !story.is_empty())
+                .cloned()
+        });
+
+        let mut tags = Vec::new();
+        if let Some(level) = heisig.and_then(|heisig| heisig.jlpt) {
+            tags.push(format!("jlpt::n{}", level));
+        }
+        match heisig.and_then(|heisig| heisig.jou_you.as_ref()) {
+            Some(JouYou::Grade(grade)) => tags.push(format!("jouyou::grade-{}", grade)),
+            Some(JouYou::JuniorHigh) => tags.push("jouyou::junior-high".to_string()),
+            Some(JouYou::Names) | None => {}
+        }
+
+        KanjiCreatePayload {
+            kanji: kanji.to_string(),
+            readings,
+            writing_mnemonic,
+            reading_mnemonic: None,
+            tags,
+        }
+    }
+}
+
+/// Adds the kanji of `word` that aren't in the knowledge base yet, see
+/// `KanjiCreatePayload::from_data`. Returns the added ones.
+pub async fn add_missing_kanjis(
+    app_handle: &tauri::AppHandle,
+    word: &str,
+) -> Result<Vec<Kanji>, String> {
+    let state = app_handle.state::<KanjisState>();
+    let history = app_handle.state::<HistoryState>();
+    let heisig_kanjis = crate::data::get_heisig_kanjis(app_handle);
+    let kanjidic2 = crate::data::get_kanjidic2_entries_reader(app_handle);
+    let kanjidic2 = kanjidic2.0.read().await;
+
+    let mut added = Vec::new();
+    for c in word.chars().filter(|c| wana_kana::utils::is_char_kanji(*c)) {
+        let kanji = c.to_string();
+        if state.has_entity(&kanji).await {
+            continue;
         }
+        let heisig = heisig_kanjis.get_by_kanji(&kanji).into_iter().next();
+        let payload = KanjiCreatePayload::from_data(
+            &kanji,
+            kanjidic2.find_by_kanji(&kanji).as_ref(),
+            heisig.as_deref(),
+        );
+        let kanji = state.add_entity(payload).await?;
+        history
+            .record(CardKind::Kanji, &kanji.kanji, HistoryEventKind::Added)
+            .await?;
+        added.push(kanji);
     }
+    Ok(added)
 }
 
 #[tauri::command]
@@ -101,9 +220,46 @@ pub async fn get_kanjis(state: tauri::State<'_, KanjisState>) -> Result<Vec<Kanj
 #[specta::specta]
 pub async fn add_kanji(
     state: tauri::State<'_, KanjisState>,
+    history: tauri::State<'_, HistoryState>,
     payload: KanjiCreatePayload,
 ) -> Result<Kanji, String> {
-    state.add_entity(payload).await
+    let kanji = state.add_entity(payload).await?;
+    history
+        .record(CardKind::Kanji, &kanji.kanji, HistoryEventKind::Added)
+        .await?;
+    Ok(kanji)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_kanji(
+    state: tauri::State<'_, KanjisState>,
+    history: tauri::State<'_, HistoryState>,
+    kanji: Kanji,
+) -> Result<Kanji, String> {
+    let kanji = state.update_entity(kanji).await?;
+    history
+        .record(CardKind::Kanji, &kanji.kanji, HistoryEventKind::Edited)
+        .await?;
+    Ok(kanji)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_kanji(
+    state: tauri::State<'_, KanjisState>,
+    collections_state: tauri::State<'_, CollectionsState>,
+    history: tauri::State<'_, HistoryState>,
+    kanji: String,
+) -> Result<Kanji, String> {
+    let kanji = state.remove_entity(&kanji).await?;
+    collections_state
+        .remove_card(CardKind::Kanji, &kanji.kanji)
+        .await?;
+    history
+        .record(CardKind::Kanji, &kanji.kanji, HistoryEventKind::Deleted)
+        .await?;
+    Ok(kanji)
 }
 
 #[tauri::command]
@@ -0,0 +1,521 @@
+use std::sync::Arc;
+
+use super::entity::*;
+use super::history::{CardKind, HistoryEventKind, HistoryState};
+use super::kanjis::KanjisState;
+use super::reading_sounds::{ReadingSoundsState, is_read_with, normalize_sound};
+use crate::data::heisig_kanji::{HeisigKanji, HeisigKanjis, story_form, strip_html};
+use serde::{Deserialize, Serialize};
+use tauri::{Wry, async_runtime::RwLock};
+use tauri_plugin_store::Store;
+use tokio::sync::RwLockWriteGuard;
+
+pub struct MnemonicsState(Arc<RwLock<Mnemonics>>);
+impl EntitiesState for MnemonicsState {
+    type Entities = Mnemonics;
+
+    fn new(mnemonics: Mnemonics) -> Self {
+        Self(Arc::new(RwLock::new(mnemonics)))
+    }
+
+    async fn get_entities(&self) -> Vec<Mnemonic> {
+        let mnemonics = self.0.read().await;
+        mnemonics.get_entities()
+    }
+
+    async fn add_entity(&self, payload: MnemonicCreatePayload) -> Result<Mnemonic, String> {
+        let mut mnemonics = self.0.write().await;
+        mnemonics.add_entity(payload)
+    }
+
+    async fn has_entity(&self, identifier: &String) -> bool {
+        let mnemonics = self.0.read().await;
+        mnemonics.has_entity(identifier)
+    }
+
+    async fn update_entity(&self, mnemonic: Mnemonic) -> Result<Mnemonic, String> {
+        let mut mnemonics = self.0.write().await;
+        mnemonics.update_entity(mnemonic)
+    }
+
+    async fn remove_entity(&self, identifier: &String) -> Result<Mnemonic, String> {
+        let mut mnemonics = self.0.write().await;
+        mnemonics.remove_entity(identifier)
+    }
+
+    async fn replace(&self, mnemonics: Mnemonics) {
+        *self.0.write().await = mnemonics;
+    }
+
+    async fn lock(&self) -> RwLockWriteGuard<'_, Mnemonics> {
+        self.0.write().await
+    }
+}
+
+impl MnemonicsState {
+    async fn get_mnemonic(&self, id: &String) -> Result<Mnemonic, String> {
+        self.get_entities()
+            .await
+            .into_iter()
+            .find(|m| &m.id == id)
+            .ok_or("Mnemonic does not exist".to_string())
+    }
+
+    async fn get_active(&self, kanji: &str, kind: MnemonicKind) -> Option<Mnemonic> {
+        self.get_entities()
+            .await
+            .into_iter()
+            .find(|m| m.kanji == kanji && m.kind == kind && m.active)
+    }
+}
+
+pub struct Mnemonics {
+    store: Arc<Store<Wry>>,
+    mnemonics: Vec<Mnemonic>,
+}
+impl Entities for Mnemonics {
+    type Entity = Mnemonic;
+    type EntityCreatePayload = MnemonicCreatePayload;
+
+    fn new(store: Arc<Store<Wry>>) -> Self {
+        Self {
+            store,
+            mnemonics: Vec::new(),
+        }
+    }
+    fn get_store(&self) -> &Arc<Store<Wry>> {
+        &self.store
+    }
+    fn borrow_entities(&self) -> &Vec<Self::Entity> {
+        &self.mnemonics
+    }
+    fn borrow_entities_mut(&mut self) -> &mut Vec<Self::Entity> {
+        &mut self.mnemonics
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, specta::Type)]
+pub enum MnemonicKind {
+    Writing,
+    Reading,
+}
+
+/// Where the first version of a story came from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, specta::Type)]
+pub enum MnemonicSource {
+    Own,
+    Heisig,
+    Koohii,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+pub struct MnemonicVersion {
+    pub text: String,
+    pub edited_at: u32,
+}
+
+/// A primitive of the kanji the story mentions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, specta::Type)]
+pub struct PrimitiveLink {
+    /// As Heisig names it, e.g. "top hat".
+    pub primitive: String,
+    /// The kanji with the primitive's name as keyword, if there is one.
+    pub kanji: Option<String>,
+}
+
+/// One of possibly several stories for a kanji. The active one of each kind
+/// is also shown as the kanji's `writing_mnemonic` or `reading_mnemonic`.
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+pub struct Mnemonic {
+    pub id: String,
+    pub kanji: String,
+    pub kind: MnemonicKind,
+    pub source: MnemonicSource,
+    /// Oldest first, the last one is the current text.
+    pub versions: Vec<MnemonicVersion>,
+    pub primitives: Vec<PrimitiveLink>,
+    /// Reading mnemonics can be built around the anchor of one of the
+    /// kanji's on'yomi, see `reading_sounds::ReadingSound`.
+    #[serde(default)]
+    pub sound: Option<String>,
+    pub active: bool,
+    pub created_at: u32,
+}
+impl Entity for Mnemonic {
+    fn identifier(&self) -> String {
+        self.id.clone()
+    }
+}
+
+impl Mnemonic {
+    pub fn text(&self) -> &str {
+        self.versions
+            .last()
+            .map(|v| v.text.as_str())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+pub struct MnemonicCreatePayload {
+    pub kanji: String,
+    pub kind: MnemonicKind,
+    pub source: MnemonicSource,
+    pub text: String,
+    #[serde(default)]
+    pub sound: Option<String>,
+}
+impl EntityCreatePayload<Mnemonic> for MnemonicCreatePayload {
+    fn to_entity(&self) -> Mnemonic {
+        let now = super::unix_timestamp();
+        Mnemonic {
+            id: uuid::Uuid::new_v4().to_string(),
+            kanji: self.kanji.clone(),
+            kind: self.kind,
+            source: self.source,
+            versions: vec![MnemonicVersion {
+                text: self.text.trim().to_string(),
+                edited_at: now,
+            }],
+            primitives: vec![],
+            sound: self.sound.as_deref().map(normalize_sound),
+            active: false,
+            created_at: now,
+        }
+    }
+}
+
+/// A Heisig or Koohii story to start from.
+#[derive(Serialize, Debug, Clone, specta::Type)]
+pub struct MnemonicSuggestion {
+    pub source: MnemonicSource,
+    pub text: String,
+    pub primitives: Vec<PrimitiveLink>,
+}
+
+/// Whether `story` contains `name` starting at a word boundary, so "one"
+/// matches "ones" but not "someone".
+fn mentions(story: &str, name: &str) -> bool {
+    let name = story_form(name);
+    if name.is_empty() {
+        return false;
+    }
+    let story = strip_html(story).to_lowercase();
+    story.match_indices(&name).any(|(start, _)| {
+        !story[..start]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// The primitives of `heisig` mentioned in `story`, linked to the kanji
+/// they're named after. The kanji's own keyword is listed among them but
+/// isn't one.
+fn link_primitives(
+    story: &str,
+    heisig: &HeisigKanji,
+    heisig_kanjis: &HeisigKanjis,
+) -> Vec<PrimitiveLink> {
+    let keyword = story_form(&heisig.keyword);
+    let mut links: Vec<PrimitiveLink> = Vec::new();
+    for primitive in &heisig.constituents {
+        let name = story_form(primitive);
+        if name == keyword || !mentions(story, &name) || links.iter().any(|l| l.primitive == name) {
+            continue;
+        }
+        let kanji = heisig_kanjis.get_by_keyword(&name).map(|k| k.kanji.clone());
+        links.push(PrimitiveLink {
+            primitive: name,
+            kanji,
+        });
+    }
+    links
+}
+
+/// Writing stories have to mention the kanji's keyword, that's what they're
+/// for. Kanji Heisig doesn't cover have no keyword to check.
+fn validate(text: &str, kind: MnemonicKind, heisig: Option<&HeisigKanji>) -> Result<(), String> {
+    if text.trim().is_empty() {
+        return Err("Mnemonic is empty".to_string());
+    }
+    match (kind, heisig) {
+        (MnemonicKind::Writing, Some(heisig)) if !mentions(text, &heisig.keyword) => Err(format!(
+            "The story doesn't mention the keyword \"{}\"",
+            heisig.keyword
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Only reading mnemonics reference a sound, only a sound with an anchor, and
+/// only one `kanji` is read with.
+async fn validate_sound(
+    app_handle: &tauri::AppHandle,
+    sounds_state: &ReadingSoundsState,
+    kanjis_state: &KanjisState,
+    kanji: &String,
+    kind: MnemonicKind,
+    sound: Option<&String>,
+) -> Result<(), String> {
+    let Some(sound) = sound else {
+        return Ok(());
+    };
+    if kind != MnemonicKind::Reading {
+        return Err("Only reading mnemonics can reference a reading sound".to_string());
+    }
+    let normalized = normalize_sound(sound);
+    if !sounds_state.has_entity(&normalized).await {
+        return Err(format!("No anchor for the reading {} yet", sound));
+    }
+
+    let readings = kanjis_state
+        .get_entities()
+        .await
+        .into_iter()
+        .find(|k| &k.kanji == kanji)
+        .map(|k| k.readings)
+        .unwrap_or_default();
+    let kanjidic2 = crate::data::get_kanjidic2_entries_reader(app_handle);
+    let kanjidic2 = kanjidic2.0.read().await;
+    if !is_read_with(kanji, &readings, &normalized, &kanjidic2) {
+        return Err(format!("{} is not read {}", kanji, sound));
+    }
+    Ok(())
+}
+
+fn find_heisig(app_handle: &tauri::AppHandle, kanji: &String) -> Option<Arc<HeisigKanji>> {
+    crate::data::get_heisig_kanjis(app_handle)
+        .get_by_kanji(kanji)
+        .into_iter()
+        .next()
+}
+
+/// Writes `text` into the kanji's field for `kind`, if the kanji is in the
+/// knowledge base.
+async fn show_on_kanji(
+    kanjis_state: &KanjisState,
+    history: &HistoryState,
+    kanji: &str,
+    kind: MnemonicKind,
+    text: Option<String>,
+) -> Result<(), String> {
+    let Some(mut entity) = kanjis_state
+        .get_entities()
+        .await
+        .into_iter()
+        .find(|k| k.kanji == kanji)
+    else {
+        return Ok(());
+    };
+    match kind {
+        MnemonicKind::Writing => entity.writing_mnemonic = text,
+        MnemonicKind::Reading => entity.reading_mnemonic = text,
+    }
+    kanjis_state.update_entity(entity).await?;
+    history
+        .record(CardKind::Kanji, kanji, HistoryEventKind::Edited)
+        .await?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_mnemonics(
+    state: tauri::State<'_, MnemonicsState>,
+    kanji: String,
+) -> Result<Vec<Mnemonic>, String> {
+    Ok(state
+        .get_entities()
+        .await
+        .into_iter()
+        .filter(|m| m.kanji == kanji)
+        .collect())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_mnemonic_suggestions(
+    app_handle: tauri::AppHandle,
+    kanji: String,
+) -> Result<Vec<MnemonicSuggestion>, String> {
+    let Some(heisig) = find_heisig(&app_handle, &kanji) else {
+        return Ok(vec![]);
+    };
+    let heisig_kanjis = crate::data::get_heisig_kanjis(&app_handle);
+    let stories = [
+        (MnemonicSource::Heisig, &heisig.heisig_story),
+        (MnemonicSource::Koohii, &heisig.koohii_story_1),
+        (MnemonicSource::Koohii, &heisig.koohii_story_2),
+    ];
+    Ok(stories
+        .into_iter()
+        .filter(|(_, story)| !story.is_empty())
+        .map(|(source, story)| MnemonicSuggestion {
+            source,
+            text: story.clone(),
+            primitives: link_primitives(story, &heisig, &heisig_kanjis),
+        })
+        .collect())
+}
+
+/// The first mnemonic of its kind for the kanji becomes the active one.
+#[tauri::command]
+#[specta::specta]
+pub async fn add_mnemonic(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, MnemonicsState>,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    history: tauri::State<'_, HistoryState>,
+    sounds_state: tauri::State<'_, ReadingSoundsState>,
+    payload: MnemonicCreatePayload,
+) -> Result<Mnemonic, String> {
+    let heisig = find_heisig(&app_handle, &payload.kanji);
+    validate(&payload.text, payload.kind, heisig.as_deref())?;
+    validate_sound(
+        &app_handle,
+        &sounds_state,
+        &kanjis_state,
+        &payload.kanji,
+        payload.kind,
+        payload.sound.as_ref(),
+    )
+    .await?;
+
+    let first = state
+        .get_active(&payload.kanji, payload.kind)
+        .await
+        .is_none();
+    let mut mnemonic = state.add_entity(payload).await?;
+    if let Some(heisig) = &heisig {
+        let heisig_kanjis = crate::data::get_heisig_kanjis(&app_handle);
+        mnemonic.primitives = link_primitives(mnemonic.text(), heisig, &heisig_kanjis);
+    }
+    if first {
+        mnemonic.active = true;
+        let text = Some(mnemonic.text().to_string());
+        show_on_kanji(
+            &kanjis_state,
+            &history,
+            &mnemonic.kanji,
+            mnemonic.kind,
+            text,
+        )
+        .await?;
+    }
+    state.update_entity(mnemonic).await
+}
+
+/// Saves `text` as a new version, the previous ones are kept.
+#[tauri::command]
+#[specta::specta]
+pub async fn edit_mnemonic(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, MnemonicsState>,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    history: tauri::State<'_, HistoryState>,
+    id: String,
+    text: String,
+) -> Result<Mnemonic, String> {
+    let mut mnemonic = state.get_mnemonic(&id).await?;
+    let heisig = find_heisig(&app_handle, &mnemonic.kanji);
+    validate(&text, mnemonic.kind, heisig.as_deref())?;
+    if text.trim() == mnemonic.text() {
+        return Ok(mnemonic);
+    }
+
+    mnemonic.versions.push(MnemonicVersion {
+        text: text.trim().to_string(),
+        edited_at: super::unix_timestamp(),
+    });
+    if let Some(heisig) = &heisig {
+        let heisig_kanjis = crate::data::get_heisig_kanjis(&app_handle);
+        mnemonic.primitives = link_primitives(mnemonic.text(), heisig, &heisig_kanjis);
+    }
+    if mnemonic.active {
+        let text = Some(mnemonic.text().to_string());
+        show_on_kanji(
+            &kanjis_state,
+            &history,
+            &mnemonic.kanji,
+            mnemonic.kind,
+            text,
+        )
+        .await?;
+    }
+    state.update_entity(mnemonic).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_active_mnemonic(
+    state: tauri::State<'_, MnemonicsState>,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    history: tauri::State<'_, HistoryState>,
+    id: String,
+) -> Result<Mnemonic, String> {
+    let mut mnemonic = state.get_mnemonic(&id).await?;
+    if let Some(mut active) = state.get_active(&mnemonic.kanji, mnemonic.kind).await {
+        active.active = false;
+        state.update_entity(active).await?;
+    }
+    mnemonic.active = true;
+    let text = Some(mnemonic.text().to_string());
+    show_on_kanji(
+        &kanjis_state,
+        &history,
+        &mnemonic.kanji,
+        mnemonic.kind,
+        text,
+    )
+    .await?;
+    state.update_entity(mnemonic).await
+}
+
+/// Builds the reading mnemonic around the anchor of `sound`, or around none.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_mnemonic_sound(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, MnemonicsState>,
+    sounds_state: tauri::State<'_, ReadingSoundsState>,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    id: String,
+    sound: Option<String>,
+) -> Result<Mnemonic, String> {
+    let mut mnemonic = state.get_mnemonic(&id).await?;
+    validate_sound(
+        &app_handle,
+        &sounds_state,
+        &kanjis_state,
+        &mnemonic.kanji,
+        mnemonic.kind,
+        sound.as_ref(),
+    )
+    .await?;
+    mnemonic.sound = sound.as_deref().map(normalize_sound);
+    state.update_entity(mnemonic).await
+}
+
+/// Removing the active mnemonic leaves the kanji without one of its kind.
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_mnemonic(
+    state: tauri::State<'_, MnemonicsState>,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    history: tauri::State<'_, HistoryState>,
+    id: String,
+) -> Result<Mnemonic, String> {
+    let mnemonic = state.remove_entity(&id).await?;
+    if mnemonic.active {
+        show_on_kanji(
+            &kanjis_state,
+            &history,
+            &mnemonic.kanji,
+            mnemonic.kind,
+            None,
+        )
+        .await?;
+    }
+    Ok(mnemonic)
+}
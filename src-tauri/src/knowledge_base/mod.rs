@@ -1,12 +1,34 @@
+pub mod collections;
 pub mod entity;
+pub mod history;
 pub mod kanjis;
+pub mod mnemonics;
+pub mod reading_sounds;
+pub mod stats;
+pub mod tags;
 pub mod words;
 
 use entity::*;
 
-const KANJIS_STORE_FILE: &str = "kanjis.json";
+pub const KANJIS_STORE_FILE: &str = "kanjis.json";
+pub const COLLECTIONS_STORE_FILE: &str = "collections.json";
+pub const MNEMONICS_STORE_FILE: &str = "mnemonics.json";
+pub const READING_SOUNDS_STORE_FILE: &str = "reading_sounds.json";
+
+/// Seconds since the Unix epoch. A `u32` because specta refuses to export
+/// 64-bit integers to TypeScript.
+pub fn unix_timestamp() -> u32 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as u32)
+        .unwrap_or_default()
+}
 
 pub fn setup(app: &mut tauri::App) {
+    history::setup(app);
     words::setup(app);
     kanjis::KanjisState::setup(app, KANJIS_STORE_FILE);
+    collections::CollectionsState::setup(app, COLLECTIONS_STORE_FILE);
+    mnemonics::MnemonicsState::setup(app, MNEMONICS_STORE_FILE);
+    reading_sounds::ReadingSoundsState::setup(app, READING_SOUNDS_STORE_FILE);
 }
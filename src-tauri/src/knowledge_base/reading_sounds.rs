@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use super::entity::*;
+use super::kanjis::{Kanji, KanjisState};
+use super::mnemonics::{Mnemonic, MnemonicsState};
+use crate::conversion::hiragana_to_katakana;
+use crate::data::kanjidic2::Kanjidic2;
+use serde::{Deserialize, Serialize};
+use tauri::{Wry, async_runtime::RwLock};
+use tauri_plugin_store::Store;
+use tokio::sync::RwLockWriteGuard;
+
+pub struct ReadingSoundsState(Arc<RwLock<ReadingSounds>>);
+impl EntitiesState for ReadingSoundsState {
+    type Entities = ReadingSounds;
+
+    fn new(sounds: ReadingSounds) -> Self {
+        Self(Arc::new(RwLock::new(sounds)))
+    }
+
+    async fn get_entities(&self) -> Vec<ReadingSound> {
+        let sounds = self.0.read().await;
+        sounds.get_entities()
+    }
+
+    async fn add_entity(&self, payload: ReadingSoundCreatePayload) -> Result<ReadingSound, String> {
+        let mut sounds = self.0.write().await;
+        sounds.add_entity(payload)
+    }
+
+    async fn has_entity(&self, identifier: &String) -> bool {
+        let sounds = self.0.read().await;
+        sounds.has_entity(identifier)
+    }
+
+    async fn update_entity(&self, sound: ReadingSound) -> Result<ReadingSound, String> {
+        let mut sounds = self.0.write().await;
+        sounds.update_entity(sound)
+    }
+
+    async fn remove_entity(&self, identifier: &String) -> Result<ReadingSound, String> {
+        let mut sounds = self.0.write().await;
+        sounds.remove_entity(identifier)
+    }
+
+    async fn replace(&self, sounds: ReadingSounds) {
+        *self.0.write().await = sounds;
+    }
+
+    async fn lock(&self) -> RwLockWriteGuard<'_, ReadingSounds> {
+        self.0.write().await
+    }
+}
+
+pub struct ReadingSounds {
+    store: Arc<Store<Wry>>,
+    sounds: Vec<ReadingSound>,
+}
+impl Entities for ReadingSounds {
+    type Entity = ReadingSound;
+    type EntityCreatePayload = ReadingSoundCreatePayload;
+
+    fn new(store: Arc<Store<Wry>>) -> Self {
+        Self {
+            store,
+            sounds: Vec::new(),
+        }
+    }
+    fn get_store(&self) -> &Arc<Store<Wry>> {
+        &self.store
+    }
+    fn borrow_entities(&self) -> &Vec<Self::Entity> {
+        &self.sounds
+    }
+    fn borrow_entities_mut(&mut self) -> &mut Vec<Self::Entity> {
+        &mut self.sounds
+    }
+}
+
+/// An on'yomi with the anchor every reading story for it is built around,
+/// e.g. こう → a cowboy, so 校, 交 and 効 all get a cowboy in their story.
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+pub struct ReadingSound {
+    pub id: String,
+    /// In katakana, the way on'yomi are written.
+    pub sound: String,
+    /// The character or actor standing for the sound.
+    pub anchor: String,
+    pub description: Option<String>,
+    pub created_at: u32,
+}
+impl Entity for ReadingSound {
+    fn identifier(&self) -> String {
+        self.sound.clone()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+pub struct ReadingSoundCreatePayload {
+    /// Hiragana is accepted too.
+    pub sound: String,
+    pub anchor: String,
+    pub description: Option<String>,
+}
+impl EntityCreatePayload<ReadingSound> for ReadingSoundCreatePayload {
+    fn to_entity(&self) -> ReadingSound {
+        ReadingSound {
+            id: uuid::Uuid::new_v4().to_string(),
+            sound: normalize_sound(&self.sound),
+            anchor: self.anchor.trim().to_string(),
+            description: self.description.clone(),
+            created_at: super::unix_timestamp(),
+        }
+    }
+}
+
+pub fn normalize_sound(sound: &str) -> String {
+    hiragana_to_katakana(sound.trim())
+}
+
+/// Returns `sound` normalized.
+fn validate(sound: &str, anchor: &str) -> Result<String, String> {
+    let normalized = normalize_sound(sound);
+    if !crate::conversion::is_katakana_word(&normalized) {
+        return Err(format!("{} is not a reading", sound));
+    }
+    if anchor.trim().is_empty() {
+        return Err("Anchor is empty".to_string());
+    }
+    Ok(normalized)
+}
+
+/// Whether `kanji` has the normalized `sound` as on'yomi, by its saved
+/// `readings` or by kanjidic2.
+pub fn is_read_with(
+    kanji: &String,
+    readings: &[String],
+    sound: &str,
+    kanjidic2: &Kanjidic2,
+) -> bool {
+    readings.iter().any(|reading| reading == sound)
+        || kanjidic2
+            .find_by_kanji(kanji)
+            .is_some_and(|entry| entry.ja_on.split(';').any(|on| on.trim() == sound))
+}
+
+/// Everything in the deck read with a sound.
+#[derive(Serialize, Debug, Clone, specta::Type)]
+pub struct ReadingSoundKanjis {
+    /// Missing when no anchor was picked for the sound yet.
+    pub sound: Option<ReadingSound>,
+    pub kanjis: Vec<Kanji>,
+    /// Reading mnemonics that reference the sound's anchor.
+    pub mnemonics: Vec<Mnemonic>,
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_reading_sounds(
+    state: tauri::State<'_, ReadingSoundsState>,
+) -> Result<Vec<ReadingSound>, String> {
+    Ok(state.get_entities().await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn add_reading_sound(
+    state: tauri::State<'_, ReadingSoundsState>,
+    payload: ReadingSoundCreatePayload,
+) -> Result<ReadingSound, String> {
+    let sound = validate(&payload.sound, &payload.anchor)?;
+    if state.has_entity(&sound).await {
+        return Err("Reading sound already exists".to_string());
+    }
+    state.add_entity(payload).await
+}
+
+/// The anchor and description can be changed, the sound can't: mnemonics
+/// reference it.
+#[tauri::command]
+#[specta::specta]
+pub async fn update_reading_sound(
+    state: tauri::State<'_, ReadingSoundsState>,
+    mut sound: ReadingSound,
+) -> Result<ReadingSound, String> {
+    sound.sound = validate(&sound.sound, &sound.anchor)?;
+    sound.anchor = sound.anchor.trim().to_string();
+    let existing = state
+        .get_entities()
+        .await
+        .into_iter()
+        .find(|s| s.id == sound.id)
+        .ok_or("Reading sound does not exist".to_string())?;
+    if existing.sound != sound.sound {
+        return Err("The sound can't be changed, add a new reading sound instead".to_string());
+    }
+    state.update_entity(sound).await
+}
+
+/// Reading mnemonics referencing the sound keep their text and lose the
+/// reference.
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_reading_sound(
+    state: tauri::State<'_, ReadingSoundsState>,
+    mnemonics_state: tauri::State<'_, MnemonicsState>,
+    sound: String,
+) -> Result<ReadingSound, String> {
+    let sound = state.remove_entity(&normalize_sound(&sound)).await?;
+    for mut mnemonic in mnemonics_state.get_entities().await {
+        if mnemonic.sound.as_ref() == Some(&sound.sound) {
+            mnemonic.sound = None;
+            mnemonics_state.update_entity(mnemonic).await?;
+        }
+    }
+    Ok(sound)
+}
+
+/// The kanji of the deck with `sound` as on'yomi, by their saved readings or
+/// by kanjidic2.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_reading_sound_kanjis(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, ReadingSoundsState>,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    mnemonics_state: tauri::State<'_, MnemonicsState>,
+    sound: String,
+) -> Result<ReadingSoundKanjis, String> {
+    let sound = normalize_sound(&sound);
+    let kanjidic2 = crate::data::get_kanjidic2_entries_reader(&app_handle);
+    let kanjidic2 = kanjidic2.0.read().await;
+
+    let kanjis = kanjis_state
+        .get_entities()
+        .await
+        .into_iter()
+        .filter(|kanji| is_read_with(&kanji.kanji, &kanji.readings, &sound, &kanjidic2))
+        .collect();
+    let mnemonics = mnemonics_state
+        .get_entities()
+        .await
+        .into_iter()
+        .filter(|m| m.sound.as_ref() == Some(&sound))
+        .collect();
+    Ok(ReadingSoundKanjis {
+        sound: state
+            .get_entities()
+            .await
+            .into_iter()
+            .find(|s| s.sound == sound),
+        kanjis,
+        mnemonics,
+    })
+}
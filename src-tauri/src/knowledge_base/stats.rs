@@ -0,0 +1,382 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::Serialize;
+
+use super::history::{CardKind, HistoryEvent, HistoryEventKind, HistoryState, ReviewGrade};
+
+const SECONDS_PER_DAY: u32 = 86_400;
+/// Days until the next review after the n-th successful review in a row.
+const REVIEW_INTERVALS: [u32; 8] = [1, 3, 7, 14, 30, 60, 120, 240];
+/// Kanji reviewed fewer times than this are too new to call weak.
+const WEAKEST_KANJI_MIN_REVIEWS: u32 = 2;
+const WEAKEST_KANJI_COUNT: usize = 10;
+
+#[derive(Serialize, Debug, Clone, specta::Type)]
+pub struct DailyCount {
+    /// Start of the day, UTC.
+    pub day: u32,
+    pub words: u32,
+    pub kanji: u32,
+}
+
+#[derive(Serialize, Debug, Clone, specta::Type)]
+pub struct WeakKanji {
+    pub kanji: String,
+    pub reviews: u32,
+    pub lapses: u32,
+    pub retention_rate: f64,
+}
+
+#[derive(Serialize, Debug, Clone, specta::Type)]
+pub struct LearningStats {
+    /// Oldest day first, ending today.
+    pub added_per_day: Vec<DailyCount>,
+    pub review_count: u32,
+    /// Share of reviews not graded `Again`, `None` before the first review.
+    pub retention_rate: Option<f64>,
+    /// Consecutive days with at least one review, up to today. A streak is
+    /// only broken once a whole day goes by without reviewing.
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    /// Reviews due per day starting today, overdue cards count for today.
+    /// Cards never reviewed aren't due for review yet.
+    pub forecast: Vec<DailyCount>,
+    pub weakest_kanji: Vec<WeakKanji>,
+}
+
+/// A card's state, replayed from its events.
+pub struct CardProgress {
+    /// Successful reviews in a row.
+    pub step: usize,
+    pub due: u32,
+    pub reviews: u32,
+    pub lapses: u32,
+}
+
+impl CardProgress {
+    fn new(added_at: u32) -> Self {
+        CardProgress {
+            step: 0,
+            due: added_at,
+            reviews: 0,
+            lapses: 0,
+        }
+    }
+
+    fn review(&mut self, grade: ReviewGrade, timestamp: u32) {
+        let interval = |step: usize| REVIEW_INTERVALS[step.min(REVIEW_INTERVALS.len() - 1)];
+        self.reviews += 1;
+        let days = match grade {
+            ReviewGrade::Again => {
+                self.step = 0;
+                self.lapses += 1;
+                0
+            }
+            ReviewGrade::Hard => interval(self.step.saturating_sub(1)),
+            ReviewGrade::Good => {
+                self.step += 1;
+                interval(self.step - 1)
+            }
+            ReviewGrade::Easy => {
+                self.step += 2;
+                interval(self.step - 1)
+            }
+        };
+        self.due = timestamp + days * SECONDS_PER_DAY;
+    }
+}
+
+fn day_of(timestamp: u32) -> u32 {
+    timestamp / SECONDS_PER_DAY
+}
+
+fn count_days(
+    days: impl Iterator<Item = u32>,
+    by_day: &BTreeMap<u32, (u32, u32)>,
+) -> Vec<DailyCount> {
+    days.map(|day| {
+        let (words, kanji) = by_day.get(&day).copied().unwrap_or_default();
+        DailyCount {
+            day: day * SECONDS_PER_DAY,
+            words,
+            kanji,
+        }
+    })
+    .collect()
+}
+
+fn increment(by_day: &mut BTreeMap<u32, (u32, u32)>, day: u32, card_kind: CardKind) {
+    let counts = by_day.entry(day).or_default();
+    match card_kind {
+        CardKind::Word => counts.0 += 1,
+        CardKind::Kanji => counts.1 += 1,
+    }
+}
+
+/// Progress of every card that wasn't deleted, keyed by kind and card.
+pub fn replay(events: &[HistoryEvent]) -> HashMap<(CardKind, &str), CardProgress> {
+    let mut cards = HashMap::new();
+    for event in events {
+        let key = (event.card_kind, event.card.as_str());
+        match &event.kind {
+            HistoryEventKind::Added => {
+                cards.insert(key, CardProgress::new(event.timestamp));
+            }
+            HistoryEventKind::Reviewed { grade } => {
+                // Cards added before the history existed start on their first review.
+                cards
+                    .entry(key)
+                    .or_insert_with(|| CardProgress::new(event.timestamp))
+                    .review(*grade, event.timestamp);
+            }
+            HistoryEventKind::Edited => {}
+            HistoryEventKind::Deleted => {
+                cards.remove(&key);
+            }
+        }
+    }
+    cards
+}
+
+/// `events` in the order they were recorded. `days` is how far the per-day
+/// counts reach back and the forecast reaches ahead.
+pub fn learning_stats(events: &[HistoryEvent], now: u32, days: u32) -> LearningStats {
+    let today = day_of(now);
+    let cards = replay(events);
+    let mut added_by_day = BTreeMap::new();
+    let mut review_days = BTreeSet::new();
+    let mut review_count = 0;
+    let mut failed_count = 0;
+
+    for event in events {
+        match &event.kind {
+            HistoryEventKind::Added => {
+                increment(&mut added_by_day, day_of(event.timestamp), event.card_kind);
+            }
+            HistoryEventKind::Reviewed { grade } => {
+                review_count += 1;
+                if *grade == ReviewGrade::Again {
+                    failed_count += 1;
+                }
+                review_days.insert(day_of(event.timestamp));
+            }
+            HistoryEventKind::Edited | HistoryEventKind::Deleted => {}
+        }
+    }
+
+    let first_day = (today + 1).saturating_sub(days);
+    let added_per_day = count_days(first_day..=today, &added_by_day);
+
+    let mut due_by_day = BTreeMap::new();
+    // New cards are studied, not reviewed, and would all pile up on today.
+    for ((card_kind, _), progress) in cards.iter().filter(|(_, p)| p.reviews > 0) {
+        increment(&mut due_by_day, day_of(progress.due).max(today), *card_kind);
+    }
+    let forecast = count_days(today..today + days, &due_by_day);
+
+    let mut longest_streak = 0;
+    let mut streak = 0;
+    let mut previous_day = None;
+    for day in &review_days {
+        streak = match previous_day {
+            Some(previous) if previous + 1 == *day => streak + 1,
+            _ => 1,
+        };
+        longest_streak = longest_streak.max(streak);
+        previous_day = Some(*day);
+    }
+    let current_streak = match previous_day {
+        Some(last) if last + 1 >= today => streak,
+        _ => 0,
+    };
+
+    let mut weakest_kanji = cards
+        .iter()
+        .filter(|((card_kind, _), progress)| {
+            *card_kind == CardKind::Kanji && progress.reviews >= WEAKEST_KANJI_MIN_REVIEWS
+        })
+        .map(|((_, kanji), progress)| WeakKanji {
+            kanji: kanji.to_string(),
+            reviews: progress.reviews,
+            lapses: progress.lapses,
+            retention_rate: 1.0 - progress.lapses as f64 / progress.reviews as f64,
+        })
+        .collect::<Vec<_>>();
+    weakest_kanji.sort_by(|a, b| {
+        a.retention_rate
+            .total_cmp(&b.retention_rate)
+            .then(b.lapses.cmp(&a.lapses))
+            .then_with(|| a.kanji.cmp(&b.kanji))
+    });
+    weakest_kanji.truncate(WEAKEST_KANJI_COUNT);
+
+    LearningStats {
+        added_per_day,
+        review_count,
+        retention_rate: (review_count > 0).then(|| 1.0 - failed_count as f64 / review_count as f64),
+        current_streak,
+        longest_streak,
+        forecast,
+        weakest_kanji,
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_learning_stats(
+    state: tauri::State<'_, HistoryState>,
+    days: u32,
+) -> Result<LearningStats, String> {
+    let events = state.get_events().await;
+    Ok(learning_stats(&events, super::unix_timestamp(), days))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u32 = 100 * SECONDS_PER_DAY + 12 * 3600;
+
+    fn event(day: u32, card_kind: CardKind, card: &str, kind: HistoryEventKind) -> HistoryEvent {
+        HistoryEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: day * SECONDS_PER_DAY + 9 * 3600,
+            card_kind,
+            card: card.to_string(),
+            kind,
+        }
+    }
+
+    fn added(day: u32, card_kind: CardKind, card: &str) -> HistoryEvent {
+        event(day, card_kind, card, HistoryEventKind::Added)
+    }
+
+    fn reviewed(day: u32, card_kind: CardKind, card: &str, grade: ReviewGrade) -> HistoryEvent {
+        event(day, card_kind, card, HistoryEventKind::Reviewed { grade })
+    }
+
+    fn reviewed_on(days: &[u32]) -> Vec<HistoryEvent> {
+        let mut events = vec![added(days[0], CardKind::Word, "猫")];
+        events.extend(
+            days.iter()
+                .map(|day| reviewed(*day, CardKind::Word, "猫", ReviewGrade::Good)),
+        );
+        events
+    }
+
+    #[test]
+    fn streak_runs_up_to_today() {
+        let stats = learning_stats(&reviewed_on(&[90, 91, 92, 97, 98, 99, 100]), NOW, 7);
+
+        assert_eq!(stats.current_streak, 4);
+        assert_eq!(stats.longest_streak, 4);
+    }
+
+    #[test]
+    fn streak_survives_until_today_ends() {
+        let stats = learning_stats(&reviewed_on(&[98, 99]), NOW, 7);
+
+        assert_eq!(stats.current_streak, 2);
+    }
+
+    #[test]
+    fn skipped_day_breaks_the_streak() {
+        let stats = learning_stats(&reviewed_on(&[90, 91, 92, 93, 94, 97, 98]), NOW, 7);
+
+        assert_eq!(stats.current_streak, 0);
+        assert_eq!(stats.longest_streak, 5);
+    }
+
+    #[test]
+    fn retention_counts_reviews_not_graded_again() {
+        let events = vec![
+            added(99, CardKind::Kanji, "未"),
+            reviewed(99, CardKind::Kanji, "未", ReviewGrade::Again),
+            reviewed(99, CardKind::Kanji, "未", ReviewGrade::Hard),
+            reviewed(100, CardKind::Kanji, "未", ReviewGrade::Good),
+            reviewed(100, CardKind::Kanji, "未", ReviewGrade::Again),
+        ];
+
+        let stats = learning_stats(&events, NOW, 7);
+
+        assert_eq!(stats.review_count, 4);
+        assert_eq!(stats.retention_rate, Some(0.5));
+        assert_eq!(stats.weakest_kanji.len(), 1);
+        assert_eq!(stats.weakest_kanji[0].lapses, 2);
+    }
+
+    #[test]
+    fn no_retention_before_the_first_review() {
+        let stats = learning_stats(&[added(100, CardKind::Word, "猫")], NOW, 7);
+
+        assert_eq!(stats.review_count, 0);
+        assert_eq!(stats.retention_rate, None);
+        assert_eq!(stats.current_streak, 0);
+    }
+
+    #[test]
+    fn added_cards_are_counted_per_day() {
+        let events = vec![
+            added(90, CardKind::Word, "犬"),
+            added(98, CardKind::Word, "猫"),
+            added(98, CardKind::Kanji, "猫"),
+            added(100, CardKind::Word, "鳥"),
+        ];
+
+        let stats = learning_stats(&events, NOW, 3);
+
+        let counts = stats
+            .added_per_day
+            .iter()
+            .map(|d| (d.day / SECONDS_PER_DAY, d.words, d.kanji))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [(98, 1, 1), (99, 0, 0), (100, 1, 0)]);
+    }
+
+    #[test]
+    fn forecast_buckets_reviews_by_due_day() {
+        let events = vec![
+            // Due tomorrow.
+            added(99, CardKind::Word, "猫"),
+            reviewed(100, CardKind::Word, "猫", ReviewGrade::Good),
+            // Due in three days.
+            added(90, CardKind::Kanji, "未"),
+            reviewed(90, CardKind::Kanji, "未", ReviewGrade::Good),
+            reviewed(91, CardKind::Kanji, "未", ReviewGrade::Good),
+            reviewed(100, CardKind::Kanji, "未", ReviewGrade::Hard),
+            // Overdue since day 91, due today.
+            added(90, CardKind::Word, "犬"),
+            reviewed(90, CardKind::Word, "犬", ReviewGrade::Good),
+            // Due beyond the forecast.
+            added(90, CardKind::Word, "鳥"),
+            reviewed(90, CardKind::Word, "鳥", ReviewGrade::Good),
+            reviewed(91, CardKind::Word, "鳥", ReviewGrade::Good),
+            reviewed(94, CardKind::Word, "鳥", ReviewGrade::Easy),
+            // Deleted cards aren't due at all.
+            added(90, CardKind::Word, "魚"),
+            reviewed(90, CardKind::Word, "魚", ReviewGrade::Good),
+            event(95, CardKind::Word, "魚", HistoryEventKind::Deleted),
+        ];
+
+        let stats = learning_stats(&events, NOW, 4);
+
+        let counts = stats
+            .forecast
+            .iter()
+            .map(|d| (d.day / SECONDS_PER_DAY, d.words, d.kanji))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [(100, 1, 0), (101, 1, 0), (102, 0, 0), (103, 0, 1)]);
+    }
+
+    #[test]
+    fn new_cards_are_not_due_for_review() {
+        let events = vec![
+            added(90, CardKind::Word, "猫"),
+            added(100, CardKind::Kanji, "未"),
+        ];
+
+        let stats = learning_stats(&events, NOW, 3);
+
+        assert!(stats.forecast.iter().all(|d| d.words == 0 && d.kanji == 0));
+    }
+}
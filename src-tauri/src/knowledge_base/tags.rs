@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::collections::CollectionsState;
+use super::entity::EntitiesState;
+use super::history::{CardKind, HistoryEventKind, HistoryState};
+use super::kanjis::KanjisState;
+use super::words::WordsState;
+
+/// Separates the levels of a hierarchical tag, `jlpt::n5` sits below `jlpt`.
+pub const TAG_SEPARATOR: &str = "::";
+
+/// Trims every level and drops empty ones, `" jlpt :: n5 "` → `jlpt::n5`.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let levels = tag
+        .split(TAG_SEPARATOR)
+        .map(str::trim)
+        .filter(|level| !level.is_empty())
+        .collect::<Vec<_>>();
+    (!levels.is_empty()).then(|| levels.join(TAG_SEPARATOR))
+}
+
+/// Normalized, sorted and without duplicates.
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized = tags
+        .iter()
+        .filter_map(|t| normalize_tag(t))
+        .collect::<Vec<_>>();
+    normalized.sort();
+    normalized.dedup();
+    normalized
+}
+
+/// Whether `tag` is `ancestor` itself or somewhere below it.
+pub fn is_within(tag: &str, ancestor: &str) -> bool {
+    tag.strip_prefix(ancestor)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(TAG_SEPARATOR))
+}
+
+/// `jlpt::n5::verbs` → `jlpt`, `jlpt::n5`, `jlpt::n5::verbs`.
+fn with_ancestors(tag: &str) -> Vec<String> {
+    let levels = tag.split(TAG_SEPARATOR).collect::<Vec<_>>();
+    (1..=levels.len())
+        .map(|n| levels[..n].join(TAG_SEPARATOR))
+        .collect()
+}
+
+fn add_to(tags: &mut Vec<String>, added: &[String]) {
+    for tag in added {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    tags.sort();
+}
+
+/// Removing a tag also removes the tags below it.
+fn remove_from(tags: &mut Vec<String>, removed: &[String]) {
+    tags.retain(|tag| !removed.iter().any(|r| is_within(tag, r)));
+}
+
+/// Moves `from` and everything below it to `to`.
+fn rename_in(tags: &mut Vec<String>, from: &str, to: &str) -> bool {
+    let mut changed = false;
+    for tag in tags.iter_mut() {
+        if is_within(tag, from) {
+            *tag = format!("{}{}", to, &tag[from.len()..]);
+            changed = true;
+        }
+    }
+    if changed {
+        tags.sort();
+        tags.dedup();
+    }
+    changed
+}
+
+#[derive(Serialize, Debug, Clone, specta::Type)]
+pub struct TagCount {
+    pub tag: String,
+    /// Cards tagged with the tag or one below it.
+    pub words: u32,
+    pub kanji: u32,
+}
+
+/// Applies `edit` to the listed cards and saves the ones it changed. Every
+/// saved card gets an `Edited` event.
+async fn edit_tags(
+    words_state: &WordsState,
+    kanjis_state: &KanjisState,
+    history: &HistoryState,
+    card_kind: CardKind,
+    cards: &[String],
+    edit: impl Fn(&mut Vec<String>) -> bool,
+) -> Result<u32, String> {
+    let mut edited = 0;
+    match card_kind {
+        CardKind::Word => {
+            for mut word in words_state.get_words().await {
+                if cards.contains(&word.word) && edit(&mut word.tags) {
+                    let word = words_state.update_word(word).await?;
+                    history
+                        .record(card_kind, &word.word, HistoryEventKind::Edited)
+                        .await?;
+                    edited += 1;
+                }
+            }
+        }
+        CardKind::Kanji => {
+            for mut kanji in kanjis_state.get_entities().await {
+                if cards.contains(&kanji.kanji) && edit(&mut kanji.tags) {
+                    let kanji = kanjis_state.update_entity(kanji).await?;
+                    history
+                        .record(card_kind, &kanji.kanji, HistoryEventKind::Edited)
+                        .await?;
+                    edited += 1;
+                }
+            }
+        }
+    }
+    Ok(edited)
+}
+
+/// Every tag in use together with its ancestors, sorted so a tag directly
+/// follows its parent.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_tags(
+    words_state: tauri::State<'_, WordsState>,
+    kanjis_state: tauri::State<'_, KanjisState>,
+) -> Result<Vec<TagCount>, String> {
+    let mut counts: BTreeMap<String, (u32, u32)> = BTreeMap::new();
+    let mut count = |tags: &[String], card_kind: CardKind| {
+        let mut tags_and_ancestors = tags
+            .iter()
+            .flat_map(|t| with_ancestors(t))
+            .collect::<Vec<_>>();
+        tags_and_ancestors.sort();
+        tags_and_ancestors.dedup();
+        for tag in tags_and_ancestors {
+            let entry = counts.entry(tag).or_default();
+            match card_kind {
+                CardKind::Word => entry.0 += 1,
+                CardKind::Kanji => entry.1 += 1,
+            }
+        }
+    };
+    for word in words_state.get_words().await {
+        count(&word.tags, CardKind::Word);
+    }
+    for kanji in kanjis_state.get_entities().await {
+        count(&kanji.tags, CardKind::Kanji);
+    }
+
+    Ok(counts
+        .into_iter()
+        .map(|(tag, (words, kanji))| TagCount { tag, words, kanji })
+        .collect())
+}
+
+/// Returns how many cards changed.
+#[tauri::command]
+#[specta::specta]
+pub async fn add_tags(
+    words_state: tauri::State<'_, WordsState>,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    history: tauri::State<'_, HistoryState>,
+    card_kind: CardKind,
+    cards: Vec<String>,
+    tags: Vec<String>,
+) -> Result<u32, String> {
+    let tags = normalize_tags(&tags);
+    edit_tags(
+        &words_state,
+        &kanjis_state,
+        &history,
+        card_kind,
+        &cards,
+        |card_tags| {
+            let before = card_tags.len();
+            add_to(card_tags, &tags);
+            card_tags.len() != before
+        },
+    )
+    .await
+}
+
+/// Returns how many cards changed.
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_tags(
+    words_state: tauri::State<'_, WordsState>,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    history: tauri::State<'_, HistoryState>,
+    card_kind: CardKind,
+    cards: Vec<String>,
+    tags: Vec<String>,
+) -> Result<u32, String> {
+    let tags = normalize_tags(&tags);
+    edit_tags(
+        &words_state,
+        &kanjis_state,
+        &history,
+        card_kind,
+        &cards,
+        |card_tags| {
+            let before = card_tags.len();
+            remove_from(card_tags, &tags);
+            card_tags.len() != before
+        },
+    )
+    .await
+}
+
+/// Renames a tag on every word, kanji and collection, the tags below it move
+/// along. Returns how many cards changed.
+#[tauri::command]
+#[specta::specta]
+pub async fn rename_tag(
+    words_state: tauri::State<'_, WordsState>,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    collections_state: tauri::State<'_, CollectionsState>,
+    history: tauri::State<'_, HistoryState>,
+    from: String,
+    to: String,
+) -> Result<u32, String> {
+    let from = normalize_tag(&from).ok_or("Tag is empty".to_string())?;
+    let to = normalize_tag(&to).ok_or("Tag is empty".to_string())?;
+
+    let words = words_state
+        .get_words()
+        .await
+        .into_iter()
+        .map(|w| w.word)
+        .collect::<Vec<_>>();
+    let kanjis = kanjis_state
+        .get_entities()
+        .await
+        .into_iter()
+        .map(|k| k.kanji)
+        .collect::<Vec<_>>();
+    let rename = |card_tags: &mut Vec<String>| rename_in(card_tags, &from, &to);
+    let edited_words = edit_tags(
+        &words_state,
+        &kanjis_state,
+        &history,
+        CardKind::Word,
+        &words,
+        rename,
+    )
+    .await?;
+    let edited_kanjis = edit_tags(
+        &words_state,
+        &kanjis_state,
+        &history,
+        CardKind::Kanji,
+        &kanjis,
+        rename,
+    )
+    .await?;
+    collections_state
+        .edit_collections(|collection| rename_in(&mut collection.tags, &from, &to))
+        .await?;
+    Ok(edited_words + edited_kanjis)
+}
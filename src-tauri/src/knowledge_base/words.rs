@@ -2,12 +2,17 @@ use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 use tauri::{Manager, Wry, async_runtime::RwLock};
-use tauri_plugin_store::{Store, StoreExt};
+use tauri_plugin_store::Store;
 
-const WORDS_STORE_FILE: &str = "words.json";
+use super::collections::CollectionsState;
+use super::history::{CardKind, HistoryEventKind, HistoryState};
+use super::kanjis::Kanji;
+use crate::data::pitch_accent::PitchAccent;
+
+pub const WORDS_STORE_FILE: &str = "words.json";
 
 pub fn setup(app: &mut tauri::App) {
-    let store = app.store(WORDS_STORE_FILE);
+    let store = crate::profile::store(app, WORDS_STORE_FILE);
 
     if let Ok(store) = store {
         let mut words = Words {
@@ -30,6 +35,16 @@ impl WordsState {
         words.get_words()
     }
 
+    /// Swaps in the words saved in `store`, e.g. after it was overwritten.
+    pub async fn reload(&self, store: Arc<Store<Wry>>) {
+        let mut words = Words {
+            store,
+            words: Vec::new(),
+        };
+        words.load_words();
+        *self.0.write().await = words;
+    }
+
     pub async fn add_word(&self, payload: WordCreatePayload) -> Result<Word, String> {
         let mut words = self.0.write().await;
         words.add_word(payload)
@@ -39,6 +54,16 @@ impl WordsState {
         let words = self.0.read().await;
         words.has_word(word)
     }
+
+    pub async fn update_word(&self, word: Word) -> Result<Word, String> {
+        let mut words = self.0.write().await;
+        words.update_word(word)
+    }
+
+    pub async fn remove_word(&self, word: &String) -> Result<Word, String> {
+        let mut words = self.0.write().await;
+        words.remove_word(word)
+    }
 }
 
 pub struct Words {
@@ -47,7 +72,7 @@ pub struct Words {
 }
 
 impl Words {
-    fn load_words(&mut self) -> Option<Vec<Word>> {
+    pub fn load_words(&mut self) -> Option<Vec<Word>> {
         let loaded_words = self.store.get("words").and_then(|data| {
             serde_json::from_value::<Vec<Word>>(data)
                 .map_err(|e| e.to_string())
@@ -75,9 +100,37 @@ impl Words {
             return Err("Word already exists".to_string());
         }
         self.words.push(word.clone());
+        self.store_words()?;
+        Ok(word)
+    }
+
+    /// Replaces the word with the same `word`.
+    pub fn update_word(&mut self, word: Word) -> Result<Word, String> {
+        let existing = self
+            .words
+            .iter_mut()
+            .find(|w| w.word == word.word)
+            .ok_or("Word does not exist".to_string())?;
+        *existing = word.clone();
+        self.store_words()?;
+        Ok(word)
+    }
+
+    pub fn remove_word(&mut self, word: &String) -> Result<Word, String> {
+        let index = self
+            .words
+            .iter()
+            .position(|w| &w.word == word)
+            .ok_or("Word does not exist".to_string())?;
+        let word = self.words.remove(index);
+        self.store_words()?;
+        Ok(word)
+    }
+
+    fn store_words(&self) -> Result<(), String> {
         let words_value = serde_json::to_value(&self.words).map_err(|e| e.to_string())?;
         self.store.set("words", words_value);
-        Ok(word)
+        Ok(())
     }
 }
 
@@ -87,6 +140,23 @@ pub struct Word {
     pub word: String,
     pub meaning: String,
     pub kanji_readings: Vec<KanjiReading>,
+    #[serde(default)]
+    pub pitch_accents: Vec<PitchAccent>,
+    #[serde(default)]
+    pub context: Option<SentenceContext>,
+    /// Hierarchical, see `tags::TAG_SEPARATOR`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Missing on words saved before it was recorded.
+    #[serde(default)]
+    pub created_at: Option<u32>,
+}
+
+impl Word {
+    /// See `reading_of`.
+    pub fn reading(&self) -> Option<String> {
+        reading_of(&self.word, &self.kanji_readings)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
@@ -94,23 +164,140 @@ pub struct KanjiReading {
     pub reading: String,
 }
 
+/// The sentence a word was mined from. The cloze is given in characters, not
+/// bytes, so the frontend can slice the sentence directly.
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+pub struct SentenceContext {
+    pub sentence: String,
+    pub cloze_start: u32,
+    pub cloze_length: u32,
+    /// Free text, e.g. "NHK Easy" or the article URL.
+    pub source: Option<String>,
+    pub mined_at: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+pub struct SentenceContextPayload {
+    pub sentence: String,
+    pub cloze_start: u32,
+    pub cloze_length: u32,
+    pub source: Option<String>,
+}
+impl SentenceContextPayload {
+    pub fn to_context(&self) -> SentenceContext {
+        SentenceContext {
+            sentence: self.sentence.clone(),
+            cloze_start: self.cloze_start,
+            cloze_length: self.cloze_length,
+            source: self.source.clone(),
+            mined_at: super::unix_timestamp(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 pub struct WordCreatePayload {
     pub word: String,
     pub meaning: String,
     pub kanji_readings: Vec<KanjiReading>,
+    /// Looked up in the pitch accent data when left empty.
+    #[serde(default)]
+    #[specta(optional)]
+    pub pitch_accents: Vec<PitchAccent>,
+    #[serde(default)]
+    #[specta(optional)]
+    pub context: Option<SentenceContextPayload>,
+    #[serde(default)]
+    #[specta(optional)]
+    pub tags: Vec<String>,
+    /// Also adds the word's kanji that aren't in the knowledge base yet,
+    /// pre-filled from kanjidic2 and Heisig.
+    #[serde(default)]
+    #[specta(optional)]
+    pub add_missing_kanjis: bool,
 }
+/// The kana reading of `word`, taking one of `kanji_readings` per kanji.
+/// `None` when they don't line up.
+fn reading_of(word: &str, kanji_readings: &[KanjiReading]) -> Option<String> {
+    let mut kanji_readings = kanji_readings.iter();
+    let mut reading = String::new();
+    for c in word.chars() {
+        if wana_kana::utils::is_char_kanji(c) {
+            reading.push_str(&kanji_readings.next()?.reading);
+        } else {
+            reading.push(c);
+        }
+    }
+    match kanji_readings.next() {
+        Some(_) => None,
+        None => Some(reading),
+    }
+}
+
 impl WordCreatePayload {
+    /// See `reading_of`.
+    pub fn reading(&self) -> Option<String> {
+        reading_of(&self.word, &self.kanji_readings)
+    }
+
     pub fn to_word(&self) -> Word {
         Word {
             id: uuid::Uuid::new_v4().to_string(),
             word: self.word.clone(),
             meaning: self.meaning.clone(),
             kanji_readings: self.kanji_readings.clone(),
+            pitch_accents: self.pitch_accents.clone(),
+            context: self.context.as_ref().map(|c| c.to_context()),
+            tags: super::tags::normalize_tags(&self.tags),
+            created_at: Some(super::unix_timestamp()),
         }
     }
 }
 
+#[derive(Serialize, Debug, Clone, specta::Type)]
+pub struct AddedWord {
+    pub word: Word,
+    /// Added along with the word, see `WordCreatePayload::add_missing_kanjis`.
+    pub added_kanjis: Vec<Kanji>,
+}
+
+/// The accents of the reading the word is saved with, not those of every
+/// reading of the headword (今日 is read きょう and こんにち). None when the
+/// reading isn't known.
+fn pitch_accents_for(
+    app_handle: &tauri::AppHandle,
+    word: &str,
+    reading: Option<&str>,
+) -> Vec<PitchAccent> {
+    match reading {
+        Some(reading) => crate::data::get_pitch_accents(app_handle).find(word, reading),
+        None => vec![],
+    }
+}
+
+/// Saves the word and records it in the history. The missing kanji are added
+/// first, so a word is never saved without the kanji it asked for.
+async fn save_word(
+    app_handle: &tauri::AppHandle,
+    state: &WordsState,
+    history: &HistoryState,
+    payload: WordCreatePayload,
+) -> Result<AddedWord, String> {
+    if state.has_word(&payload.word).await {
+        return Err("Word already exists".to_string());
+    }
+    let added_kanjis = if payload.add_missing_kanjis {
+        super::kanjis::add_missing_kanjis(app_handle, &payload.word).await?
+    } else {
+        vec![]
+    };
+    let word = state.add_word(payload).await?;
+    history
+        .record(CardKind::Word, &word.word, HistoryEventKind::Added)
+        .await?;
+    Ok(AddedWord { word, added_kanjis })
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn get_words(state: tauri::State<'_, WordsState>) -> Result<Vec<Word>, String> {
@@ -120,10 +307,16 @@ pub async fn get_words(state: tauri::State<'_, WordsState>) -> Result<Vec<Word>,
 #[tauri::command]
 #[specta::specta]
 pub async fn add_word(
+    app_handle: tauri::AppHandle,
     state: tauri::State<'_, WordsState>,
-    payload: WordCreatePayload,
-) -> Result<Word, String> {
-    state.add_word(payload).await
+    history: tauri::State<'_, HistoryState>,
+    mut payload: WordCreatePayload,
+) -> Result<AddedWord, String> {
+    if payload.pitch_accents.is_empty() {
+        payload.pitch_accents =
+            pitch_accents_for(&app_handle, &payload.word, payload.reading().as_deref());
+    }
+    save_word(&app_handle, &state, &history, payload).await
 }
 
 #[tauri::command]
@@ -131,3 +324,99 @@ pub async fn add_word(
 pub async fn has_word(state: tauri::State<'_, WordsState>, word: String) -> Result<bool, String> {
     Ok(state.has_word(&word).await)
 }
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_word(
+    state: tauri::State<'_, WordsState>,
+    history: tauri::State<'_, HistoryState>,
+    word: Word,
+) -> Result<Word, String> {
+    let word = state.update_word(word).await?;
+    history
+        .record(CardKind::Word, &word.word, HistoryEventKind::Edited)
+        .await?;
+    Ok(word)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_word(
+    state: tauri::State<'_, WordsState>,
+    collections_state: tauri::State<'_, CollectionsState>,
+    history: tauri::State<'_, HistoryState>,
+    word: String,
+) -> Result<Word, String> {
+    let word = state.remove_word(&word).await?;
+    collections_state
+        .remove_card(CardKind::Word, &word.word)
+        .await?;
+    history
+        .record(CardKind::Word, &word.word, HistoryEventKind::Deleted)
+        .await?;
+    Ok(word)
+}
+
+/// Mines the `token_index`-th token of `sentence`: the word is saved in its
+/// dictionary form with meaning and kanji readings looked up, and the sentence
+/// is kept as its context. `add_missing_kanjis` as in `WordCreatePayload`.
+#[tauri::command]
+#[specta::specta]
+pub async fn add_word_from_sentence(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, WordsState>,
+    history: tauri::State<'_, HistoryState>,
+    sentence: String,
+    token_index: u32,
+    source: Option<String>,
+    add_missing_kanjis: bool,
+) -> Result<AddedWord, String> {
+    let tokens = crate::tokenizer::tokenize(&app_handle, &sentence).await?;
+    let token = tokens
+        .get(token_index as usize)
+        .ok_or(format!("No token at index {}", token_index))?;
+    let lemma = token.lemma_or_text().to_string();
+
+    let entry = crate::word::Word::new(lemma.clone(), &app_handle)
+        .find_in_dictionary()
+        .await?;
+    let meaning = entry
+        .as_ref()
+        .map(|e| e.translations_string())
+        .unwrap_or_default();
+    // Prefer the dictionary reading of the lemma, the token reading belongs to
+    // the inflected surface form.
+    let reading = entry
+        .as_ref()
+        .and_then(|e| e.reading_element_string().map(|r| r.to_string()));
+    let (kanji_readings, reading) = if lemma.chars().any(wana_kana::utils::is_char_kanji) {
+        let furigana = crate::kanji::parser::parse_word(&app_handle, &lemma, reading).await?;
+        let kanji_readings = furigana
+            .kanji_readings()
+            .into_iter()
+            .map(|reading| KanjiReading { reading })
+            .collect();
+        (kanji_readings, furigana.reading())
+    } else {
+        (vec![], lemma.clone())
+    };
+
+    let range = token.byte_range();
+    let context = SentenceContextPayload {
+        cloze_start: sentence[..range.start].chars().count() as u32,
+        cloze_length: sentence[range].chars().count() as u32,
+        sentence: sentence.clone(),
+        source,
+    };
+
+    let payload = WordCreatePayload {
+        pitch_accents: pitch_accents_for(&app_handle, &lemma, Some(&reading)),
+        word: lemma,
+        meaning,
+        kanji_readings,
+        context: Some(context),
+        tags: vec![],
+        add_missing_kanjis,
+    };
+    save_word(&app_handle, &state, &history, payload).await
+}
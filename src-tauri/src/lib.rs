@@ -3,8 +3,14 @@ use tauri_specta::{Builder, collect_commands};
 
 mod conversion;
 mod data;
+mod handwriting;
 mod kanji;
 mod knowledge_base;
+mod profile;
+mod quiz;
+mod reader;
+pub mod sync;
+mod tokenizer;
 mod translation;
 mod word;
 
@@ -17,16 +23,80 @@ pub fn run() {
             kanji::commands::get_heisig_kanjis,
             kanji::commands::search_heisig_kanjis,
             kanji::commands::get_kanjidic2_by_kanji,
+            kanji::commands::get_contrast_pairs,
+            kanji::commands::get_stroke_order,
+            handwriting::recognize_handwriting,
             kanji::commands::parse_word,
+            kanji::commands::get_pitch_accents,
+            kanji::commands::render_pitch_accent,
+            kanji::commands::render_furigana,
+            kanji::commands::parse_furigana,
             kanji::commands::validate_dictionary,
+            conversion::convert_text,
+            tokenizer::get_tokenizer_dictionary,
+            tokenizer::set_tokenizer_dictionary,
+            tokenizer::user_dictionary::get_user_dictionary_entries,
+            tokenizer::user_dictionary::add_user_dictionary_entry,
+            tokenizer::user_dictionary::update_user_dictionary_entry,
+            tokenizer::user_dictionary::remove_user_dictionary_entry,
             word::get_word_dict_entry,
             word::get_word_candidates,
+            word::get_example_sentences,
+            word::get_study_suggestions,
+            translation::autocomplete::autocomplete,
             knowledge_base::words::get_words,
             knowledge_base::words::add_word,
             knowledge_base::words::has_word,
+            knowledge_base::words::add_word_from_sentence,
+            knowledge_base::words::update_word,
+            knowledge_base::words::remove_word,
             knowledge_base::kanjis::get_kanjis,
             knowledge_base::kanjis::add_kanji,
             knowledge_base::kanjis::has_kanji,
+            knowledge_base::kanjis::update_kanji,
+            knowledge_base::kanjis::remove_kanji,
+            knowledge_base::tags::get_tags,
+            knowledge_base::tags::add_tags,
+            knowledge_base::tags::remove_tags,
+            knowledge_base::tags::rename_tag,
+            knowledge_base::collections::get_collections,
+            knowledge_base::collections::add_collection,
+            knowledge_base::collections::update_collection,
+            knowledge_base::collections::remove_collection,
+            knowledge_base::collections::add_to_collection,
+            knowledge_base::collections::remove_from_collection,
+            knowledge_base::collections::get_collection_cards,
+            knowledge_base::collections::get_collection_study_queue,
+            knowledge_base::mnemonics::get_mnemonics,
+            knowledge_base::mnemonics::get_mnemonic_suggestions,
+            knowledge_base::mnemonics::add_mnemonic,
+            knowledge_base::mnemonics::edit_mnemonic,
+            knowledge_base::mnemonics::set_active_mnemonic,
+            knowledge_base::mnemonics::set_mnemonic_sound,
+            knowledge_base::mnemonics::remove_mnemonic,
+            knowledge_base::reading_sounds::get_reading_sounds,
+            knowledge_base::reading_sounds::add_reading_sound,
+            knowledge_base::reading_sounds::update_reading_sound,
+            knowledge_base::reading_sounds::remove_reading_sound,
+            knowledge_base::reading_sounds::get_reading_sound_kanjis,
+            knowledge_base::history::get_history,
+            knowledge_base::history::record_review,
+            knowledge_base::stats::get_learning_stats,
+            profile::export_profile,
+            profile::import_profile,
+            profile::get_profile_backups,
+            profile::profiles::get_profiles,
+            profile::profiles::create_profile,
+            profile::profiles::rename_profile,
+            profile::profiles::switch_profile,
+            profile::profiles::delete_profile,
+            sync::sync_knowledge_base,
+            sync::start_sync_server,
+            sync::stop_sync_server,
+            reader::annotate_text,
+            reader::score::score_text,
+            quiz::start_quiz,
+            quiz::answer_quiz,
         ]);
 
     #[cfg(debug_assertions)] // <- Only export on non-release builds
@@ -41,9 +111,14 @@ pub fn run() {
         .setup(move |app| {
             builder.mount_events(app);
 
+            profile::setup(app);
+            tokenizer::setup(app);
             data::setup(app);
+            handwriting::setup(app);
             knowledge_base::setup(app);
             translation::setup(app);
+            quiz::setup(app);
+            sync::setup(app);
 
             Ok(())
         })
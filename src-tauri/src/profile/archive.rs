@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Tells profile archives apart from any other JSON file.
+const ARCHIVE_FORMAT: &str = "kanji-profile";
+/// Bumped whenever the layout of the archive or of a store changes, with a
+/// step added to `migrate`.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Every store of a profile in one file. Stores are kept as the raw key/value
+/// maps tauri-plugin-store writes, keyed by store file name.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProfileArchive {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: u32,
+    /// Over the serialized `stores`, see `checksum`.
+    pub checksum: String,
+    pub stores: BTreeMap<String, serde_json::Map<String, serde_json::Value>>,
+}
+
+/// CRC-32 (IEEE). Catches truncated and hand edited archives, it is not meant
+/// to stop anyone from forging one.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn checksum(
+    stores: &BTreeMap<String, serde_json::Map<String, serde_json::Value>>,
+) -> Result<String, String> {
+    let bytes = serde_json::to_vec(stores).map_err(|e| e.to_string())?;
+    Ok(format!("crc32:{:08x}", crc32(&bytes)))
+}
+
+impl ProfileArchive {
+    pub fn new(
+        stores: BTreeMap<String, serde_json::Map<String, serde_json::Value>>,
+    ) -> Result<Self, String> {
+        Ok(ProfileArchive {
+            format: ARCHIVE_FORMAT.to_string(),
+            version: ARCHIVE_VERSION,
+            exported_at: crate::knowledge_base::unix_timestamp(),
+            checksum: checksum(&stores)?,
+            stores,
+        })
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Could not write {:?}: {}", path, e))
+    }
+
+    /// Reads and validates an archive, `migrate` brings it up to date.
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {:?}: {}", path, e))?;
+        let archive = serde_json::from_str::<ProfileArchive>(&json)
+            .map_err(|e| format!("Not a profile archive: {}", e))?;
+
+        if archive.format != ARCHIVE_FORMAT {
+            return Err("Not a profile archive".to_string());
+        }
+        if archive.version > ARCHIVE_VERSION {
+            return Err(format!(
+                "The archive was made by a newer version of the app (format version {})",
+                archive.version
+            ));
+        }
+        if archive.checksum != checksum(&archive.stores)? {
+            return Err("The archive is corrupted, its checksum does not match".to_string());
+        }
+        Ok(archive)
+    }
+
+    /// Upgrades the stores to the current version. Every format change adds
+    /// a step here, there are none yet.
+    pub fn migrate(mut self) -> Result<Self, String> {
+        if self.version < ARCHIVE_VERSION {
+            return Err(format!("No migration from version {}", self.version));
+        }
+        self.checksum = checksum(&self.stores)?;
+        Ok(self)
+    }
+}
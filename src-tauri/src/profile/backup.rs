@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+
+use tauri::Manager;
+
+use super::archive::ProfileArchive;
+use super::profiles::ProfilesState;
+
+const BACKUP_DIR: &str = "backups";
+const BACKUP_PREFIX: &str = "profile-";
+/// Older backups are deleted once there are more than this.
+const MAX_BACKUPS: usize = 10;
+
+#[derive(serde::Serialize, Debug, Clone, specta::Type)]
+pub struct ProfileBackup {
+    /// Can be passed to `import_profile` to restore the backup.
+    pub path: String,
+    pub created_at: u32,
+}
+
+/// Every profile keeps its own backups.
+fn backup_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let profile_dir = app_handle
+        .try_state::<ProfilesState>()
+        .map(|profiles| profiles.active_dir())
+        .unwrap_or_default();
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(profile_dir).join(BACKUP_DIR))
+        .map_err(|e| e.to_string())
+}
+
+/// Newest first.
+pub fn list_backups(app_handle: &tauri::AppHandle) -> Result<Vec<ProfileBackup>, String> {
+    let dir = backup_dir(app_handle)?;
+    let Ok(files) = std::fs::read_dir(&dir) else {
+        return Ok(vec![]);
+    };
+    let mut backups = files
+        .filter_map(|file| file.ok())
+        .filter_map(|file| {
+            let name = file.file_name().to_string_lossy().to_string();
+            let stem = name.strip_prefix(BACKUP_PREFIX)?.strip_suffix(".json")?;
+            let (created_at, counter) = match stem.split_once('-') {
+                Some((created_at, counter)) => (created_at, counter.parse::<u32>().ok()?),
+                None => (stem, 1),
+            };
+            let backup = ProfileBackup {
+                path: file.path().to_string_lossy().to_string(),
+                created_at: created_at.parse::<u32>().ok()?,
+            };
+            Some((backup, counter))
+        })
+        .collect::<Vec<_>>();
+    backups.sort_by(|(a, a_counter), (b, b_counter)| {
+        (b.created_at, b_counter).cmp(&(a.created_at, a_counter))
+    });
+    Ok(backups.into_iter().map(|(backup, _)| backup).collect())
+}
+
+/// `profile-<created_at>.json`, or `profile-<created_at>-<n>.json` for the
+/// n-th backup made within the same second, so none gets overwritten.
+fn backup_path(dir: &Path, created_at: u32) -> PathBuf {
+    let mut path = dir.join(format!("{}{}.json", BACKUP_PREFIX, created_at));
+    let mut counter = 2;
+    while path.exists() {
+        path = dir.join(format!("{}{}-{}.json", BACKUP_PREFIX, created_at, counter));
+        counter += 1;
+    }
+    path
+}
+
+/// Saves `archive` as a new backup and deletes the oldest ones beyond
+/// `MAX_BACKUPS`.
+pub fn write_backup(
+    app_handle: &tauri::AppHandle,
+    archive: &ProfileArchive,
+) -> Result<ProfileBackup, String> {
+    let path = backup_path(&backup_dir(app_handle)?, archive.exported_at);
+    archive.write(&path)?;
+
+    for old in list_backups(app_handle)?.iter().skip(MAX_BACKUPS) {
+        if let Err(e) = std::fs::remove_file(&old.path) {
+            eprintln!("Could not delete old backup {}: {}", old.path, e);
+        }
+    }
+
+    Ok(ProfileBackup {
+        path: path.to_string_lossy().to_string(),
+        created_at: archive.exported_at,
+    })
+}
@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use tauri::{Manager, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+
+use crate::knowledge_base::collections::CollectionsState;
+use crate::knowledge_base::entity::EntitiesState;
+use crate::knowledge_base::history::{HISTORY_STORE_FILE, HistoryState};
+use crate::knowledge_base::kanjis::KanjisState;
+use crate::knowledge_base::mnemonics::MnemonicsState;
+use crate::knowledge_base::reading_sounds::ReadingSoundsState;
+use crate::knowledge_base::words::{WORDS_STORE_FILE, WordsState};
+use crate::knowledge_base::{
+    COLLECTIONS_STORE_FILE, KANJIS_STORE_FILE, MNEMONICS_STORE_FILE, READING_SOUNDS_STORE_FILE,
+};
+use crate::tokenizer::user_dictionary::{USER_DICTIONARY_STORE_FILE, UserDictionaryState};
+use crate::tokenizer::{SETTINGS_STORE_FILE, TokenizerState};
+
+pub mod archive;
+pub mod backup;
+pub mod profiles;
+
+use archive::ProfileArchive;
+use backup::ProfileBackup;
+use profiles::ProfilesState;
+
+/// Everything that makes up a profile. A store added anywhere in the app has
+/// to be listed here to be exported and switched, and reloaded in
+/// `reload_states`.
+const PROFILE_STORE_FILES: [&str; 8] = [
+    WORDS_STORE_FILE,
+    KANJIS_STORE_FILE,
+    COLLECTIONS_STORE_FILE,
+    MNEMONICS_STORE_FILE,
+    READING_SOUNDS_STORE_FILE,
+    HISTORY_STORE_FILE,
+    USER_DICTIONARY_STORE_FILE,
+    SETTINGS_STORE_FILE,
+];
+
+pub fn setup(app: &tauri::App) {
+    profiles::setup(app);
+}
+
+/// The store `file` of the active profile.
+pub fn store<M: Manager<Wry>>(manager: &M, file: &str) -> Result<Arc<Store<Wry>>, String> {
+    let dir = manager
+        .try_state::<ProfilesState>()
+        .map(|profiles| profiles.active_dir())
+        .unwrap_or_default();
+    manager.store(dir.join(file)).map_err(|e| e.to_string())
+}
+
+/// Writes the stores of the active profile to disk now instead of waiting
+/// for the debounced autosave.
+fn save_stores(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    for file in PROFILE_STORE_FILES {
+        store(app_handle, file)?.save().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn current_archive(app_handle: &tauri::AppHandle) -> Result<ProfileArchive, String> {
+    let mut stores = BTreeMap::new();
+    for file in PROFILE_STORE_FILES {
+        let store = store(app_handle, file)?;
+        stores.insert(file.to_string(), store.entries().into_iter().collect());
+    }
+    ProfileArchive::new(stores)
+}
+
+/// Points the managed states at the stores of the active profile, so an
+/// import or a profile switch shows up without restarting.
+pub(crate) async fn reload_states(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let store = |file: &str| store(app_handle, file);
+
+    if let Some(state) = app_handle.try_state::<WordsState>() {
+        state.reload(store(WORDS_STORE_FILE)?).await;
+    }
+    if let Some(state) = app_handle.try_state::<KanjisState>() {
+        state.reload(store(KANJIS_STORE_FILE)?).await;
+    }
+    if let Some(state) = app_handle.try_state::<CollectionsState>() {
+        state.reload(store(COLLECTIONS_STORE_FILE)?).await;
+    }
+    if let Some(state) = app_handle.try_state::<MnemonicsState>() {
+        state.reload(store(MNEMONICS_STORE_FILE)?).await;
+    }
+    if let Some(state) = app_handle.try_state::<ReadingSoundsState>() {
+        state.reload(store(READING_SOUNDS_STORE_FILE)?).await;
+    }
+    if let Some(state) = app_handle.try_state::<HistoryState>() {
+        state.reload(store(HISTORY_STORE_FILE)?).await;
+    }
+
+    let mut user_entries = vec![];
+    if let Some(state) = app_handle.try_state::<UserDictionaryState>() {
+        state.reload(store(USER_DICTIONARY_STORE_FILE)?).await;
+        user_entries = state.get_entities().await;
+    }
+    if let Some(tokenizer) = app_handle.try_state::<TokenizerState>() {
+        tokenizer
+            .reload(store(SETTINGS_STORE_FILE).ok(), user_entries)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Writes every store of the profile to a single archive at `path`.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_profile(app_handle: tauri::AppHandle, path: String) -> Result<(), String> {
+    current_archive(&app_handle)?.write(Path::new(&path))
+}
+
+/// Replaces every store of the active profile with the ones of `archive`.
+async fn replace_stores(
+    app_handle: &tauri::AppHandle,
+    mut archive: ProfileArchive,
+) -> Result<(), String> {
+    for file in PROFILE_STORE_FILES {
+        let store = store(app_handle, file)?;
+        store.clear();
+        // Stores missing from the archive stay empty, the import replaces
+        // the whole profile.
+        for (key, value) in archive.stores.remove(file).unwrap_or_default() {
+            store.set(key, value);
+        }
+        store.save().map_err(|e| e.to_string())?;
+    }
+    for file in archive.stores.keys() {
+        eprintln!("Ignoring unknown store {} in profile archive", file);
+    }
+
+    reload_states(app_handle).await
+}
+
+/// Replaces the profile with the archive at `path`. The current profile is
+/// backed up first, the returned backup restores it. An import failing
+/// partway puts the backup back.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_profile(
+    app_handle: tauri::AppHandle,
+    path: String,
+) -> Result<ProfileBackup, String> {
+    let archive = ProfileArchive::read(Path::new(&path))?;
+    let previous = current_archive(&app_handle)?;
+    let backup = backup::write_backup(&app_handle, &previous)?;
+    let archive = archive.migrate()?;
+
+    if let Err(e) = replace_stores(&app_handle, archive).await {
+        return match replace_stores(&app_handle, previous).await {
+            Ok(()) => Err(e),
+            Err(restore_error) => Err(format!(
+                "{}, restoring the backup {} failed too: {}",
+                e, backup.path, restore_error
+            )),
+        };
+    }
+    Ok(backup)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_profile_backups(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<ProfileBackup>, String> {
+    backup::list_backups(&app_handle)
+}
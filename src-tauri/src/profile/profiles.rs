@@ -0,0 +1,290 @@
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+
+/// Lists the profiles and remembers the active one, shared by all profiles.
+const PROFILES_STORE_FILE: &str = "profiles.json";
+const PROFILES_DIR: &str = "profiles";
+/// Keeps its stores where they were before profiles existed.
+pub const DEFAULT_PROFILE_ID: &str = "default";
+const DEFAULT_PROFILE_NAME: &str = "Default";
+
+pub fn setup(app: &tauri::App) {
+    let store = app.store(PROFILES_STORE_FILE);
+
+    if let Ok(store) = store {
+        let mut profiles = Profiles {
+            store,
+            profiles: Vec::new(),
+            active: DEFAULT_PROFILE_ID.to_string(),
+        };
+        profiles.load_profiles();
+        app.manage(ProfilesState::new(profiles));
+    }
+}
+
+/// A std lock rather than the async one the other states use: store paths
+/// are resolved from the synchronous setup code too, and the lock is never
+/// held across an await.
+pub struct ProfilesState(RwLock<Profiles>);
+impl ProfilesState {
+    pub fn new(profiles: Profiles) -> Self {
+        Self(RwLock::new(profiles))
+    }
+
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, Profiles>, String> {
+        self.0.read().map_err(|e| e.to_string())
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, Profiles>, String> {
+        self.0.write().map_err(|e| e.to_string())
+    }
+
+    pub fn get_profiles(&self) -> Result<Vec<Profile>, String> {
+        Ok(self.read()?.profiles.clone())
+    }
+
+    pub fn active(&self) -> Result<Profile, String> {
+        let profiles = self.read()?;
+        profiles.get(&profiles.active).cloned()
+    }
+
+    /// Where the active profile keeps its stores, relative to the app data
+    /// directory.
+    pub fn active_dir(&self) -> PathBuf {
+        self.read()
+            .map(|profiles| profile_dir(&profiles.active))
+            .unwrap_or_default()
+    }
+
+    pub fn create(&self, name: &str) -> Result<Profile, String> {
+        self.write()?.create(name)
+    }
+
+    pub fn rename(&self, id: &String, name: &str) -> Result<Profile, String> {
+        self.write()?.rename(id, name)
+    }
+
+    pub fn set_active(&self, id: &String) -> Result<Profile, String> {
+        self.write()?.set_active(id)
+    }
+
+    pub fn remove(&self, id: &String) -> Result<Profile, String> {
+        self.write()?.remove(id)
+    }
+}
+
+/// Relative to the app data directory, empty for the default profile.
+pub fn profile_dir(id: &str) -> PathBuf {
+    if id == DEFAULT_PROFILE_ID {
+        PathBuf::new()
+    } else {
+        PathBuf::from(PROFILES_DIR).join(id)
+    }
+}
+
+pub struct Profiles {
+    store: Arc<Store<Wry>>,
+    profiles: Vec<Profile>,
+    active: String,
+}
+
+impl Profiles {
+    fn load_profiles(&mut self) {
+        let loaded_profiles = self.store.get("profiles").and_then(|data| {
+            serde_json::from_value::<Vec<Profile>>(data)
+                .map_err(|e| e.to_string())
+                .ok()
+        });
+        self.profiles = loaded_profiles.unwrap_or_default();
+        if !self.profiles.iter().any(|p| p.id == DEFAULT_PROFILE_ID) {
+            self.profiles.insert(
+                0,
+                Profile {
+                    id: DEFAULT_PROFILE_ID.to_string(),
+                    name: DEFAULT_PROFILE_NAME.to_string(),
+                    created_at: crate::knowledge_base::unix_timestamp(),
+                },
+            );
+        }
+
+        let active = self
+            .store
+            .get("active")
+            .and_then(|data| serde_json::from_value::<String>(data).ok());
+        self.active = active
+            .filter(|id| self.profiles.iter().any(|p| &p.id == id))
+            .unwrap_or(DEFAULT_PROFILE_ID.to_string());
+    }
+
+    fn store_profiles(&self) -> Result<(), String> {
+        let profiles_value = serde_json::to_value(&self.profiles).map_err(|e| e.to_string())?;
+        self.store.set("profiles", profiles_value);
+        self.store.set("active", self.active.clone());
+        Ok(())
+    }
+
+    fn get(&self, id: &String) -> Result<&Profile, String> {
+        self.profiles
+            .iter()
+            .find(|p| &p.id == id)
+            .ok_or("Profile does not exist".to_string())
+    }
+
+    /// `id` is the profile being renamed, it may keep its name.
+    fn check_name(&self, name: &str, id: Option<&String>) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Profile name is empty".to_string());
+        }
+        if self
+            .profiles
+            .iter()
+            .any(|p| p.name == name && Some(&p.id) != id)
+        {
+            return Err("Profile already exists".to_string());
+        }
+        Ok(name.to_string())
+    }
+
+    fn create(&mut self, name: &str) -> Result<Profile, String> {
+        let profile = Profile {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: self.check_name(name, None)?,
+            created_at: crate::knowledge_base::unix_timestamp(),
+        };
+        self.profiles.push(profile.clone());
+        self.store_profiles()?;
+        Ok(profile)
+    }
+
+    fn rename(&mut self, id: &String, name: &str) -> Result<Profile, String> {
+        let name = self.check_name(name, Some(id))?;
+        let profile = self
+            .profiles
+            .iter_mut()
+            .find(|p| &p.id == id)
+            .ok_or("Profile does not exist".to_string())?;
+        profile.name = name;
+        let profile = profile.clone();
+        self.store_profiles()?;
+        Ok(profile)
+    }
+
+    fn set_active(&mut self, id: &String) -> Result<Profile, String> {
+        let profile = self.get(id)?.clone();
+        self.active = profile.id.clone();
+        self.store_profiles()?;
+        Ok(profile)
+    }
+
+    fn remove(&mut self, id: &String) -> Result<Profile, String> {
+        if id == DEFAULT_PROFILE_ID {
+            return Err("The default profile can't be deleted".to_string());
+        }
+        if id == &self.active {
+            return Err(
+                "The active profile can't be deleted, switch to another one first".to_string(),
+            );
+        }
+        let index = self
+            .profiles
+            .iter()
+            .position(|p| &p.id == id)
+            .ok_or("Profile does not exist".to_string())?;
+        let profile = self.profiles.remove(index);
+        self.store_profiles()?;
+        Ok(profile)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub created_at: u32,
+}
+
+#[derive(Serialize, Debug, Clone, specta::Type)]
+pub struct ProfilesPayload {
+    pub profiles: Vec<Profile>,
+    pub active: Profile,
+}
+
+fn profiles_state(
+    app_handle: &tauri::AppHandle,
+) -> Result<tauri::State<'_, ProfilesState>, String> {
+    app_handle
+        .try_state::<ProfilesState>()
+        .ok_or("Profiles are not available".to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_profiles(app_handle: tauri::AppHandle) -> Result<ProfilesPayload, String> {
+    let state = profiles_state(&app_handle)?;
+    Ok(ProfilesPayload {
+        profiles: state.get_profiles()?,
+        active: state.active()?,
+    })
+}
+
+/// The new profile starts empty, it is not switched to.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_profile(app_handle: tauri::AppHandle, name: String) -> Result<Profile, String> {
+    profiles_state(&app_handle)?.create(&name)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn rename_profile(
+    app_handle: tauri::AppHandle,
+    id: String,
+    name: String,
+) -> Result<Profile, String> {
+    profiles_state(&app_handle)?.rename(&id, &name)
+}
+
+/// Saves the stores of the current profile and loads the ones of `id` into
+/// the managed states. When loading fails the current profile stays active.
+#[tauri::command]
+#[specta::specta]
+pub async fn switch_profile(app_handle: tauri::AppHandle, id: String) -> Result<Profile, String> {
+    super::save_stores(&app_handle)?;
+    let profiles = profiles_state(&app_handle)?;
+    let previous = profiles.active()?;
+    let profile = profiles.set_active(&id)?;
+
+    if let Err(e) = super::reload_states(&app_handle).await {
+        // Some states may hold the new profile already, they are reloaded too.
+        profiles.set_active(&previous.id)?;
+        return match super::reload_states(&app_handle).await {
+            Ok(()) => Err(e),
+            Err(reload_error) => Err(format!(
+                "{}, reloading profile {} failed too: {}",
+                e, previous.name, reload_error
+            )),
+        };
+    }
+    Ok(profile)
+}
+
+/// Deletes the profile together with its stores and backups.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_profile(app_handle: tauri::AppHandle, id: String) -> Result<Profile, String> {
+    let profile = profiles_state(&app_handle)?.remove(&id)?;
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(profile_dir(&profile.id));
+    if dir.is_dir() {
+        std::fs::remove_dir_all(&dir).map_err(|e| format!("Could not delete {:?}: {}", dir, e))?;
+    }
+    Ok(profile)
+}
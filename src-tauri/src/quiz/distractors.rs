@@ -0,0 +1,239 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::data::heisig_kanji::{HeisigKanji, HeisigKanjis};
+use crate::data::similar_kanji::{SimilarKanjiIndex, components};
+
+use super::shuffle;
+
+/// How plausible `other` is as a wrong answer for `target`. Visual similarity
+/// weighs most, then shared components, then a shared reading. Zero means
+/// unrelated.
+fn kanji_relatedness(
+    target: &HeisigKanji,
+    other: &HeisigKanji,
+    similar_kanji: &SimilarKanjiIndex,
+) -> u32 {
+    let visual_similarity = (similar_kanji.score(&target.kanji, &other.kanji) * 10.0) as u32;
+    let shared_components = components(target).intersection(&components(other)).count() as u32;
+    let target_readings = kanji_readings(target);
+    let shares_reading = kanji_readings(other)
+        .iter()
+        .any(|r| target_readings.contains(r));
+
+    visual_similarity + shared_components * 2 + shares_reading as u32
+}
+
+/// Kanji readings in hiragana, on'yomi first.
+pub fn kanji_readings(kanji: &HeisigKanji) -> Vec<String> {
+    kanji
+        .on_yomi
+        .iter()
+        .chain(kanji.kun_yomi.iter())
+        .map(|r| super::normalize_reading(&r.to_kana()))
+        .collect()
+}
+
+/// Up to `count` kanji most easily confused with `target`, falling back to
+/// random kanji when not enough related ones exist.
+pub fn related_kanji(
+    target: &HeisigKanji,
+    heisig_kanjis: &HeisigKanjis,
+    similar_kanji: &SimilarKanjiIndex,
+    count: usize,
+) -> Vec<Arc<HeisigKanji>> {
+    let mut candidates = heisig_kanjis
+        .get_all()
+        .iter()
+        .filter(|k| k.kanji != target.kanji)
+        .cloned()
+        .collect::<Vec<_>>();
+    // Shuffled first so ties, and the fallback, differ between quizzes.
+    shuffle(&mut candidates);
+    candidates
+        .sort_by_cached_key(|k| std::cmp::Reverse(kanji_relatedness(target, k, similar_kanji)));
+    candidates.truncate(count);
+    candidates
+}
+
+/// Readings of kanji related to `target` that are not among its own readings.
+pub fn related_readings(
+    target: &HeisigKanji,
+    accepted: &[String],
+    heisig_kanjis: &HeisigKanjis,
+    similar_kanji: &SimilarKanjiIndex,
+    count: usize,
+) -> Vec<String> {
+    let mut seen = accepted.iter().cloned().collect::<HashSet<_>>();
+    related_kanji(target, heisig_kanjis, similar_kanji, count * 3)
+        .iter()
+        .flat_map(|k| kanji_readings(k).into_iter().take(1))
+        .filter(|r| seen.insert(r.clone()))
+        .take(count)
+        .collect()
+}
+
+fn shared_kanji_count(a: &str, b: &str) -> usize {
+    a.chars()
+        .filter(|c| wana_kana::utils::is_char_kanji(*c) && b.contains(*c))
+        .count()
+}
+
+/// Words from `pool` ranked by how similar they look to `word`: shared kanji
+/// first, then a close length.
+pub fn related_words<'a>(word: &str, pool: &'a [(String, String)]) -> Vec<&'a (String, String)> {
+    let mut candidates = pool
+        .iter()
+        .filter(|(other, _)| other != word)
+        .collect::<Vec<_>>();
+    shuffle(&mut candidates);
+    let length = word.chars().count();
+    candidates.sort_by_cached_key(|(other, _)| {
+        (
+            std::cmp::Reverse(shared_kanji_count(word, other)),
+            other.chars().count().abs_diff(length),
+        )
+    });
+    candidates
+}
+
+/// Fake words made by swapping one kanji of `word` for a related one, e.g.
+/// `末来` for `未来`.
+pub fn lookalike_words(
+    word: &str,
+    heisig_kanjis: &HeisigKanjis,
+    similar_kanji: &SimilarKanjiIndex,
+    count: usize,
+) -> Vec<String> {
+    let chars = word.chars().collect::<Vec<_>>();
+    let mut lookalikes = Vec::new();
+    for (i, c) in chars.iter().enumerate() {
+        let Some(kanji) = heisig_kanjis
+            .get_by_kanji(&c.to_string())
+            .into_iter()
+            .next()
+        else {
+            continue;
+        };
+        for related in related_kanji(&kanji, heisig_kanjis, similar_kanji, count) {
+            let mut swapped = chars.clone();
+            swapped.splice(i..=i, related.kanji.chars());
+            lookalikes.push(swapped.into_iter().collect::<String>());
+        }
+    }
+    shuffle(&mut lookalikes);
+    lookalikes.truncate(count);
+    lookalikes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::heisig_kanji::get_heisig_kanjis;
+
+    fn heisig_kanji(heisig_kanjis: &HeisigKanjis, kanji: &str) -> Arc<HeisigKanji> {
+        heisig_kanjis
+            .get_by_kanji(&kanji.to_string())
+            .into_iter()
+            .next()
+            .unwrap()
+    }
+
+    fn words(words: &[(&str, &str)]) -> Vec<(String, String)> {
+        words
+            .iter()
+            .map(|(word, reading)| (word.to_string(), reading.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn lookalike_kanji_come_first() {
+        let heisig_kanjis = get_heisig_kanjis().unwrap();
+        let similar_kanji = SimilarKanjiIndex::new(&heisig_kanjis, None);
+        let not_yet = heisig_kanji(&heisig_kanjis, "未");
+
+        let related = related_kanji(&not_yet, &heisig_kanjis, &similar_kanji, 3);
+
+        assert_eq!(related.len(), 3);
+        // 本 looks just as close, ties are shuffled.
+        assert!(related.iter().any(|k| k.kanji == "末"));
+        assert!(related.iter().all(|k| k.kanji != "未"));
+    }
+
+    #[test]
+    fn unrelated_kanji_fill_up_the_choices() {
+        let heisig_kanjis = get_heisig_kanjis().unwrap();
+        let similar_kanji = SimilarKanjiIndex::new(&heisig_kanjis, None);
+        let one = heisig_kanji(&heisig_kanjis, "一");
+
+        let related = related_kanji(&one, &heisig_kanjis, &similar_kanji, 50);
+
+        assert_eq!(related.len(), 50);
+        let distinct = related.iter().map(|k| &k.kanji).collect::<HashSet<_>>();
+        assert_eq!(distinct.len(), 50);
+        assert!(!distinct.contains(&"一".to_string()));
+    }
+
+    #[test]
+    fn readings_are_hiragana_on_yomi_first() {
+        let heisig_kanjis = get_heisig_kanjis().unwrap();
+        let one = heisig_kanji(&heisig_kanjis, "一");
+
+        let readings = kanji_readings(&one);
+
+        assert_eq!(readings.first().map(String::as_str), Some("いち"));
+        assert!(readings.contains(&"ひとつ".to_string()));
+        assert!(readings.iter().all(|r| !r.contains(['.', '-'])));
+    }
+
+    #[test]
+    fn distractor_readings_are_not_accepted_answers() {
+        let heisig_kanjis = get_heisig_kanjis().unwrap();
+        let similar_kanji = SimilarKanjiIndex::new(&heisig_kanjis, None);
+        let not_yet = heisig_kanji(&heisig_kanjis, "未");
+        let accepted = kanji_readings(&not_yet);
+
+        let readings = related_readings(&not_yet, &accepted, &heisig_kanjis, &similar_kanji, 3);
+
+        assert_eq!(readings.len(), 3);
+        assert!(readings.iter().all(|r| !accepted.contains(r)));
+        assert_eq!(readings.iter().collect::<HashSet<_>>().len(), 3);
+    }
+
+    #[test]
+    fn words_sharing_kanji_come_first() {
+        let pool = words(&[
+            ("犬", "いぬ"),
+            ("将来", "しょうらい"),
+            ("未来", "みらい"),
+            ("未来形", "みらいけい"),
+            ("猫", "ねこ"),
+        ]);
+
+        let related = related_words("未来", &pool)
+            .into_iter()
+            .map(|(word, _)| word.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(related.len(), 4);
+        assert_eq!(related[..2], ["未来形", "将来"]);
+        assert!(!related.contains(&"未来"));
+    }
+
+    #[test]
+    fn lookalike_words_swap_one_kanji() {
+        let heisig_kanjis = get_heisig_kanjis().unwrap();
+        let similar_kanji = SimilarKanjiIndex::new(&heisig_kanjis, None);
+
+        // Only one kanji to swap, so the closest lookalikes all make the cut.
+        let lookalikes = lookalike_words("未だ", &heisig_kanjis, &similar_kanji, 5);
+
+        assert_eq!(lookalikes.len(), 5);
+        assert!(lookalikes.contains(&"末だ".to_string()));
+        assert!(
+            lookalikes
+                .iter()
+                .all(|l| l.ends_with('だ') && l.chars().count() == 2 && l != "未だ")
+        );
+    }
+}
@@ -0,0 +1,414 @@
+use std::collections::HashMap;
+
+use tauri::{Manager, async_runtime::RwLock};
+
+use crate::data::heisig_kanji::HeisigKanjis;
+use crate::data::similar_kanji::SimilarKanjiIndex;
+use crate::knowledge_base::entity::EntitiesState;
+use crate::knowledge_base::kanjis::{Kanji, KanjisState};
+use crate::knowledge_base::words::WordsState;
+
+mod distractors;
+
+const DEFAULT_CHOICE_COUNT: u32 = 4;
+
+pub fn setup(app: &tauri::App) {
+    app.manage(QuizState::default());
+}
+
+/// Running quizzes by id. Answers never leave this state until a question
+/// has been answered.
+#[derive(Default)]
+pub struct QuizState(RwLock<HashMap<String, QuizSession>>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum QuestionType {
+    KeywordToKanji,
+    KanjiToReading,
+    WordToReading,
+    ReadingToWord,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, specta::Type)]
+pub struct QuizConfig {
+    /// Questions cycle through these types, types without material are skipped.
+    pub question_types: Vec<QuestionType>,
+    pub question_count: u32,
+    /// Including the correct one. Defaults to 4.
+    pub choice_count: Option<u32>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct QuizQuestion {
+    pub index: u32,
+    pub question_type: QuestionType,
+    pub prompt: String,
+    pub choices: Vec<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct QuizSessionPayload {
+    pub id: String,
+    pub questions: Vec<QuizQuestion>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct AnsweredQuestion {
+    pub question: QuizQuestion,
+    pub answer: String,
+    pub correct: bool,
+    pub correct_answers: Vec<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct QuizResults {
+    pub total: u32,
+    pub correct: u32,
+    pub percentage: f64,
+    pub answers: Vec<AnsweredQuestion>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct QuizAnswerResult {
+    pub answered: AnsweredQuestion,
+    /// Set once the last question is answered, the session is closed then.
+    pub results: Option<QuizResults>,
+}
+
+struct PreparedQuestion {
+    question: QuizQuestion,
+    accepted: Vec<String>,
+}
+
+impl PreparedQuestion {
+    fn new(
+        question_type: QuestionType,
+        prompt: String,
+        accepted: Vec<String>,
+        distractors: Vec<String>,
+    ) -> Option<Self> {
+        let answer = accepted.first()?.clone();
+        let mut choices = vec![answer];
+        choices.extend(distractors);
+        shuffle(&mut choices);
+        Some(PreparedQuestion {
+            question: QuizQuestion {
+                index: 0,
+                question_type,
+                prompt,
+                choices,
+            },
+            accepted,
+        })
+    }
+
+    fn grade(&self, answer: &str) -> bool {
+        match self.question.question_type {
+            QuestionType::KeywordToKanji | QuestionType::ReadingToWord => {
+                self.accepted.iter().any(|a| a == answer.trim())
+            }
+            QuestionType::KanjiToReading | QuestionType::WordToReading => {
+                let answer = normalize_reading(answer);
+                self.accepted.iter().any(|a| *a == answer)
+            }
+        }
+    }
+}
+
+struct QuizSession {
+    questions: Vec<PreparedQuestion>,
+    answers: Vec<AnsweredQuestion>,
+}
+
+impl QuizSession {
+    fn results(&self) -> QuizResults {
+        let total = self.questions.len() as u32;
+        let correct = self.answers.iter().filter(|a| a.correct).count() as u32;
+        QuizResults {
+            total,
+            correct,
+            percentage: if total == 0 {
+                0.0
+            } else {
+                correct as f64 * 100.0 / total as f64
+            },
+            answers: self.answers.clone(),
+        }
+    }
+}
+
+/// No `rand` in the tree, random v4 UUIDs make good enough sort keys.
+fn shuffle<T>(items: &mut [T]) {
+    items.sort_by_cached_key(|_| uuid::Uuid::new_v4());
+}
+
+/// Hiragana without kanjidic-style okurigana dots and affix dashes, so romaji,
+/// katakana and `た.べる` all compare equal to `たべる`.
+fn normalize_reading(reading: &str) -> String {
+    let reading = reading.trim().replace(['.', '-'], "");
+    wana_kana::ConvertJapanese::to_hiragana(reading.as_str())
+}
+
+fn keyword_to_kanji(
+    kanji: &Kanji,
+    heisig_kanjis: &HeisigKanjis,
+    similar_kanji: &SimilarKanjiIndex,
+    choice_count: usize,
+) -> Option<PreparedQuestion> {
+    let heisig_kanji = heisig_kanjis
+        .get_by_kanji(&kanji.kanji)
+        .into_iter()
+        .next()?;
+    let distractors = distractors::related_kanji(
+        &heisig_kanji,
+        heisig_kanjis,
+        similar_kanji,
+        choice_count - 1,
+    )
+    .iter()
+    .map(|k| k.kanji.clone())
+    .collect();
+    PreparedQuestion::new(
+        QuestionType::KeywordToKanji,
+        heisig_kanji.keyword.clone(),
+        vec![kanji.kanji.clone()],
+        distractors,
+    )
+}
+
+fn kanji_to_reading(
+    kanji: &Kanji,
+    heisig_kanjis: &HeisigKanjis,
+    similar_kanji: &SimilarKanjiIndex,
+    choice_count: usize,
+) -> Option<PreparedQuestion> {
+    let heisig_kanji = heisig_kanjis
+        .get_by_kanji(&kanji.kanji)
+        .into_iter()
+        .next()?;
+    // The deck's own readings are what the user studies, Heisig's are the
+    // fallback for cards saved without any.
+    let mut accepted = kanji
+        .readings
+        .iter()
+        .map(|r| normalize_reading(r))
+        .filter(|r| !r.is_empty())
+        .collect::<Vec<_>>();
+    if accepted.is_empty() {
+        accepted = distractors::kanji_readings(&heisig_kanji);
+    }
+    let distractors = distractors::related_readings(
+        &heisig_kanji,
+        &accepted,
+        heisig_kanjis,
+        similar_kanji,
+        choice_count - 1,
+    );
+    PreparedQuestion::new(
+        QuestionType::KanjiToReading,
+        kanji.kanji.clone(),
+        accepted,
+        distractors,
+    )
+}
+
+/// `words` pairs every deck word with its hiragana reading.
+fn word_to_reading(
+    word: &(String, String),
+    words: &[(String, String)],
+    choice_count: usize,
+) -> Option<PreparedQuestion> {
+    let (word, reading) = word;
+    let mut distractors = Vec::new();
+    for (_, other_reading) in distractors::related_words(word, words) {
+        if distractors.len() + 1 >= choice_count {
+            break;
+        }
+        if other_reading != reading && !distractors.contains(other_reading) {
+            distractors.push(other_reading.clone());
+        }
+    }
+    PreparedQuestion::new(
+        QuestionType::WordToReading,
+        word.clone(),
+        vec![reading.clone()],
+        distractors,
+    )
+}
+
+fn reading_to_word(
+    word: &(String, String),
+    words: &[(String, String)],
+    heisig_kanjis: &HeisigKanjis,
+    similar_kanji: &SimilarKanjiIndex,
+    choice_count: usize,
+) -> Option<PreparedQuestion> {
+    let (word, reading) = word;
+    // Lookalikes first, real words with the same kanji fill up the rest.
+    let mut distractors =
+        distractors::lookalike_words(word, heisig_kanjis, similar_kanji, choice_count / 2);
+    for (other, other_reading) in distractors::related_words(word, words) {
+        if distractors.len() + 1 >= choice_count {
+            break;
+        }
+        if other_reading != reading && !distractors.contains(other) {
+            distractors.push(other.clone());
+        }
+    }
+    PreparedQuestion::new(
+        QuestionType::ReadingToWord,
+        reading.clone(),
+        vec![word.clone()],
+        distractors,
+    )
+}
+
+fn generate_questions(
+    config: &QuizConfig,
+    kanjis: &[Kanji],
+    words: &[(String, String)],
+    heisig_kanjis: &HeisigKanjis,
+    similar_kanji: &SimilarKanjiIndex,
+) -> Vec<PreparedQuestion> {
+    let choice_count = config.choice_count.unwrap_or(DEFAULT_CHOICE_COUNT).max(2) as usize;
+    let mut cursors = vec![0; config.question_types.len()];
+    let mut questions = Vec::new();
+
+    while questions.len() < config.question_count as usize {
+        let mut progressed = false;
+        for (question_type, cursor) in config.question_types.iter().zip(cursors.iter_mut()) {
+            if questions.len() >= config.question_count as usize {
+                break;
+            }
+            // Items the question cannot be built from (e.g. kanji missing in
+            // the Heisig data) are skipped.
+            loop {
+                let question = match question_type {
+                    QuestionType::KeywordToKanji | QuestionType::KanjiToReading => {
+                        let Some(kanji) = kanjis.get(*cursor) else {
+                            break;
+                        };
+                        if *question_type == QuestionType::KeywordToKanji {
+                            keyword_to_kanji(kanji, heisig_kanjis, similar_kanji, choice_count)
+                        } else {
+                            kanji_to_reading(kanji, heisig_kanjis, similar_kanji, choice_count)
+                        }
+                    }
+                    QuestionType::WordToReading | QuestionType::ReadingToWord => {
+                        let Some(word) = words.get(*cursor) else {
+                            break;
+                        };
+                        if *question_type == QuestionType::WordToReading {
+                            word_to_reading(word, words, choice_count)
+                        } else {
+                            reading_to_word(word, words, heisig_kanjis, similar_kanji, choice_count)
+                        }
+                    }
+                };
+                *cursor += 1;
+                if let Some(mut question) = question {
+                    question.question.index = questions.len() as u32;
+                    questions.push(question);
+                    progressed = true;
+                    break;
+                }
+            }
+        }
+        if !progressed {
+            break;
+        }
+    }
+
+    questions
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn start_quiz(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, QuizState>,
+    kanjis_state: tauri::State<'_, KanjisState>,
+    words_state: tauri::State<'_, WordsState>,
+    config: QuizConfig,
+) -> Result<QuizSessionPayload, String> {
+    let mut kanjis = kanjis_state.get_entities().await;
+    shuffle(&mut kanjis);
+
+    let uses_words = config
+        .question_types
+        .iter()
+        .any(|t| matches!(t, QuestionType::WordToReading | QuestionType::ReadingToWord));
+    let mut words = Vec::new();
+    if uses_words {
+        for word in words_state.get_words().await {
+            // The saved readings are the ones the user studies, lindera's
+            // reading is the fallback when they don't line up with the word.
+            let reading = match word.reading() {
+                Some(reading) => reading,
+                None => match crate::conversion::convert(&app_handle, &word.word).await {
+                    Ok(reading) => reading,
+                    // Left out of the quiz rather than failing all of it.
+                    Err(_) => continue,
+                },
+            };
+            words.push((word.word, normalize_reading(&reading)));
+        }
+    }
+    shuffle(&mut words);
+
+    let heisig_kanjis = crate::data::get_heisig_kanjis(&app_handle);
+    let similar_kanji = crate::data::get_similar_kanji(&app_handle);
+    let questions = generate_questions(&config, &kanjis, &words, &heisig_kanjis, &similar_kanji);
+    if questions.is_empty() {
+        return Err("Not enough kanji or words in the deck for this quiz".to_string());
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let payload = QuizSessionPayload {
+        id: id.clone(),
+        questions: questions.iter().map(|q| q.question.clone()).collect(),
+    };
+    state.0.write().await.insert(
+        id,
+        QuizSession {
+            questions,
+            answers: Vec::new(),
+        },
+    );
+    Ok(payload)
+}
+
+/// Grades `answer` against the next unanswered question of the quiz.
+#[tauri::command]
+#[specta::specta]
+pub async fn answer_quiz(
+    state: tauri::State<'_, QuizState>,
+    id: String,
+    answer: String,
+) -> Result<QuizAnswerResult, String> {
+    let mut sessions = state.0.write().await;
+    let session = sessions
+        .get_mut(&id)
+        .ok_or(format!("No quiz with id {}", id))?;
+    let question = session
+        .questions
+        .get(session.answers.len())
+        .ok_or("Quiz is already finished".to_string())?;
+
+    let answered = AnsweredQuestion {
+        question: question.question.clone(),
+        correct: question.grade(&answer),
+        answer,
+        correct_answers: question.accepted.clone(),
+    };
+    session.answers.push(answered.clone());
+
+    let results = if session.answers.len() == session.questions.len() {
+        let results = session.results();
+        sessions.remove(&id);
+        Some(results)
+    } else {
+        None
+    };
+    Ok(QuizAnswerResult { answered, results })
+}
@@ -0,0 +1,180 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::kanji::parser::FuriganaString;
+use crate::knowledge_base::history::{CardKind, HistoryState};
+use crate::knowledge_base::words::{KanjiReading, WordCreatePayload, WordsState};
+use crate::word::MyToken;
+
+pub mod score;
+
+/// Successful reviews in a row after which a saved word counts as known, its
+/// next review is a week or more away by then.
+const KNOWN_MIN_STEP: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum TokenStatus {
+    /// Particles, auxiliaries, punctuation and other non-Japanese text.
+    Grammar,
+    /// The token's lemma (or surface form) is saved in the words deck.
+    InDeck,
+    /// Saved and reviewed successfully often enough to need no furigana.
+    Known,
+    Unknown,
+}
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct AnnotatedToken {
+    pub surface: String,
+    pub lemma: String,
+    pub reading: Option<String>,
+    pub furigana: FuriganaString,
+    pub status: TokenStatus,
+    /// Prefilled payload for `add_word`, only set for unknown tokens the
+    /// dictionary has.
+    pub add_payload: Option<WordCreatePayload>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct AnnotatedText {
+    pub tokens: Vec<AnnotatedToken>,
+}
+
+pub fn is_japanese(token: &MyToken) -> bool {
+    token
+        .text()
+        .chars()
+        .any(|c| wana_kana::utils::is_char_japanese(c))
+}
+
+/// Grammar rather than vocabulary, so never worth adding to the deck.
+pub fn is_grammatical(token: &MyToken) -> bool {
+    !is_japanese(token) || token.category().is_some_and(|c| c.is_grammatical())
+}
+
+pub fn token_status(token: &MyToken, deck: &HashMap<String, TokenStatus>) -> TokenStatus {
+    if let Some(status) = deck
+        .get(token.lemma_or_text())
+        .or_else(|| deck.get(token.text()))
+    {
+        *status
+    } else if is_grammatical(token) {
+        TokenStatus::Grammar
+    } else {
+        TokenStatus::Unknown
+    }
+}
+
+pub async fn known_words(words_state: &WordsState) -> HashSet<String> {
+    words_state
+        .get_words()
+        .await
+        .into_iter()
+        .map(|w| w.word)
+        .collect()
+}
+
+/// Status of every saved word, `Known` or `InDeck` depending on its reviews.
+pub async fn deck_statuses(
+    words_state: &WordsState,
+    history_state: &HistoryState,
+) -> HashMap<String, TokenStatus> {
+    let events = history_state.get_events().await;
+    let progress = crate::knowledge_base::stats::replay(&events);
+    words_state
+        .get_words()
+        .await
+        .into_iter()
+        .map(|w| {
+            let known = progress
+                .get(&(CardKind::Word, w.word.as_str()))
+                .is_some_and(|p| p.step >= KNOWN_MIN_STEP);
+            let status = if known {
+                TokenStatus::Known
+            } else {
+                TokenStatus::InDeck
+            };
+            (w.word, status)
+        })
+        .collect()
+}
+
+async fn furigana_for(
+    app_handle: &tauri::AppHandle,
+    text: &str,
+    reading: Option<String>,
+) -> FuriganaString {
+    let has_kanji = text.chars().any(wana_kana::utils::is_char_kanji);
+    if !has_kanji {
+        return FuriganaString::from_plain(text);
+    }
+    crate::kanji::parser::parse_word(app_handle, &text.to_string(), reading)
+        .await
+        .unwrap_or_else(|_| FuriganaString::from_plain(text))
+}
+
+async fn add_payload_for(app_handle: &tauri::AppHandle, lemma: &str) -> WordCreatePayload {
+    // Headword lookup only, this runs for every unknown token of the text.
+    let meaning = crate::translation::MyDictionary::get(app_handle)
+        .get_exact(lemma)
+        .map(|entry| entry.translations_string())
+        .unwrap_or_default();
+    let kanji_readings = furigana_for(app_handle, lemma, None)
+        .await
+        .kanji_readings()
+        .into_iter()
+        .map(|reading| KanjiReading { reading })
+        .collect();
+
+    WordCreatePayload {
+        word: lemma.to_string(),
+        meaning,
+        kanji_readings,
+        pitch_accents: vec![],
+        context: None,
+        tags: vec![],
+        add_missing_kanjis: false,
+    }
+}
+
+pub async fn annotate_tokens(
+    app_handle: &tauri::AppHandle,
+    tokens: Vec<MyToken>,
+    deck: &HashMap<String, TokenStatus>,
+) -> AnnotatedText {
+    let mut annotated = Vec::new();
+    for token in tokens {
+        let reading = token
+            .reading()
+            .map(|r| wana_kana::ConvertJapanese::to_hiragana(r));
+        let status = token_status(&token, deck);
+        let furigana = furigana_for(app_handle, token.text(), reading.clone()).await;
+        let add_payload = if status == TokenStatus::Unknown && !token.is_unknown() {
+            Some(add_payload_for(app_handle, token.lemma_or_text()).await)
+        } else {
+            None
+        };
+
+        annotated.push(AnnotatedToken {
+            surface: token.text().to_string(),
+            lemma: token.lemma_or_text().to_string(),
+            reading,
+            furigana,
+            status,
+            add_payload,
+        });
+    }
+    AnnotatedText { tokens: annotated }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn annotate_text(
+    text: String,
+    app_handle: tauri::AppHandle,
+    words_state: tauri::State<'_, WordsState>,
+    history_state: tauri::State<'_, HistoryState>,
+) -> Result<AnnotatedText, String> {
+    let tokens = crate::tokenizer::tokenize(&app_handle, &text).await?;
+    let deck = deck_statuses(&words_state, &history_state).await;
+    Ok(annotate_tokens(&app_handle, tokens, &deck).await)
+}
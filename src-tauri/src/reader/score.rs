@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use crate::data::heisig_kanji::HeisigKanjis;
+use crate::data::word_frequency::WordFrequencies;
+use crate::knowledge_base::history::HistoryState;
+use crate::knowledge_base::words::WordsState;
+use crate::translation::MyDictionary;
+use crate::word::MyToken;
+
+use super::TokenStatus;
+
+/// Share of tokens that has to be at or below a JLPT level for the text to be
+/// rated at that level.
+const JLPT_COVERAGE: f64 = 0.95;
+/// Rough upper bounds of the frequency ranks covered by N5..N2 vocabulary,
+/// anything rarer counts as N1.
+const JLPT_FREQUENCY_RANKS: [(u8, u32); 4] = [(5, 800), (4, 1_500), (3, 3_750), (2, 6_000)];
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct UnknownWord {
+    pub lemma: String,
+    pub occurrences: u32,
+    pub frequency_rank: Option<u32>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct TextScore {
+    pub token_count: u32,
+    pub known_token_count: u32,
+    pub known_token_percentage: f64,
+    pub lemma_count: u32,
+    pub known_lemma_count: u32,
+    pub known_lemma_percentage: f64,
+    /// Most common first, words without a rank last. More repeated in the
+    /// text wins ties.
+    pub unknown_words: Vec<UnknownWord>,
+    pub estimated_jlpt_level: Option<u8>,
+}
+
+fn percentage(part: u32, total: u32) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+fn frequency_rank(
+    lemma: &str,
+    dictionary: &MyDictionary,
+    word_frequencies: &WordFrequencies,
+) -> Option<u32> {
+    word_frequencies.corpus_rank(lemma).or_else(|| {
+        dictionary
+            .get_exact(lemma)
+            .and_then(|entry| entry.priority_rank())
+    })
+}
+
+/// JLPT level of a single token: the hardest of its kanji levels and of the
+/// level its frequency rank suggests. Words too rare to be ranked count as N1.
+fn token_jlpt_level(
+    token: &MyToken,
+    frequency_rank: Option<u32>,
+    heisig_kanjis: &HeisigKanjis,
+) -> u8 {
+    let kanji_level = token
+        .text()
+        .chars()
+        .filter(|c| wana_kana::utils::is_char_kanji(*c))
+        .map(|c| {
+            heisig_kanjis
+                .get_by_kanji(&c.to_string())
+                .first()
+                .and_then(|k| k.jlpt)
+                .unwrap_or(1)
+        })
+        .min()
+        .unwrap_or(5);
+    let frequency_level = frequency_rank
+        .map(|rank| {
+            JLPT_FREQUENCY_RANKS
+                .iter()
+                .find(|(_, max_rank)| rank <= *max_rank)
+                .map(|(level, _)| *level)
+                .unwrap_or(1)
+        })
+        .unwrap_or(1);
+    kanji_level.min(frequency_level)
+}
+
+fn estimate_jlpt_level(mut levels: Vec<u8>) -> Option<u8> {
+    if levels.is_empty() {
+        return None;
+    }
+    // Easiest first, the level at the coverage percentile is the one a reader
+    // needs to understand that share of the text.
+    levels.sort_unstable_by(|a, b| b.cmp(a));
+    let index = ((levels.len() as f64 * JLPT_COVERAGE).ceil() as usize).max(1) - 1;
+    levels.get(index).copied()
+}
+
+pub fn score_tokens(
+    tokens: &[MyToken],
+    deck: &HashMap<String, TokenStatus>,
+    dictionary: &MyDictionary,
+    word_frequencies: &WordFrequencies,
+    heisig_kanjis: &HeisigKanjis,
+) -> TextScore {
+    let mut token_count = 0;
+    let mut known_token_count = 0;
+    let mut lemma_known: HashMap<&str, bool> = HashMap::new();
+    let mut unknown_occurrences: HashMap<&str, u32> = HashMap::new();
+    let mut levels = Vec::new();
+
+    for token in tokens {
+        // Words missing from the dictionary can't be rated, they are
+        // mostly names.
+        if token.is_symbol() || !super::is_japanese(token) || token.is_unknown() {
+            continue;
+        }
+        let lemma = token.lemma_or_text();
+        let status = super::token_status(token, deck);
+        let known = status != TokenStatus::Unknown;
+
+        token_count += 1;
+        if known {
+            known_token_count += 1;
+        } else {
+            *unknown_occurrences.entry(lemma).or_default() += 1;
+        }
+        lemma_known.insert(lemma, known);
+
+        // Particles and auxiliaries say nothing about the vocabulary level.
+        if status == TokenStatus::Grammar {
+            continue;
+        }
+        let rank = frequency_rank(lemma, dictionary, word_frequencies);
+        levels.push(token_jlpt_level(token, rank, heisig_kanjis));
+    }
+
+    let mut unknown_words = unknown_occurrences
+        .into_iter()
+        .map(|(lemma, occurrences)| UnknownWord {
+            lemma: lemma.to_string(),
+            occurrences,
+            frequency_rank: frequency_rank(lemma, dictionary, word_frequencies),
+        })
+        .collect::<Vec<_>>();
+    unknown_words.sort_by_key(|w| {
+        (
+            w.frequency_rank.unwrap_or(u32::MAX),
+            std::cmp::Reverse(w.occurrences),
+        )
+    });
+
+    let lemma_count = lemma_known.len() as u32;
+    let known_lemma_count = lemma_known.values().filter(|known| **known).count() as u32;
+
+    TextScore {
+        token_count,
+        known_token_count,
+        known_token_percentage: percentage(known_token_count, token_count),
+        lemma_count,
+        known_lemma_count,
+        known_lemma_percentage: percentage(known_lemma_count, lemma_count),
+        unknown_words,
+        estimated_jlpt_level: estimate_jlpt_level(levels),
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn score_text(
+    text: String,
+    app_handle: tauri::AppHandle,
+    words_state: tauri::State<'_, WordsState>,
+    history_state: tauri::State<'_, HistoryState>,
+) -> Result<TextScore, String> {
+    let tokens = crate::tokenizer::tokenize(&app_handle, &text).await?;
+    let deck = super::deck_statuses(&words_state, &history_state).await;
+    let dictionary = MyDictionary::get(&app_handle);
+    let word_frequencies = crate::data::get_word_frequencies(&app_handle);
+    let heisig_kanjis = crate::data::get_heisig_kanjis(&app_handle);
+
+    Ok(score_tokens(
+        &tokens,
+        &deck,
+        &dictionary,
+        &word_frequencies,
+        &heisig_kanjis,
+    ))
+}
@@ -0,0 +1,331 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// When a change was made. Compared timestamp first, the counter orders
+/// changes made within the same second and the device breaks exact ties, so
+/// every replica picks the same winner.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Clock {
+    pub timestamp: u32,
+    pub counter: u32,
+    pub device: String,
+}
+
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, specta::Type,
+)]
+pub enum RecordKind {
+    Word,
+    Kanji,
+    Collection,
+    Mnemonic,
+    ReadingSound,
+    HistoryEvent,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, specta::Type)]
+pub enum ConflictStrategy {
+    /// The most recently changed side wins the whole entity.
+    LastWriterWins,
+    /// Every field goes to the side that changed it last, so edits to
+    /// different fields on two devices are both kept.
+    FieldMerge,
+}
+
+/// One entity as it travels between replicas: its JSON fields, each with the
+/// clock of its last change.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SyncRecord {
+    pub kind: RecordKind,
+    pub id: String,
+    pub fields: BTreeMap<String, Value>,
+    pub clocks: BTreeMap<String, Clock>,
+    /// Set when deleted. A field changed after the deletion brings the
+    /// entity back.
+    pub deleted: Option<Clock>,
+}
+
+impl SyncRecord {
+    pub fn key(&self) -> String {
+        record_key(self.kind, &self.id)
+    }
+
+    /// The latest change of any kind.
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clocks.values().chain(self.deleted.as_ref()).max()
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        match &self.deleted {
+            Some(deleted) => self.clocks.values().all(|c| c < deleted),
+            None => false,
+        }
+    }
+
+    pub fn to_entity(&self) -> Map<String, Value> {
+        self.fields
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    /// Whether `device` changed anything after `since`.
+    fn changed_by_since(&self, device: &str, since: Option<&Clock>) -> bool {
+        self.clocks
+            .values()
+            .chain(self.deleted.as_ref())
+            .any(|c| c.device == device && Some(c) > since)
+    }
+
+    /// Returns whether `self` changed.
+    fn merge(&mut self, incoming: &SyncRecord, strategy: ConflictStrategy) -> bool {
+        match strategy {
+            ConflictStrategy::LastWriterWins => {
+                if incoming.clock() > self.clock() {
+                    *self = incoming.clone();
+                    true
+                } else {
+                    false
+                }
+            }
+            ConflictStrategy::FieldMerge => {
+                let mut changed = false;
+                for (field, clock) in &incoming.clocks {
+                    if Some(clock) > self.clocks.get(field) {
+                        let value = incoming.fields.get(field).cloned().unwrap_or(Value::Null);
+                        self.fields.insert(field.clone(), value);
+                        self.clocks.insert(field.clone(), clock.clone());
+                        changed = true;
+                    }
+                }
+                if incoming.deleted > self.deleted {
+                    self.deleted = incoming.deleted.clone();
+                    changed = true;
+                }
+                changed
+            }
+        }
+    }
+}
+
+fn record_key(kind: RecordKind, id: &str) -> String {
+    format!("{:?}/{}", kind, id)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct MergeReport {
+    /// Incoming records that changed the replica.
+    pub applied: u32,
+    /// Records changed on both sides since the last sync.
+    pub conflicts: u32,
+}
+
+/// Everything one device or the server knows about the synced entities,
+/// deleted ones included.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Replica {
+    pub device: String,
+    /// The latest clock handed out or seen, new clocks are always later even
+    /// if the system clock goes back.
+    pub last_clock: Option<Clock>,
+    /// The latest of this device's clocks the server has received.
+    pub pushed: Option<Clock>,
+    /// The server synced with last, see `SyncResponse::server`.
+    pub server: Option<String>,
+    /// Server position of the last pull.
+    pub cursor: u32,
+    records: BTreeMap<String, SyncRecord>,
+}
+
+impl Replica {
+    pub fn new(device: &str) -> Self {
+        Replica {
+            device: device.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &SyncRecord> {
+        self.records.values()
+    }
+
+    pub fn get(&self, kind: RecordKind, id: &str) -> Option<&SyncRecord> {
+        self.records.get(&record_key(kind, id))
+    }
+
+    fn tick(&mut self, now: u32) -> Clock {
+        let clock = match &self.last_clock {
+            Some(last) if last.timestamp >= now => Clock {
+                timestamp: last.timestamp,
+                counter: last.counter + 1,
+                device: self.device.clone(),
+            },
+            _ => Clock {
+                timestamp: now,
+                counter: 0,
+                device: self.device.clone(),
+            },
+        };
+        self.last_clock = Some(clock.clone());
+        clock
+    }
+
+    fn observe(&mut self, clock: Option<&Clock>) {
+        if clock > self.last_clock.as_ref() {
+            self.last_clock = clock.cloned();
+        }
+    }
+
+    /// Compares the current entities of `kind` with what was recorded last
+    /// time and stamps what changed: new and edited fields, and entities that
+    /// are gone. Entities need an `id` field. Returns how many changed.
+    pub fn record_entities(
+        &mut self,
+        kind: RecordKind,
+        entities: Vec<Map<String, Value>>,
+        now: u32,
+    ) -> u32 {
+        let mut changed = 0;
+        let mut present = Vec::new();
+        for entity in entities {
+            let Some(id) = entity
+                .get("id")
+                .and_then(|id| id.as_str())
+                .map(String::from)
+            else {
+                continue;
+            };
+            let key = record_key(kind, &id);
+            present.push(key.clone());
+
+            let mut record = self.records.get(&key).cloned().unwrap_or(SyncRecord {
+                kind,
+                id,
+                fields: BTreeMap::new(),
+                clocks: BTreeMap::new(),
+                deleted: None,
+            });
+            let was_deleted = record.is_deleted();
+            let mut edited = false;
+            for (field, value) in &entity {
+                if was_deleted || record.fields.get(field) != Some(value) {
+                    let clock = self.tick(now);
+                    record.fields.insert(field.clone(), value.clone());
+                    record.clocks.insert(field.clone(), clock);
+                    edited = true;
+                }
+            }
+            // Fields dropped from the entity, e.g. an optional one cleared.
+            let dropped = record
+                .fields
+                .keys()
+                .filter(|field| !entity.contains_key(*field))
+                .cloned()
+                .collect::<Vec<_>>();
+            for field in dropped {
+                if record.fields.get(&field) != Some(&Value::Null) {
+                    let clock = self.tick(now);
+                    record.fields.insert(field.clone(), Value::Null);
+                    record.clocks.insert(field, clock);
+                    edited = true;
+                }
+            }
+            if edited {
+                changed += 1;
+                self.records.insert(key, record);
+            }
+        }
+
+        let gone = self
+            .records
+            .iter()
+            .filter(|(key, record)| {
+                record.kind == kind && !record.is_deleted() && !present.contains(key)
+            })
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in gone {
+            let clock = self.tick(now);
+            if let Some(record) = self.records.get_mut(&key) {
+                record.deleted = Some(clock);
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    /// Deletes a record from this replica, e.g. a duplicate.
+    pub fn delete(&mut self, kind: RecordKind, id: &str, now: u32) {
+        let clock = self.tick(now);
+        if let Some(record) = self.records.get_mut(&record_key(kind, id)) {
+            record.deleted = Some(clock);
+        }
+    }
+
+    /// Changes one field of a record, e.g. of the one kept of duplicates.
+    pub fn set_field(&mut self, kind: RecordKind, id: &str, field: &str, value: Value, now: u32) {
+        let clock = self.tick(now);
+        if let Some(record) = self.records.get_mut(&record_key(kind, id)) {
+            record.fields.insert(field.to_string(), value);
+            record.clocks.insert(field.to_string(), clock);
+        }
+    }
+
+    /// Records this device changed since the server last received them.
+    pub fn pending_changes(&self) -> Vec<SyncRecord> {
+        self.records
+            .values()
+            .filter(|r| r.changed_by_since(&self.device, self.pushed.as_ref()))
+            .cloned()
+            .collect()
+    }
+
+    /// Call once the server has received `pending_changes`.
+    pub fn mark_pushed(&mut self) {
+        self.pushed = self.last_clock.clone();
+    }
+
+    /// For a server that lost its state: everything is pushed again and
+    /// pulled from the start.
+    pub fn forget_server(&mut self) {
+        self.server = None;
+        self.pushed = None;
+        self.cursor = 0;
+    }
+
+    pub fn merge(&mut self, incoming: &[SyncRecord], strategy: ConflictStrategy) -> MergeReport {
+        let mut report = MergeReport::default();
+        for record in incoming {
+            self.observe(record.clock());
+            let key = record.key();
+            match self.records.get_mut(&key) {
+                Some(local) => {
+                    let conflicting = local != record
+                        && local.changed_by_since(&self.device, self.pushed.as_ref());
+                    if local.merge(record, strategy) {
+                        report.applied += 1;
+                        if conflicting {
+                            report.conflicts += 1;
+                        }
+                    }
+                }
+                None => {
+                    self.records.insert(key, record.clone());
+                    report.applied += 1;
+                }
+            }
+        }
+        report
+    }
+
+    /// The entities of `kind` that aren't deleted.
+    pub fn entities(&self, kind: RecordKind) -> Vec<Map<String, Value>> {
+        self.records
+            .values()
+            .filter(|r| r.kind == kind && !r.is_deleted())
+            .map(|r| r.to_entity())
+            .collect()
+    }
+}
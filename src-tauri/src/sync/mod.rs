@@ -0,0 +1,389 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tauri::async_runtime::RwLock;
+use tauri::{Manager, Runtime};
+use tauri_plugin_store::Store;
+use tokio::sync::RwLockWriteGuard;
+
+use crate::knowledge_base::collections::CollectionsState;
+use crate::knowledge_base::entity::{Entities, EntitiesState};
+use crate::knowledge_base::history::{
+    CardKind, HISTORY_STORE_FILE, HistoryEvent, HistoryEventKind, HistoryState,
+};
+use crate::knowledge_base::kanjis::KanjisState;
+use crate::knowledge_base::mnemonics::MnemonicsState;
+use crate::knowledge_base::reading_sounds::ReadingSoundsState;
+use crate::knowledge_base::tags::normalize_tags;
+use crate::knowledge_base::words::{WORDS_STORE_FILE, WordsState};
+use crate::knowledge_base::{
+    COLLECTIONS_STORE_FILE, KANJIS_STORE_FILE, MNEMONICS_STORE_FILE, READING_SOUNDS_STORE_FILE,
+};
+
+pub mod change_set;
+pub mod protocol;
+pub mod server;
+
+use change_set::{ConflictStrategy, RecordKind, Replica};
+use protocol::SyncRequest;
+use server::LocalSyncServer;
+
+/// The replica of the profile, kept apart from the synced stores so it is
+/// neither exported nor synced itself.
+const SYNC_STORE_FILE: &str = "sync.json";
+
+/// Where the entities of every synced kind are stored.
+const SYNCED_STORES: [(RecordKind, &str, &str); 6] = [
+    (RecordKind::Word, WORDS_STORE_FILE, "words"),
+    (RecordKind::Kanji, KANJIS_STORE_FILE, "entities"),
+    (RecordKind::Collection, COLLECTIONS_STORE_FILE, "entities"),
+    (RecordKind::Mnemonic, MNEMONICS_STORE_FILE, "entities"),
+    (
+        RecordKind::ReadingSound,
+        READING_SOUNDS_STORE_FILE,
+        "entities",
+    ),
+    (RecordKind::HistoryEvent, HISTORY_STORE_FILE, "events"),
+];
+
+pub fn setup(app: &tauri::App) {
+    app.manage(SyncServerState(RwLock::new(None)));
+}
+
+pub struct SyncServerState(RwLock<Option<LocalSyncServer>>);
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq, specta::Type)]
+pub struct SyncReport {
+    pub pushed: u32,
+    pub pulled: u32,
+    /// Entities changed on both sides, resolved by the strategy.
+    pub conflicts: u32,
+    /// Entities added on two devices independently, only one is kept.
+    pub duplicates: u32,
+}
+
+/// The field two entities of a kind can't share. Adding the same word on two
+/// devices gives it two ids.
+fn unique_field(kind: RecordKind) -> Option<&'static str> {
+    match kind {
+        RecordKind::Word => Some("word"),
+        RecordKind::Kanji => Some("kanji"),
+        RecordKind::ReadingSound => Some("sound"),
+        RecordKind::Collection | RecordKind::Mnemonic | RecordKind::HistoryEvent => None,
+    }
+}
+
+/// Which history events a kind's entities have, `None` for kinds without.
+fn card_kind(kind: RecordKind) -> Option<CardKind> {
+    match kind {
+        RecordKind::Word => Some(CardKind::Word),
+        RecordKind::Kanji => Some(CardKind::Kanji),
+        RecordKind::Collection
+        | RecordKind::Mnemonic
+        | RecordKind::ReadingSound
+        | RecordKind::HistoryEvent => None,
+    }
+}
+
+/// Keeps the entity with the smallest id of every group sharing the unique
+/// field, so all devices keep the same one. Collections and history refer to
+/// cards by the word or kanji rather than the id, what has to follow the
+/// removed duplicate is its tags and its `Added` event.
+fn remove_duplicates(replica: &mut Replica, now: u32) -> u32 {
+    let mut removed = 0;
+    let mut duplicated_cards = HashSet::new();
+    for (kind, _, _) in SYNCED_STORES {
+        let Some(field) = unique_field(kind) else {
+            continue;
+        };
+        let mut kept = HashMap::<String, Map<String, Value>>::new();
+        let mut entities = replica.entities(kind);
+        entities.sort_by(|a, b| entity_id(a).cmp(entity_id(b)));
+        for entity in entities {
+            let Some(value) = entity.get(field).cloned() else {
+                continue;
+            };
+            match kept.entry(value.to_string()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(entity);
+                }
+                Entry::Occupied(mut entry) => {
+                    carry_over_tags(replica, kind, entry.get_mut(), &entity, now);
+                    replica.delete(kind, entity_id(&entity), now);
+                    if let (Some(card_kind), Some(card)) = (card_kind(kind), value.as_str()) {
+                        duplicated_cards.insert((card_kind, card.to_string()));
+                    }
+                    removed += 1;
+                }
+            }
+        }
+    }
+    for id in redundant_added_events(replica, &duplicated_cards) {
+        replica.delete(RecordKind::HistoryEvent, &id, now);
+    }
+    removed
+}
+
+/// Adds the tags of a removed duplicate to the one kept, so the card stays in
+/// the collections it belonged to through them.
+fn carry_over_tags(
+    replica: &mut Replica,
+    kind: RecordKind,
+    kept: &mut Map<String, Value>,
+    duplicate: &Map<String, Value>,
+    now: u32,
+) {
+    let tags = |entity: &Map<String, Value>| {
+        entity
+            .get("tags")
+            .and_then(|tags| serde_json::from_value::<Vec<String>>(tags.clone()).ok())
+    };
+    let (Some(kept_tags), Some(duplicate_tags)) = (tags(kept), tags(duplicate)) else {
+        return;
+    };
+    let merged = normalize_tags(&[kept_tags.clone(), duplicate_tags].concat());
+    if merged != kept_tags {
+        let merged = Value::from(merged);
+        replica.set_field(kind, entity_id(kept), "tags", merged.clone(), now);
+        kept.insert("tags".to_string(), merged);
+    }
+}
+
+/// Ids of `Added` events of `cards` recorded while the card already existed,
+/// i.e. those of duplicates added on another device. Replaying them would
+/// start the card's progress over.
+fn redundant_added_events(replica: &Replica, cards: &HashSet<(CardKind, String)>) -> Vec<String> {
+    if cards.is_empty() {
+        return vec![];
+    }
+    let mut events = replica
+        .entities(RecordKind::HistoryEvent)
+        .into_iter()
+        .filter_map(|event| serde_json::from_value::<HistoryEvent>(Value::Object(event)).ok())
+        .filter(|event| cards.contains(&(event.card_kind, event.card.clone())))
+        .collect::<Vec<_>>();
+    events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.id.cmp(&b.id)));
+
+    let mut existing = HashSet::new();
+    let mut redundant = Vec::new();
+    for event in events {
+        let card = (event.card_kind, event.card);
+        match event.kind {
+            HistoryEventKind::Added => {
+                if !existing.insert(card) {
+                    redundant.push(event.id);
+                }
+            }
+            HistoryEventKind::Deleted => {
+                existing.remove(&card);
+            }
+            HistoryEventKind::Reviewed { .. } | HistoryEventKind::Edited => {}
+        }
+    }
+    redundant
+}
+
+fn entity_id(entity: &Map<String, Value>) -> &str {
+    entity.get("id").and_then(Value::as_str).unwrap_or_default()
+}
+
+/// One round trip with the server at `url`: pushes what changed locally,
+/// pulls and merges what changed elsewhere. The caller records local changes
+/// into the replica before and writes its entities back after.
+pub fn sync_replica(
+    replica: &mut Replica,
+    url: &str,
+    token: &str,
+    strategy: ConflictStrategy,
+    now: u32,
+) -> Result<SyncReport, String> {
+    let mut request = SyncRequest {
+        token: token.to_string(),
+        device: replica.device.clone(),
+        cursor: replica.cursor,
+        strategy,
+        changes: replica.pending_changes(),
+    };
+    let mut response = protocol::post(url, &request)?;
+    if replica
+        .server
+        .as_ref()
+        .is_some_and(|s| s != &response.server)
+    {
+        // The server started over, it only has what was just pushed.
+        replica.forget_server();
+        request.cursor = 0;
+        request.changes = replica.records().cloned().collect();
+        response = protocol::post(url, &request)?;
+    }
+
+    let merged = replica.merge(&response.changes, strategy);
+    replica.server = Some(response.server);
+    replica.cursor = response.cursor;
+    replica.mark_pushed();
+    Ok(SyncReport {
+        pushed: request.changes.len() as u32,
+        pulled: merged.applied,
+        conflicts: merged.conflicts,
+        duplicates: remove_duplicates(replica, now),
+    })
+}
+
+/// Stores keep entities in the order they were made, the replica by id.
+fn sort_entities(entities: &mut [Map<String, Value>]) {
+    let made_at = |entity: &Map<String, Value>| {
+        entity
+            .get("created_at")
+            .or_else(|| entity.get("timestamp"))
+            .and_then(Value::as_u64)
+            .unwrap_or_default()
+    };
+    entities.sort_by(|a, b| {
+        made_at(a)
+            .cmp(&made_at(b))
+            .then_with(|| entity_id(a).cmp(entity_id(b)))
+    });
+}
+
+/// Syncs the entities kept in the stores of a profile, `store` resolving
+/// them by file name. Local changes are recorded into the replica kept in
+/// `sync.json`, and after the round trip with `url` the merged entities are
+/// written back. Blocks on the network, the stores must not change meanwhile.
+pub fn sync_stores<R: Runtime>(
+    store: impl Fn(&str) -> Result<Arc<Store<R>>, String>,
+    url: &str,
+    token: &str,
+    strategy: ConflictStrategy,
+    now: u32,
+) -> Result<SyncReport, String> {
+    let sync_store = store(SYNC_STORE_FILE)?;
+    let mut replica = sync_store
+        .get("replica")
+        .and_then(|data| serde_json::from_value::<Replica>(data).ok())
+        .unwrap_or_else(|| Replica::new(&uuid::Uuid::new_v4().to_string()));
+
+    for (kind, file, key) in SYNCED_STORES {
+        let entities = store(file)?
+            .get(key)
+            .and_then(|data| serde_json::from_value::<Vec<Map<String, Value>>>(data).ok())
+            .unwrap_or_default();
+        replica.record_entities(kind, entities, now);
+    }
+
+    let report = sync_replica(&mut replica, url, token, strategy, now);
+    // Local changes were stamped even if the server couldn't be reached.
+    let replica_value = serde_json::to_value(&replica).map_err(|e| e.to_string())?;
+    sync_store.set("replica", replica_value);
+    let report = report?;
+
+    for (kind, file, key) in SYNCED_STORES {
+        let mut entities = replica.entities(kind);
+        sort_entities(&mut entities);
+        let entities = entities.into_iter().map(Value::Object).collect();
+        store(file)?.set(key, Value::Array(entities));
+    }
+    Ok(report)
+}
+
+async fn lock_entities<S: EntitiesState>(
+    app_handle: &tauri::AppHandle,
+) -> Option<RwLockWriteGuard<'_, S::Entities>> {
+    Some(app_handle.try_state::<S>()?.inner().lock().await)
+}
+
+/// Syncs the words, kanji, collections, mnemonics, reading sounds and review
+/// history of the active profile with the server at `url`, which was started
+/// with `token`.
+#[tauri::command]
+#[specta::specta]
+pub async fn sync_knowledge_base(
+    app_handle: tauri::AppHandle,
+    url: String,
+    token: String,
+    strategy: ConflictStrategy,
+) -> Result<SyncReport, String> {
+    // Held until the merged entities are loaded back, so nothing changed
+    // during the round trip gets overwritten.
+    let mut words = match app_handle.try_state::<WordsState>() {
+        Some(state) => Some(state.inner().0.write().await),
+        None => None,
+    };
+    let mut kanjis = lock_entities::<KanjisState>(&app_handle).await;
+    let mut collections = lock_entities::<CollectionsState>(&app_handle).await;
+    let mut mnemonics = lock_entities::<MnemonicsState>(&app_handle).await;
+    let mut reading_sounds = lock_entities::<ReadingSoundsState>(&app_handle).await;
+    let mut history = match app_handle.try_state::<HistoryState>() {
+        Some(state) => Some(state.inner().0.write().await),
+        None => None,
+    };
+
+    // Resolved up front, a profile switch meanwhile must not redirect them.
+    let stores = SYNCED_STORES
+        .iter()
+        .map(|(_, file, _)| *file)
+        .chain([SYNC_STORE_FILE])
+        .map(|file| Ok((file, crate::profile::store(&app_handle, file)?)))
+        .collect::<Result<HashMap<_, _>, String>>()?;
+    let now = crate::knowledge_base::unix_timestamp();
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        let store = |file: &str| {
+            stores
+                .get(file)
+                .cloned()
+                .ok_or(format!("{} is not synced", file))
+        };
+        sync_stores(store, &url, &token, strategy, now)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    if let Some(words) = words.as_mut() {
+        words.load_words();
+    }
+    if let Some(kanjis) = kanjis.as_mut() {
+        kanjis.load_entities();
+    }
+    if let Some(collections) = collections.as_mut() {
+        collections.load_entities();
+    }
+    if let Some(mnemonics) = mnemonics.as_mut() {
+        mnemonics.load_entities();
+    }
+    if let Some(reading_sounds) = reading_sounds.as_mut() {
+        reading_sounds.load_entities();
+    }
+    if let Some(history) = history.as_mut() {
+        history.load_events();
+    }
+    Ok(report)
+}
+
+/// Serves the sync endpoint from this machine, for devices on the same
+/// network. Returns the URL to sync with from here, other devices use this
+/// machine's address with the same port. Anyone on the network can reach it,
+/// only devices syncing with `token` are let in.
+#[tauri::command]
+#[specta::specta]
+pub async fn start_sync_server(
+    state: tauri::State<'_, SyncServerState>,
+    port: u16,
+    token: String,
+) -> Result<String, String> {
+    let mut server = state.0.write().await;
+    // Stops the previous one first, it may hold the port.
+    *server = None;
+    let started = LocalSyncServer::start(&format!("0.0.0.0:{}", port), &token)?;
+    let url = started.url();
+    *server = Some(started);
+    Ok(url)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn stop_sync_server(state: tauri::State<'_, SyncServerState>) -> Result<(), String> {
+    *state.0.write().await = None;
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::change_set::{ConflictStrategy, SyncRecord};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The sync endpoint is a single `POST` taking a `SyncRequest` and answering
+/// with a `SyncResponse`, both JSON.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SyncRequest {
+    /// Shared by every device syncing with the server, which refuses
+    /// requests without it.
+    pub token: String,
+    pub device: String,
+    /// What the device already pulled, `0` for everything.
+    pub cursor: u32,
+    pub strategy: ConflictStrategy,
+    pub changes: Vec<SyncRecord>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SyncResponse {
+    /// Changes when the server loses its state, the cursor is meaningless
+    /// then.
+    pub server: String,
+    /// To send as the next `cursor`.
+    pub cursor: u32,
+    /// Records changed on the server after the request's cursor, after the
+    /// request's changes were merged in.
+    pub changes: Vec<SyncRecord>,
+}
+
+/// Head and body of an HTTP message. Only `Content-Length` bodies are
+/// supported, which is what the bundled server sends.
+pub fn read_message(stream: &mut impl Read) -> Result<(Vec<String>, Vec<u8>), String> {
+    let mut reader = BufReader::new(stream);
+    let mut head = Vec::new();
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line).map_err(|e| e.to_string())?;
+        let line = line.trim_end().to_string();
+        if read == 0 || line.is_empty() {
+            break;
+        }
+        head.push(line);
+    }
+
+    let content_length = head
+        .iter()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    Ok((head, body))
+}
+
+/// `http://host:port/path` → `("host:port", "/path")`.
+fn split_url(url: &str) -> Result<(&str, &str), String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or("Only http:// sync servers are supported".to_string())?;
+    Ok(match rest.find('/') {
+        Some(slash) => (&rest[..slash], &rest[slash..]),
+        None => (rest, "/"),
+    })
+}
+
+/// Plain HTTP/1.1 over `std::net` is all the endpoint needs, the server is
+/// expected on the local network.
+pub fn post(url: &str, request: &SyncRequest) -> Result<SyncResponse, String> {
+    let (host, path) = split_url(url)?;
+    let body = serde_json::to_vec(request).map_err(|e| e.to_string())?;
+
+    let mut stream =
+        TcpStream::connect(host).map_err(|e| format!("Could not reach {}: {}", host, e))?;
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        path,
+        host,
+        body.len()
+    )
+    .and_then(|_| stream.write_all(&body))
+    .map_err(|e| e.to_string())?;
+
+    let (head, body) = read_message(&mut stream)?;
+    let status = head.first().ok_or("Empty response".to_string())?;
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(format!(
+            "Sync server answered {}: {}",
+            status,
+            String::from_utf8_lossy(&body)
+        ));
+    }
+    serde_json::from_slice(&body).map_err(|e| format!("Invalid sync response: {}", e))
+}
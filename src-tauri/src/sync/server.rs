@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use super::change_set::Replica;
+use super::protocol::{SyncRequest, SyncResponse, read_message};
+
+pub const SYNC_PATH: &str = "/sync";
+const SERVER_DEVICE: &str = "server";
+/// A client that stops sending mid-request holds up everyone else, the
+/// server handles one request at a time.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The server side of the protocol, kept in memory.
+#[derive(Default)]
+pub struct SyncHub {
+    /// New for every hub, tells clients the server started over.
+    id: String,
+    replica: Replica,
+    /// Bumped for every record that changes, requests pull by it.
+    sequence: u32,
+    record_sequences: HashMap<String, u32>,
+}
+
+impl SyncHub {
+    pub fn new() -> Self {
+        SyncHub {
+            id: uuid::Uuid::new_v4().to_string(),
+            replica: Replica::new(SERVER_DEVICE),
+            ..Default::default()
+        }
+    }
+
+    pub fn handle(&mut self, request: &SyncRequest) -> SyncResponse {
+        for record in &request.changes {
+            let before = self.replica.get(record.kind, &record.id).cloned();
+            self.replica
+                .merge(std::slice::from_ref(record), request.strategy);
+            if self.replica.get(record.kind, &record.id) != before.as_ref() {
+                self.sequence += 1;
+                self.record_sequences.insert(record.key(), self.sequence);
+            }
+        }
+
+        let changes = self
+            .replica
+            .records()
+            .filter(|r| {
+                self.record_sequences
+                    .get(&r.key())
+                    .is_some_and(|sequence| *sequence > request.cursor)
+            })
+            .cloned()
+            .collect();
+        SyncResponse {
+            server: self.id.clone(),
+            cursor: self.sequence,
+            changes,
+        }
+    }
+}
+
+/// A sync endpoint for the local network, or for tests. Everything is lost
+/// when it stops, devices notice by the new hub id and push their whole
+/// state again.
+pub struct LocalSyncServer {
+    address: SocketAddr,
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl LocalSyncServer {
+    /// `address` like `0.0.0.0:8765`, port `0` picks a free one. Only
+    /// requests carrying `token` are served.
+    pub fn start(address: &str, token: &str) -> Result<Self, String> {
+        if token.is_empty() {
+            return Err("Sync token is empty".to_string());
+        }
+        let token = token.to_string();
+        let listener = TcpListener::bind(address)
+            .map_err(|e| format!("Could not listen on {}: {}", address, e))?;
+        let address = listener.local_addr().map_err(|e| e.to_string())?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let hub = Arc::new(Mutex::new(SyncHub::new()));
+
+        let thread_stopped = stopped.clone();
+        let thread = std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                // One bad client must not take the server down.
+                if let Err(e) = stream
+                    .map_err(|e| e.to_string())
+                    .and_then(|stream| serve(stream, &token, &hub))
+                {
+                    eprintln!("Sync request failed: {}", e);
+                }
+            }
+        });
+
+        Ok(LocalSyncServer {
+            address,
+            stopped,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Where clients on this machine reach the server.
+    pub fn url(&self) -> String {
+        format!("http://{}{}", self.loopback_address(), SYNC_PATH)
+    }
+
+    fn loopback_address(&self) -> SocketAddr {
+        if self.address.ip().is_unspecified() {
+            SocketAddr::new(Ipv4Addr::LOCALHOST.into(), self.address.port())
+        } else {
+            self.address
+        }
+    }
+}
+
+impl Drop for LocalSyncServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wakes up the blocking accept so the thread sees the flag.
+        let _ = TcpStream::connect(self.loopback_address());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &[u8]) -> Result<(), String> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        body.len()
+    )
+    .and_then(|_| stream.write_all(body))
+    .map_err(|e| e.to_string())
+}
+
+fn serve(mut stream: TcpStream, token: &str, hub: &Mutex<SyncHub>) -> Result<(), String> {
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    let (head, body) = read_message(&mut stream)?;
+    let request_line = head.first().map(String::as_str).unwrap_or_default();
+    if request_line != format!("POST {} HTTP/1.1", SYNC_PATH) {
+        return respond(&mut stream, "404 Not Found", b"\"Not found\"");
+    }
+
+    let request = match serde_json::from_slice::<SyncRequest>(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            let message = serde_json::to_vec(&e.to_string()).unwrap_or_default();
+            return respond(&mut stream, "400 Bad Request", &message);
+        }
+    };
+    if request.token != token {
+        return respond(&mut stream, "401 Unauthorized", b"\"Wrong sync token\"");
+    }
+    let response = hub.lock().map_err(|e| e.to_string())?.handle(&request);
+    let body = serde_json::to_vec(&response).map_err(|e| e.to_string())?;
+    respond(&mut stream, "200 OK", &body)
+}
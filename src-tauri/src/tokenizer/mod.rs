@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use lindera::dictionary::{DictionaryKind, load_embedded_dictionary};
+use lindera::mode::Mode;
+use lindera::segmenter::Segmenter;
+use lindera::tokenizer::Tokenizer;
+use tauri::async_runtime::RwLock;
+use tauri::{Manager, Wry};
+use tauri_plugin_store::Store;
+
+use crate::word::MyToken;
+use crate::word::token::{DetailsSchema, IPADIC_SCHEMA, UNIDIC_SCHEMA};
+use user_dictionary::UserDictionaryEntry;
+
+pub mod user_dictionary;
+
+pub const SETTINGS_STORE_FILE: &str = "settings.json";
+const DICTIONARY_SETTING: &str = "tokenizer_dictionary";
+/// Texts tokenized while typing are short and repetitive, a few thousand of
+/// them cover a session. The cache is simply dropped once it is full.
+const TOKEN_CACHE_CAPACITY: usize = 5_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum TokenizerDictionary {
+    Ipadic,
+    /// Only available when built with the `unidic` feature.
+    Unidic,
+}
+
+impl TokenizerDictionary {
+    fn kind(&self) -> Result<DictionaryKind, String> {
+        match self {
+            TokenizerDictionary::Ipadic => Ok(DictionaryKind::IPADIC),
+            #[cfg(feature = "unidic")]
+            TokenizerDictionary::Unidic => Ok(DictionaryKind::UniDic),
+            #[cfg(not(feature = "unidic"))]
+            TokenizerDictionary::Unidic => {
+                Err("UniDic support is not compiled in, enable the `unidic` feature".to_string())
+            }
+        }
+    }
+
+    fn schema(&self) -> &'static DetailsSchema {
+        match self {
+            TokenizerDictionary::Ipadic => &IPADIC_SCHEMA,
+            TokenizerDictionary::Unidic => &UNIDIC_SCHEMA,
+        }
+    }
+}
+
+fn saved_dictionary(store: Option<&std::sync::Arc<Store<Wry>>>) -> TokenizerDictionary {
+    store
+        .and_then(|store| store.get(DICTIONARY_SETTING))
+        .and_then(|value| serde_json::from_value::<TokenizerDictionary>(value).ok())
+        .unwrap_or(TokenizerDictionary::Ipadic)
+}
+
+pub fn setup(app: &tauri::App) {
+    let store = crate::profile::store(app, SETTINGS_STORE_FILE).ok();
+    let dictionary = saved_dictionary(store.as_ref());
+    let user_entries = user_dictionary::setup(app);
+    let user_dictionary_path = user_dictionary::csv_path(app);
+
+    let service = TokenizerService::new(dictionary, user_entries.clone(), &user_dictionary_path)
+        .or_else(|e| {
+            eprintln!(
+                "Failed to load {:?} tokenizer, using IPADIC without user dictionary: {}",
+                dictionary, e
+            );
+            TokenizerService::new(TokenizerDictionary::Ipadic, vec![], &None)
+        });
+    match service {
+        Ok(service) => {
+            app.manage(TokenizerState {
+                service: RwLock::new(service),
+                store: RwLock::new(store),
+                user_dictionary_path,
+            });
+        }
+        Err(e) => eprintln!("Failed to load tokenizer: {}", e),
+    }
+}
+
+pub struct TokenizerState {
+    service: RwLock<TokenizerService>,
+    /// Swapped out when switching profiles.
+    store: RwLock<Option<std::sync::Arc<Store<Wry>>>>,
+    user_dictionary_path: Option<PathBuf>,
+}
+
+impl TokenizerState {
+    pub async fn tokenize(&self, text: &str) -> Result<Vec<MyToken>, String> {
+        self.service.read().await.tokenize(text)
+    }
+
+    /// For bulk work (e.g. indexing a corpus) running outside of the async
+    /// runtime. Skips the cache so it is not flushed by one-off texts.
+    pub fn blocking_tokenize_uncached(&self, text: &str) -> Result<Vec<MyToken>, String> {
+        self.service.blocking_read().tokenize_uncached(text)
+    }
+
+    pub async fn dictionary(&self) -> TokenizerDictionary {
+        self.service.read().await.dictionary
+    }
+
+    pub async fn set_dictionary(&self, dictionary: TokenizerDictionary) -> Result<(), String> {
+        let mut service = self.service.write().await;
+        *service = TokenizerService::new(
+            dictionary,
+            service.user_entries.clone(),
+            &self.user_dictionary_path,
+        )?;
+        if let Some(store) = self.store.read().await.as_ref() {
+            let value = serde_json::to_value(dictionary).map_err(|e| e.to_string())?;
+            store.set(DICTIONARY_SETTING, value);
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the segmenter with the dictionary setting saved in `store`,
+    /// e.g. after it was overwritten or another profile was switched to.
+    pub async fn reload(
+        &self,
+        store: Option<std::sync::Arc<Store<Wry>>>,
+        user_entries: Vec<UserDictionaryEntry>,
+    ) -> Result<(), String> {
+        let dictionary = saved_dictionary(store.as_ref());
+        *self.store.write().await = store;
+        let mut service = self.service.write().await;
+        *service = TokenizerService::new(dictionary, user_entries, &self.user_dictionary_path)?;
+        Ok(())
+    }
+
+    /// Rebuilds the segmenter with the given user dictionary entries.
+    pub async fn set_user_dictionary(
+        &self,
+        user_entries: Vec<UserDictionaryEntry>,
+    ) -> Result<(), String> {
+        let mut service = self.service.write().await;
+        *service =
+            TokenizerService::new(service.dictionary, user_entries, &self.user_dictionary_path)?;
+        Ok(())
+    }
+}
+
+pub struct TokenizerService {
+    dictionary: TokenizerDictionary,
+    user_entries: Vec<UserDictionaryEntry>,
+    tokenizer: Tokenizer,
+    cache: Mutex<HashMap<String, Vec<MyToken>>>,
+}
+
+impl TokenizerService {
+    pub fn new(
+        dictionary: TokenizerDictionary,
+        user_entries: Vec<UserDictionaryEntry>,
+        user_dictionary_path: &Option<PathBuf>,
+    ) -> Result<Self, String> {
+        let lindera_dictionary = load_embedded_dictionary(dictionary.kind()?)
+            .map_err(|_| format!("Could not load dictionary"))?;
+        let lindera_user_dictionary = match user_dictionary_path {
+            Some(path) => {
+                user_dictionary::build_user_dictionary(&lindera_dictionary, &user_entries, path)?
+            }
+            None => None,
+        };
+        let segmenter = Segmenter::new(Mode::Normal, lindera_dictionary, lindera_user_dictionary);
+        Ok(TokenizerService {
+            dictionary,
+            user_entries,
+            tokenizer: Tokenizer::new(segmenter),
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn tokenize_uncached(&self, text: &str) -> Result<Vec<MyToken>, String> {
+        let mut tokens = self
+            .tokenizer
+            .tokenize(text)
+            .map_err(|_| format!("Could not tokenize work {}", text))?;
+
+        let mut token_vec = Vec::new();
+        for token in tokens.iter_mut() {
+            token_vec.push(MyToken::from_lindera_token(token, self.dictionary.schema()));
+        }
+
+        Ok(token_vec)
+    }
+
+    pub fn tokenize(&self, text: &str) -> Result<Vec<MyToken>, String> {
+        if let Some(tokens) = self.cache.lock().ok().and_then(|c| c.get(text).cloned()) {
+            return Ok(tokens);
+        }
+
+        let tokens = self.tokenize_uncached(text)?;
+        if let Ok(mut cache) = self.cache.lock() {
+            if cache.len() >= TOKEN_CACHE_CAPACITY {
+                cache.clear();
+            }
+            cache.insert(text.to_string(), tokens.clone());
+        }
+        Ok(tokens)
+    }
+}
+
+pub fn get_tokenizer(app_handle: &tauri::AppHandle) -> tauri::State<TokenizerState> {
+    app_handle.state::<TokenizerState>()
+}
+
+pub async fn tokenize(app_handle: &tauri::AppHandle, text: &str) -> Result<Vec<MyToken>, String> {
+    get_tokenizer(app_handle).tokenize(text).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_tokenizer_dictionary(
+    state: tauri::State<'_, TokenizerState>,
+) -> Result<TokenizerDictionary, String> {
+    Ok(state.dictionary().await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_tokenizer_dictionary(
+    state: tauri::State<'_, TokenizerState>,
+    dictionary: TokenizerDictionary,
+) -> Result<(), String> {
+    state.set_dictionary(dictionary).await
+}
@@ -0,0 +1,262 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use lindera::dictionary::{Dictionary, UserDictionary, load_user_dictionary_from_csv};
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, Wry, async_runtime::RwLock};
+use tauri_plugin_store::Store;
+use tokio::sync::RwLockWriteGuard;
+
+use crate::knowledge_base::entity::*;
+
+pub const USER_DICTIONARY_STORE_FILE: &str = "user_dictionary.json";
+/// Lindera only builds user dictionaries from files, the entries are written
+/// here before every rebuild.
+const USER_DICTIONARY_CSV_FILE: &str = "user_dictionary.csv";
+const DEFAULT_PART_OF_SPEECH: &str = "カスタム名詞";
+
+/// Loads the saved entries and manages their state. Returns the entries so
+/// the tokenizer can be built with them right away.
+pub fn setup(app: &tauri::App) -> Vec<UserDictionaryEntry> {
+    let Ok(store) = crate::profile::store(app, USER_DICTIONARY_STORE_FILE) else {
+        return vec![];
+    };
+    let mut entries = UserDictionaryEntries::new(store);
+    entries.load_entities();
+    let loaded = entries.get_entities();
+    app.manage(UserDictionaryState::new(entries));
+    loaded
+}
+
+pub fn csv_path(app: &tauri::App) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|dir| dir.join(USER_DICTIONARY_CSV_FILE))
+}
+
+/// Writes `entries` in lindera's simple user dictionary format
+/// (`surface,part_of_speech,reading`) and builds a dictionary from it.
+pub fn build_user_dictionary(
+    dictionary: &Dictionary,
+    entries: &[UserDictionaryEntry],
+    path: &Path,
+) -> Result<Option<UserDictionary>, String> {
+    if entries.is_empty() {
+        return Ok(None);
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_path(path)
+        .map_err(|e| e.to_string())?;
+    for entry in entries {
+        writer
+            .write_record([
+                entry.surface.as_str(),
+                entry.part_of_speech.as_str(),
+                // Lindera dictionaries store readings in katakana.
+                &crate::conversion::hiragana_to_katakana(&entry.reading),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+
+    load_user_dictionary_from_csv(&dictionary.metadata, path)
+        .map(Some)
+        .map_err(|e| format!("Could not load user dictionary: {}", e))
+}
+
+pub struct UserDictionaryState(Arc<RwLock<UserDictionaryEntries>>);
+impl EntitiesState for UserDictionaryState {
+    type Entities = UserDictionaryEntries;
+
+    fn new(entries: UserDictionaryEntries) -> Self {
+        Self(Arc::new(RwLock::new(entries)))
+    }
+
+    async fn get_entities(&self) -> Vec<UserDictionaryEntry> {
+        let entries = self.0.read().await;
+        entries.get_entities()
+    }
+
+    async fn add_entity(
+        &self,
+        payload: UserDictionaryEntryCreatePayload,
+    ) -> Result<UserDictionaryEntry, String> {
+        let mut entries = self.0.write().await;
+        entries.add_entity(payload)
+    }
+
+    async fn has_entity(&self, identifier: &String) -> bool {
+        let entries = self.0.read().await;
+        entries.has_entity(identifier)
+    }
+
+    async fn update_entity(
+        &self,
+        entry: UserDictionaryEntry,
+    ) -> Result<UserDictionaryEntry, String> {
+        let mut entries = self.0.write().await;
+        entries.update_entity(entry)
+    }
+
+    async fn remove_entity(&self, identifier: &String) -> Result<UserDictionaryEntry, String> {
+        let mut entries = self.0.write().await;
+        entries.remove_entity(identifier)
+    }
+
+    async fn replace(&self, entries: UserDictionaryEntries) {
+        *self.0.write().await = entries;
+    }
+
+    async fn lock(&self) -> RwLockWriteGuard<'_, UserDictionaryEntries> {
+        self.0.write().await
+    }
+}
+
+impl UserDictionaryState {
+    /// Applies `change` to a copy of the entries and keeps the result only if
+    /// the tokenizer could be rebuilt with it, so an entry lindera rejects is
+    /// never saved.
+    async fn change<T>(
+        &self,
+        tokenizer: &super::TokenizerState,
+        change: impl FnOnce(&mut Vec<UserDictionaryEntry>) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut entries = self.0.write().await;
+        let mut changed = entries.get_entities();
+        let result = change(&mut changed)?;
+        tokenizer.set_user_dictionary(changed.clone()).await?;
+        *entries.borrow_entities_mut() = changed;
+        entries.store_entities()?;
+        Ok(result)
+    }
+}
+
+pub struct UserDictionaryEntries {
+    store: Arc<Store<Wry>>,
+    entries: Vec<UserDictionaryEntry>,
+}
+impl Entities for UserDictionaryEntries {
+    type Entity = UserDictionaryEntry;
+    type EntityCreatePayload = UserDictionaryEntryCreatePayload;
+
+    fn new(store: Arc<Store<Wry>>) -> Self {
+        Self {
+            store,
+            entries: Vec::new(),
+        }
+    }
+    fn get_store(&self) -> &Arc<Store<Wry>> {
+        &self.store
+    }
+    fn borrow_entities(&self) -> &Vec<Self::Entity> {
+        &self.entries
+    }
+    fn borrow_entities_mut(&mut self) -> &mut Vec<Self::Entity> {
+        &mut self.entries
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+pub struct UserDictionaryEntry {
+    pub surface: String,
+    pub part_of_speech: String,
+    pub reading: String,
+}
+impl Entity for UserDictionaryEntry {
+    fn identifier(&self) -> String {
+        self.surface.clone()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+pub struct UserDictionaryEntryCreatePayload {
+    pub surface: String,
+    /// Defaults to `カスタム名詞` (custom noun).
+    pub part_of_speech: Option<String>,
+    pub reading: String,
+}
+impl EntityCreatePayload<UserDictionaryEntry> for UserDictionaryEntryCreatePayload {
+    fn to_entity(&self) -> UserDictionaryEntry {
+        UserDictionaryEntry {
+            surface: self.surface.clone(),
+            part_of_speech: self
+                .part_of_speech
+                .clone()
+                .unwrap_or(DEFAULT_PART_OF_SPEECH.to_string()),
+            reading: self.reading.clone(),
+        }
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_user_dictionary_entries(
+    state: tauri::State<'_, UserDictionaryState>,
+) -> Result<Vec<UserDictionaryEntry>, String> {
+    Ok(state.get_entities().await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn add_user_dictionary_entry(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, UserDictionaryState>,
+    payload: UserDictionaryEntryCreatePayload,
+) -> Result<UserDictionaryEntry, String> {
+    let tokenizer = super::get_tokenizer(&app_handle);
+    state
+        .change(&tokenizer, |entries| {
+            let entry = payload.to_entity();
+            if entries.iter().any(|e| e.surface == entry.surface) {
+                return Err("Entity already exists".to_string());
+            }
+            entries.push(entry.clone());
+            Ok(entry)
+        })
+        .await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_user_dictionary_entry(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, UserDictionaryState>,
+    entry: UserDictionaryEntry,
+) -> Result<UserDictionaryEntry, String> {
+    let tokenizer = super::get_tokenizer(&app_handle);
+    state
+        .change(&tokenizer, |entries| {
+            let existing = entries
+                .iter_mut()
+                .find(|e| e.surface == entry.surface)
+                .ok_or("Entity does not exist".to_string())?;
+            *existing = entry.clone();
+            Ok(entry)
+        })
+        .await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_user_dictionary_entry(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, UserDictionaryState>,
+    surface: String,
+) -> Result<UserDictionaryEntry, String> {
+    let tokenizer = super::get_tokenizer(&app_handle);
+    state
+        .change(&tokenizer, |entries| {
+            let index = entries
+                .iter()
+                .position(|e| e.surface == surface)
+                .ok_or("Entity does not exist".to_string())?;
+            Ok(entries.remove(index))
+        })
+        .await
+}
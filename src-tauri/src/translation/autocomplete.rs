@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+
+use tauri::Manager;
+use tauri::async_runtime::RwLock;
+
+use crate::conversion::katakana_to_hiragana;
+use crate::data::word_frequency::WordFrequencies;
+use crate::knowledge_base::words::WordsState;
+
+use super::{MyEntry, MyEntryDisplay};
+
+pub fn setup(app: &tauri::App) {
+    app.manage(AutocompleteState(RwLock::new(AutocompleteIndex::default())));
+
+    // Building the trie over all of JMdict takes a moment, suggestions are
+    // empty until it is ready.
+    let app_handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let loader_handle = app_handle.clone();
+        let index = tauri::async_runtime::spawn_blocking(move || {
+            let word_frequencies = crate::data::get_word_frequencies(&loader_handle);
+            AutocompleteIndex::new(&word_frequencies)
+        })
+        .await;
+        match index {
+            Ok(index) => {
+                let state = app_handle.state::<AutocompleteState>();
+                *state.0.write().await = index;
+            }
+            Err(e) => eprintln!("Failed to build autocomplete index: {}", e),
+        }
+    });
+}
+
+pub struct AutocompleteState(pub RwLock<AutocompleteIndex>);
+
+#[derive(Default)]
+struct TrieNode {
+    /// Sorted by character.
+    children: Vec<(char, u32)>,
+    /// Entries with a spelling ending at this node.
+    entries: Vec<u32>,
+}
+
+/// Prefix trie over the kanji and (hiragana) reading elements of JMdict.
+/// Entries are numbered from most to least frequent, so the best matches are
+/// simply the lowest ids.
+#[derive(Default)]
+pub struct AutocompleteIndex {
+    entries: Vec<MyEntry>,
+    nodes: Vec<TrieNode>,
+}
+
+impl AutocompleteIndex {
+    pub fn new(word_frequencies: &WordFrequencies) -> Self {
+        let mut entries = jmdict::entries()
+            .map(MyEntry)
+            .map(|entry| (entry.frequency_rank(word_frequencies), entry))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(rank, _)| rank.unwrap_or(u32::MAX));
+
+        let mut index = AutocompleteIndex {
+            entries: Vec::with_capacity(entries.len()),
+            nodes: vec![TrieNode::default()],
+        };
+        for (id, (_, entry)) in entries.into_iter().enumerate() {
+            let keys = entry
+                .0
+                .kanji_elements()
+                .map(|k| k.text)
+                .chain(entry.0.reading_elements().map(|r| r.text))
+                .map(katakana_to_hiragana)
+                .collect::<HashSet<_>>();
+            for key in keys {
+                index.insert(&key, id as u32);
+            }
+            index.entries.push(entry);
+        }
+        index
+    }
+
+    fn insert(&mut self, key: &str, id: u32) {
+        let mut node = 0;
+        for c in key.chars() {
+            node = match self.nodes[node]
+                .children
+                .binary_search_by_key(&c, |(child, _)| *child)
+            {
+                Ok(i) => self.nodes[node].children[i].1 as usize,
+                Err(i) => {
+                    let child = self.nodes.len();
+                    self.nodes.push(TrieNode::default());
+                    self.nodes[node].children.insert(i, (c, child as u32));
+                    child
+                }
+            };
+        }
+        self.nodes[node].entries.push(id);
+    }
+
+    fn find_node(&self, prefix: &str) -> Option<usize> {
+        let mut node = 0;
+        for c in prefix.chars() {
+            let children = &self.nodes[node].children;
+            let i = children
+                .binary_search_by_key(&c, |(child, _)| *child)
+                .ok()?;
+            node = children[i].1 as usize;
+        }
+        Some(node)
+    }
+
+    /// Ids of all entries with a spelling starting with `prefix`, most frequent
+    /// first.
+    fn search(&self, prefix: &str) -> Vec<u32> {
+        let Some(start) = self.find_node(prefix) else {
+            return vec![];
+        };
+        let mut ids = Vec::new();
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            ids.extend(&self.nodes[node].entries);
+            stack.extend(self.nodes[node].children.iter().map(|(_, c)| *c as usize));
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+/// Hiragana (or kanji) prefix for the trie. Romaji is converted first, an
+/// unfinished syllable at the end (`tab`) is left out of the search.
+fn normalize_prefix(prefix: &str) -> String {
+    let prefix = prefix.trim().to_lowercase();
+    let kana = if prefix.chars().any(|c| c.is_ascii_alphabetic()) {
+        wana_kana::ConvertJapanese::to_hiragana(prefix.as_str())
+    } else {
+        prefix
+    };
+    katakana_to_hiragana(kana.trim_end_matches(|c: char| c.is_ascii_alphabetic()))
+}
+
+#[derive(Debug, Clone, serde::Serialize, specta::Type)]
+pub struct AutocompleteSuggestion {
+    pub entry: MyEntryDisplay,
+    pub saved: bool,
+}
+
+/// Dictionary words starting with `prefix`. Saved words come first, then the
+/// most frequent ones.
+#[tauri::command]
+#[specta::specta]
+pub async fn autocomplete(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AutocompleteState>,
+    words_state: tauri::State<'_, WordsState>,
+    prefix: String,
+    limit: u32,
+) -> Result<Vec<AutocompleteSuggestion>, String> {
+    let prefix = normalize_prefix(&prefix);
+    if prefix.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let saved_words = crate::reader::known_words(&words_state).await;
+    let index = state.0.read().await;
+    let mut matches = index
+        .search(&prefix)
+        .into_iter()
+        .map(|id| {
+            let entry = &index.entries[id as usize];
+            let saved = entry
+                .word_string()
+                .is_some_and(|word| saved_words.contains(&word));
+            (saved, entry)
+        })
+        .collect::<Vec<_>>();
+    // Stable, so the frequency order from the search is kept within groups.
+    matches.sort_by_key(|(saved, _)| !*saved);
+    matches.truncate(limit as usize);
+
+    let pitch_accents = crate::data::get_pitch_accents(&app_handle);
+    let word_frequencies = crate::data::get_word_frequencies(&app_handle);
+    Ok(matches
+        .into_iter()
+        .map(|(saved, entry)| AutocompleteSuggestion {
+            entry: entry.entry_display_with(&pitch_accents, &word_frequencies),
+            saved,
+        })
+        .collect())
+}
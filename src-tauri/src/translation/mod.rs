@@ -1,9 +1,22 @@
 use jmdict;
 use tauri::Manager;
 
+use crate::data::pitch_accent::{PitchAccent, PitchAccents};
+use crate::data::word_frequency::WordFrequencies;
+
+pub mod autocomplete;
+
+/// Every JMdict `nfXX` bucket holds 500 words.
+const PRIORITY_BUCKET_SIZE: u32 = 500;
+/// Approximate ranks for entries tagged only with `news1`/`ichi1`/`spec1`/`gai1`
+/// respectively their `*2` counterparts, placed after the `nf` buckets.
+const PRIMARY_PRIORITY_RANK: u32 = 24_000;
+const SECONDARY_PRIORITY_RANK: u32 = 36_000;
+
 pub fn setup(app: &tauri::App) {
     let dictionary = MyDictionary::new();
     app.manage(dictionary);
+    autocomplete::setup(app);
 }
 
 pub struct MyDictionary {
@@ -38,6 +51,11 @@ impl MyDictionary {
         MyDictionary { kanji_to_entry }
     }
 
+    /// Lookup by headword only, without the full dictionary scans of `find`.
+    pub fn get_exact(&self, word: &str) -> Option<MyEntry> {
+        self.kanji_to_entry.get(word).cloned()
+    }
+
     fn find(&self, word: &String) -> Option<MyEntry> {
         println!("Finding word: {}", word);
         let entry = self.kanji_to_entry.get(word).cloned();
@@ -158,11 +176,87 @@ impl MyEntry {
             .join("; ")
     }
 
+    /// Approximate frequency rank derived from the JMdict priority tags of the
+    /// entry's kanji and reading elements, lower is more frequent.
+    pub fn priority_rank(&self) -> Option<u32> {
+        let priorities = self
+            .0
+            .kanji_elements()
+            .map(|k| k.priority)
+            .chain(self.0.reading_elements().map(|r| r.priority));
+
+        let mut best: Option<u32> = None;
+        for priority in priorities {
+            let corpora = [
+                priority.news,
+                priority.ichimango,
+                priority.loanwords,
+                priority.additional,
+            ];
+            let rank = if priority.frequency_bucket > 0 {
+                Some((priority.frequency_bucket as u32 - 1) * PRIORITY_BUCKET_SIZE)
+            } else if corpora
+                .iter()
+                .any(|c| matches!(c, jmdict::PriorityInCorpus::Primary))
+            {
+                Some(PRIMARY_PRIORITY_RANK)
+            } else if corpora
+                .iter()
+                .any(|c| matches!(c, jmdict::PriorityInCorpus::Secondary))
+            {
+                Some(SECONDARY_PRIORITY_RANK)
+            } else {
+                None
+            };
+            best = match (best, rank) {
+                (Some(best), Some(rank)) => Some(best.min(rank)),
+                (best, rank) => best.or(rank),
+            };
+        }
+        best
+    }
+
+    /// Rank from the corpus frequency list when any of the entry's spellings is
+    /// in it, falling back to the JMdict priority estimate.
+    pub fn frequency_rank(&self, word_frequencies: &WordFrequencies) -> Option<u32> {
+        self.0
+            .kanji_elements()
+            .map(|k| k.text)
+            .chain(self.0.reading_elements().map(|r| r.text))
+            .filter_map(|text| word_frequencies.corpus_rank(text))
+            .min()
+            .or_else(|| self.priority_rank())
+    }
+
+    pub fn pitch_accents(&self, pitch_accents: &PitchAccents) -> Vec<PitchAccent> {
+        let Some(word) = self.word_string() else {
+            return vec![];
+        };
+        self.0
+            .reading_elements()
+            .flat_map(|r| pitch_accents.find(&word, r.text))
+            .collect()
+    }
+
     pub fn entry_display(&self) -> MyEntryDisplay {
         MyEntryDisplay {
             word: self.word_string().unwrap_or("No word found".to_string()),
             reading: self.reading_elements_string(),
             translations: self.translations_string(),
+            pitch_accents: vec![],
+            frequency_rank: self.priority_rank(),
+        }
+    }
+
+    pub fn entry_display_with(
+        &self,
+        pitch_accents: &PitchAccents,
+        word_frequencies: &WordFrequencies,
+    ) -> MyEntryDisplay {
+        MyEntryDisplay {
+            pitch_accents: self.pitch_accents(pitch_accents),
+            frequency_rank: self.frequency_rank(word_frequencies),
+            ..self.entry_display()
         }
     }
 }
@@ -172,6 +266,8 @@ pub struct MyEntryDisplay {
     word: String,
     reading: String,
     translations: String,
+    pitch_accents: Vec<PitchAccent>,
+    frequency_rank: Option<u32>,
 }
 
 pub async fn translate_word(word: &String, app_handle: &tauri::AppHandle) -> Option<MyEntry> {
@@ -1,11 +1,10 @@
-use lindera::dictionary::{DictionaryKind, load_embedded_dictionary};
-use lindera::mode::Mode;
-use lindera::segmenter::Segmenter;
-use lindera::token::Token;
-use lindera::tokenizer::Tokenizer;
-
+use crate::data::sentences::ExampleSentence;
 use crate::translation::{MyEntry, MyEntryDisplay};
 
+pub mod token;
+
+pub use token::MyToken;
+
 pub struct Word {
     word: String,
     tauri_app_handle: tauri::AppHandle,
@@ -23,22 +22,8 @@ impl Word {
         &self.word
     }
 
-    fn katakana_to_hiragana(input: &str) -> String {
-        input
-            .chars()
-            .map(|c| {
-                if ('ァ'..='ン').contains(&c) {
-                    // Shift Unicode value from Katakana to Hiragana block
-                    std::char::from_u32(c as u32 - 0x60).unwrap()
-                } else {
-                    c
-                }
-            })
-            .collect()
-    }
-
-    fn get_lemma(&self) -> Result<String, String> {
-        let tokens = self.tokenize()?;
+    pub async fn get_lemma(&self) -> Result<String, String> {
+        let tokens = self.tokenize().await?;
         if let Some(token) = tokens.first() {
             if let Some(lemma) = token.lemma() {
                 return Ok(lemma.to_string());
@@ -47,39 +32,22 @@ impl Word {
         Err("No lemma found".to_string())
     }
 
-    fn tokenize(&self) -> Result<Vec<MyToken>, String> {
-        let text = &self.word;
-        let dictionary = load_embedded_dictionary(DictionaryKind::IPADIC)
-            .map_err(|_| format!("Could not load dictionary"))?;
-        let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
-        let tokenizer = Tokenizer::new(segmenter);
-
-        let mut tokens = tokenizer
-            .tokenize(text)
-            .map_err(|_| format!("Could not tokenize work {}", self.word))?;
-
-        let mut token_vec = Vec::new();
-        for token in tokens.iter_mut() {
-            token_vec.push(MyToken::from_lindera_token(token));
-        }
-
-        Ok(token_vec)
+    async fn tokenize(&self) -> Result<Vec<MyToken>, String> {
+        crate::tokenizer::tokenize(&self.tauri_app_handle, &self.word).await
     }
 
-    pub fn to_hiragana(&self) -> Result<String, String> {
-        let tokens = self.tokenize()?;
-
-        let mut hiragana_output = String::new();
-        for token in tokens.iter() {
-            hiragana_output.push_str(token.reading().unwrap_or(token.text()));
-        }
+    pub async fn to_hiragana(&self) -> Result<String, String> {
+        let tokens = self.tokenize().await?;
 
-        Ok(hiragana_output)
+        Ok(crate::conversion::convert_tokens(
+            &tokens,
+            &crate::conversion::ConversionOptions::default(),
+        ))
     }
 
     pub async fn find_in_dictionary(&self) -> Result<Option<MyEntry>, String> {
         println!("Searching for word in dictionary: {}", self.word);
-        let lemma = self.get_lemma()?;
+        let lemma = self.get_lemma().await?;
         println!("Searching for lemma: {}", lemma);
         let lemma_dict_entry =
             crate::translation::translate_word(&lemma, &self.tauri_app_handle).await;
@@ -100,6 +68,7 @@ impl Word {
         }
     }
 
+    /// Keeps the first entry per word, so callers should sort by preference first.
     fn unique_entries(entries: Vec<MyEntry>) -> Vec<MyEntry> {
         let mut seen = std::collections::HashSet::new();
         entries
@@ -114,7 +83,7 @@ impl Word {
     ) -> Result<Vec<MyEntry>, String> {
         let mut candidates = Vec::new();
 
-        let lemma = self.get_lemma();
+        let lemma = self.get_lemma().await;
         if let Ok(lemma) = lemma {
             let lemma_dict_entries =
                 crate::translation::translate_word_all(&lemma, app_handle).await;
@@ -125,37 +94,11 @@ impl Word {
             crate::translation::translate_word_all(&self.word, app_handle).await;
         candidates.extend(word_dict_entries);
 
-        Ok(Self::unique_entries(candidates))
-    }
-}
+        // Stable sort, so entries without any frequency data keep scan order.
+        let word_frequencies = crate::data::get_word_frequencies(app_handle);
+        candidates.sort_by_key(|entry| entry.frequency_rank(&word_frequencies).unwrap_or(u32::MAX));
 
-pub struct MyToken {
-    details: Vec<String>,
-}
-impl MyToken {
-    pub fn from_lindera_token(token: &mut Token) -> Self {
-        MyToken {
-            details: token.details().iter().map(|s| s.to_string()).collect(),
-        }
-    }
-
-    pub fn text(&self) -> &str {
-        &self.details[0] // Assuming the first detail is the surface form
-    }
-
-    pub fn reading(&self) -> Option<&str> {
-        if self.details.len() > 7 {
-            Some(&self.details[7]) // Assuming the reading is at index 7
-        } else {
-            None
-        }
-    }
-    pub fn lemma(&self) -> Option<&str> {
-        if self.details.len() > 6 {
-            Some(&self.details[6]) // Assuming the lemma is at index 6
-        } else {
-            None
-        }
+        Ok(Self::unique_entries(candidates))
     }
 }
 
@@ -166,8 +109,12 @@ pub async fn get_word_dict_entry(
     app_handle: tauri::AppHandle,
 ) -> Result<Option<MyEntryDisplay>, String> {
     let word_instance = Word::new(word, &app_handle);
+    let pitch_accents = crate::data::get_pitch_accents(&app_handle);
+    let word_frequencies = crate::data::get_word_frequencies(&app_handle);
     match word_instance.find_in_dictionary().await {
-        Ok(Some(entry)) => Ok(Some(entry.entry_display())),
+        Ok(Some(entry)) => Ok(Some(
+            entry.entry_display_with(&pitch_accents, &word_frequencies),
+        )),
         Ok(None) => Ok(None),
         Err(e) => Err(e),
     }
@@ -180,11 +127,81 @@ pub async fn get_word_candidates(
     app_handle: tauri::AppHandle,
 ) -> Result<Vec<MyEntryDisplay>, String> {
     let word_instance = Word::new(word, &app_handle);
+    let pitch_accents = crate::data::get_pitch_accents(&app_handle);
+    let word_frequencies = crate::data::get_word_frequencies(&app_handle);
     match word_instance.find_all_candidates(&app_handle).await {
         Ok(entries) => Ok(entries
             .into_iter()
-            .map(|entry| entry.entry_display())
+            .map(|entry| entry.entry_display_with(&pitch_accents, &word_frequencies))
             .collect()),
         Err(e) => Err(e),
     }
 }
+
+/// The most frequent dictionary words that are not saved yet.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_study_suggestions(
+    limit: u32,
+    app_handle: tauri::AppHandle,
+    words_state: tauri::State<'_, crate::knowledge_base::words::WordsState>,
+) -> Result<Vec<MyEntryDisplay>, String> {
+    let known_words = words_state
+        .get_words()
+        .await
+        .into_iter()
+        .map(|w| w.word)
+        .collect::<std::collections::HashSet<_>>();
+    let pitch_accents = crate::data::get_pitch_accents(&app_handle);
+    let word_frequencies = crate::data::get_word_frequencies(&app_handle);
+
+    let mut suggestions = crate::translation::get_all_entries(&app_handle)
+        .await
+        .into_iter()
+        .filter(|entry| {
+            entry
+                .word_string()
+                .is_some_and(|word| !known_words.contains(&word))
+        })
+        .filter_map(|entry| {
+            entry
+                .frequency_rank(&word_frequencies)
+                .map(|rank| (rank, entry))
+        })
+        .collect::<Vec<_>>();
+    suggestions.sort_by_key(|(rank, _)| *rank);
+
+    Ok(suggestions
+        .into_iter()
+        .take(limit as usize)
+        .map(|(_, entry)| entry.entry_display_with(&pitch_accents, &word_frequencies))
+        .collect())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_example_sentences(
+    word: String,
+    limit: u32,
+    app_handle: tauri::AppHandle,
+    words_state: tauri::State<'_, crate::knowledge_base::words::WordsState>,
+) -> Result<Vec<ExampleSentence>, String> {
+    let word_instance = Word::new(word.clone(), &app_handle);
+    let mut lemmas = vec![word];
+    if let Ok(lemma) = word_instance.get_lemma().await {
+        if !lemmas.contains(&lemma) {
+            lemmas.push(lemma);
+        }
+    }
+
+    let known_words = words_state
+        .get_words()
+        .await
+        .into_iter()
+        .map(|w| w.word)
+        .collect::<std::collections::HashSet<_>>();
+
+    let corpus_state = crate::data::get_sentence_corpus(&app_handle);
+    let corpus = corpus_state.0.read().await;
+    Ok(corpus.find(&lemmas, &known_words, limit as usize))
+}
@@ -0,0 +1,228 @@
+use lindera::token::Token;
+
+/// Placeholder lindera dictionaries use for fields that do not apply.
+const EMPTY_FIELD: &str = "*";
+/// Details older lindera versions report for words missing from the dictionary.
+const UNKNOWN_DETAILS: &str = "UNK";
+
+/// Where each piece of information sits in a dictionary's token details.
+#[derive(Debug, Clone, Copy)]
+pub struct DetailsSchema {
+    /// The main category, the subcategories follow it.
+    pub part_of_speech: usize,
+    pub lemma: usize,
+    pub reading: usize,
+}
+
+/// 品詞,品詞細分類1,品詞細分類2,品詞細分類3,活用型,活用形,原形,読み,発音
+pub const IPADIC_SCHEMA: DetailsSchema = DetailsSchema {
+    part_of_speech: 0,
+    lemma: 6,
+    reading: 7,
+};
+
+/// 品詞大分類,中分類,小分類,細分類,活用型,活用形,語彙素読み,語彙素,書字形出現形,
+/// 発音形出現形,書字形基本形,... UniDic has no kana spelling of the surface form,
+/// so the pronunciation is used as the reading, and the written base form is
+/// used as the lemma since 語彙素 normalizes e.g. する to 為る.
+pub const UNIDIC_SCHEMA: DetailsSchema = DetailsSchema {
+    part_of_speech: 0,
+    lemma: 10,
+    reading: 9,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartOfSpeechCategory {
+    /// 名詞
+    Noun,
+    /// 動詞
+    Verb,
+    /// 形容詞
+    Adjective,
+    /// 形状詞, UniDic only. IPADIC tags these as 名詞,形容動詞語幹.
+    AdjectivalNoun,
+    /// 副詞
+    Adverb,
+    /// 連体詞
+    Adnominal,
+    /// 接続詞
+    Conjunction,
+    /// 感動詞
+    Interjection,
+    /// 助詞
+    Particle,
+    /// 助動詞
+    AuxiliaryVerb,
+    /// 接頭詞 (IPADIC), 接頭辞 (UniDic)
+    Prefix,
+    /// 接尾辞, UniDic only. IPADIC tags these as 名詞,接尾.
+    Suffix,
+    /// 代名詞, UniDic only. IPADIC tags these as 名詞,代名詞.
+    Pronoun,
+    /// 記号
+    Symbol,
+    /// 補助記号, UniDic's punctuation.
+    AuxiliarySymbol,
+    /// フィラー
+    Filler,
+    /// 空白
+    Whitespace,
+    Other(String),
+}
+
+impl PartOfSpeechCategory {
+    pub fn from_tag(tag: &str) -> Self {
+        match tag {
+            "名詞" => PartOfSpeechCategory::Noun,
+            "動詞" => PartOfSpeechCategory::Verb,
+            "形容詞" => PartOfSpeechCategory::Adjective,
+            "形状詞" => PartOfSpeechCategory::AdjectivalNoun,
+            "副詞" => PartOfSpeechCategory::Adverb,
+            "連体詞" => PartOfSpeechCategory::Adnominal,
+            "接続詞" => PartOfSpeechCategory::Conjunction,
+            "感動詞" => PartOfSpeechCategory::Interjection,
+            "助詞" => PartOfSpeechCategory::Particle,
+            "助動詞" => PartOfSpeechCategory::AuxiliaryVerb,
+            "接頭詞" | "接頭辞" => PartOfSpeechCategory::Prefix,
+            "接尾辞" => PartOfSpeechCategory::Suffix,
+            "代名詞" => PartOfSpeechCategory::Pronoun,
+            "記号" => PartOfSpeechCategory::Symbol,
+            "補助記号" => PartOfSpeechCategory::AuxiliarySymbol,
+            "フィラー" => PartOfSpeechCategory::Filler,
+            "空白" => PartOfSpeechCategory::Whitespace,
+            other => PartOfSpeechCategory::Other(other.to_string()),
+        }
+    }
+
+    /// Punctuation and other symbols, in either dictionary.
+    pub fn is_symbol(&self) -> bool {
+        matches!(
+            self,
+            PartOfSpeechCategory::Symbol
+                | PartOfSpeechCategory::AuxiliarySymbol
+                | PartOfSpeechCategory::Whitespace
+        )
+    }
+
+    /// Particles, auxiliaries and symbols: grammar rather than vocabulary.
+    pub fn is_grammatical(&self) -> bool {
+        self.is_symbol()
+            || matches!(
+                self,
+                PartOfSpeechCategory::Particle | PartOfSpeechCategory::AuxiliaryVerb
+            )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MyToken {
+    surface: String,
+    byte_start: usize,
+    byte_end: usize,
+    /// The word is not in the dictionary. Its part of speech is guessed by
+    /// lindera from the character class, everything else is missing.
+    unknown: bool,
+    category: Option<PartOfSpeechCategory>,
+    lemma: Option<String>,
+    reading: Option<String>,
+}
+
+fn field(details: &[&str], index: usize) -> Option<String> {
+    details
+        .get(index)
+        .filter(|value| !value.is_empty() && **value != EMPTY_FIELD)
+        .map(|value| value.to_string())
+}
+
+impl MyToken {
+    pub fn from_lindera_token(token: &mut Token, schema: &DetailsSchema) -> Self {
+        let surface = token.text.to_string();
+        let byte_start = token.byte_start;
+        let byte_end = token.byte_end;
+        let is_unknown_word = token.word_id.is_unknown();
+        let details = token.details();
+
+        if is_unknown_word || details.as_slice() == [UNKNOWN_DETAILS] {
+            return MyToken {
+                surface,
+                byte_start,
+                byte_end,
+                unknown: true,
+                category: Self::category_from(&details, schema),
+                lemma: None,
+                reading: None,
+            };
+        }
+
+        MyToken {
+            surface,
+            byte_start,
+            byte_end,
+            unknown: false,
+            category: Self::category_from(&details, schema),
+            lemma: field(&details, schema.lemma),
+            reading: field(&details, schema.reading),
+        }
+    }
+
+    fn category_from(details: &[&str], schema: &DetailsSchema) -> Option<PartOfSpeechCategory> {
+        field(details, schema.part_of_speech)
+            .filter(|tag| tag != UNKNOWN_DETAILS)
+            .map(|tag| PartOfSpeechCategory::from_tag(&tag))
+    }
+
+    /// A dictionary token without a lindera token behind it.
+    #[cfg(test)]
+    pub fn new(
+        surface: &str,
+        category: Option<PartOfSpeechCategory>,
+        reading: Option<&str>,
+    ) -> Self {
+        MyToken {
+            surface: surface.to_string(),
+            byte_start: 0,
+            byte_end: surface.len(),
+            unknown: false,
+            category,
+            lemma: Some(surface.to_string()),
+            reading: reading.map(str::to_string),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.surface
+    }
+
+    /// Byte range of the token in the tokenized text.
+    pub fn byte_range(&self) -> std::ops::Range<usize> {
+        self.byte_start..self.byte_end
+    }
+
+    /// Not in the dictionary, e.g. a name or a typo.
+    pub fn is_unknown(&self) -> bool {
+        self.unknown
+    }
+
+    pub fn category(&self) -> Option<&PartOfSpeechCategory> {
+        self.category.as_ref()
+    }
+
+    pub fn is_symbol(&self) -> bool {
+        self.category().is_some_and(|c| c.is_symbol())
+    }
+
+    /// The dictionary form used to index and look up tokens, falling back to
+    /// the surface form for unknown words.
+    pub fn lemma_or_text(&self) -> &str {
+        self.lemma().unwrap_or(self.text())
+    }
+
+    pub fn lemma(&self) -> Option<&str> {
+        self.lemma.as_deref()
+    }
+
+    /// Katakana reading of the surface form.
+    pub fn reading(&self) -> Option<&str> {
+        self.reading.as_deref()
+    }
+}
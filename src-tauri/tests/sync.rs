@@ -0,0 +1,422 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use japanese_learner_lib::sync::change_set::{ConflictStrategy, RecordKind, Replica};
+use japanese_learner_lib::sync::server::LocalSyncServer;
+use japanese_learner_lib::sync::{SyncReport, sync_replica, sync_stores};
+use serde_json::{Map, Value, json};
+use tauri::test::{MockRuntime, mock_builder, mock_context, noop_assets};
+use tauri_plugin_store::{Store, StoreExt};
+
+const TOKEN: &str = "secret";
+
+/// A device's word store together with its sync replica, the way the app
+/// keeps them.
+struct Device {
+    replica: Replica,
+    words: Vec<Map<String, Value>>,
+}
+
+impl Device {
+    fn new(name: &str) -> Self {
+        Device {
+            replica: Replica::new(name),
+            words: vec![],
+        }
+    }
+
+    fn add_word(&mut self, id: &str, word: &str, meaning: &str) {
+        let word = json!({ "id": id, "word": word, "meaning": meaning, "tags": [] });
+        self.words.push(word.as_object().unwrap().clone());
+    }
+
+    fn word_mut(&mut self, word: &str) -> &mut Map<String, Value> {
+        self.words.iter_mut().find(|w| w["word"] == word).unwrap()
+    }
+
+    fn field(&self, word: &str, field: &str) -> Value {
+        self.words.iter().find(|w| w["word"] == word).unwrap()[field].clone()
+    }
+
+    fn sync(
+        &mut self,
+        server: &LocalSyncServer,
+        strategy: ConflictStrategy,
+        now: u32,
+    ) -> SyncReport {
+        self.replica
+            .record_entities(RecordKind::Word, self.words.clone(), now);
+        let report = sync_replica(&mut self.replica, &server.url(), TOKEN, strategy, now).unwrap();
+        self.words = self.replica.entities(RecordKind::Word);
+        report
+    }
+}
+
+fn start_server() -> LocalSyncServer {
+    LocalSyncServer::start("127.0.0.1:0", TOKEN).unwrap()
+}
+
+#[test]
+fn added_words_reach_the_other_device() {
+    let server = start_server();
+    let mut desktop = Device::new("desktop");
+    let mut laptop = Device::new("laptop");
+
+    desktop.add_word("1", "学校", "school");
+    let report = desktop.sync(&server, ConflictStrategy::FieldMerge, 100);
+    assert_eq!(report.pushed, 1);
+
+    let report = laptop.sync(&server, ConflictStrategy::FieldMerge, 110);
+    assert_eq!(report.pulled, 1);
+    assert_eq!(laptop.field("学校", "meaning"), "school");
+}
+
+#[test]
+fn deletions_reach_the_other_device() {
+    let server = start_server();
+    let mut desktop = Device::new("desktop");
+    let mut laptop = Device::new("laptop");
+    desktop.add_word("1", "学校", "school");
+    desktop.add_word("2", "先生", "teacher");
+    desktop.sync(&server, ConflictStrategy::FieldMerge, 100);
+    laptop.sync(&server, ConflictStrategy::FieldMerge, 110);
+
+    laptop.words.retain(|w| w["word"] != "先生");
+    laptop.sync(&server, ConflictStrategy::FieldMerge, 120);
+    desktop.sync(&server, ConflictStrategy::FieldMerge, 130);
+
+    assert_eq!(desktop.words.len(), 1);
+    assert_eq!(desktop.field("学校", "meaning"), "school");
+}
+
+#[test]
+fn field_merge_keeps_edits_to_different_fields() {
+    let server = start_server();
+    let mut desktop = Device::new("desktop");
+    let mut laptop = Device::new("laptop");
+    desktop.add_word("1", "学校", "school");
+    desktop.sync(&server, ConflictStrategy::FieldMerge, 100);
+    laptop.sync(&server, ConflictStrategy::FieldMerge, 110);
+
+    desktop.word_mut("学校")["meaning"] = json!("school, academy");
+    laptop.word_mut("学校")["tags"] = json!(["jlpt::n5"]);
+    desktop.sync(&server, ConflictStrategy::FieldMerge, 200);
+    let report = laptop.sync(&server, ConflictStrategy::FieldMerge, 210);
+    desktop.sync(&server, ConflictStrategy::FieldMerge, 220);
+
+    assert_eq!(report.conflicts, 1);
+    for device in [&desktop, &laptop] {
+        assert_eq!(device.field("学校", "meaning"), "school, academy");
+        assert_eq!(device.field("学校", "tags"), json!(["jlpt::n5"]));
+    }
+}
+
+#[test]
+fn last_writer_wins_takes_the_later_entity() {
+    let server = start_server();
+    let mut desktop = Device::new("desktop");
+    let mut laptop = Device::new("laptop");
+    desktop.add_word("1", "学校", "school");
+    desktop.sync(&server, ConflictStrategy::LastWriterWins, 100);
+    laptop.sync(&server, ConflictStrategy::LastWriterWins, 110);
+
+    // The laptop's edit is later but reaches the server first.
+    desktop.word_mut("学校")["meaning"] = json!("academy");
+    desktop.word_mut("学校")["tags"] = json!(["desktop"]);
+    desktop
+        .replica
+        .record_entities(RecordKind::Word, desktop.words.clone(), 200);
+    laptop.word_mut("学校")["meaning"] = json!("schoolhouse");
+    laptop.sync(&server, ConflictStrategy::LastWriterWins, 300);
+    desktop.sync(&server, ConflictStrategy::LastWriterWins, 310);
+    laptop.sync(&server, ConflictStrategy::LastWriterWins, 320);
+
+    for device in [&desktop, &laptop] {
+        assert_eq!(device.field("学校", "meaning"), "schoolhouse");
+        assert_eq!(device.field("学校", "tags"), json!([]));
+    }
+}
+
+#[test]
+fn words_added_on_both_devices_are_kept_once() {
+    let server = start_server();
+    let mut desktop = Device::new("desktop");
+    let mut laptop = Device::new("laptop");
+    desktop.add_word("a", "学校", "school");
+    laptop.add_word("b", "学校", "school");
+
+    desktop.sync(&server, ConflictStrategy::FieldMerge, 100);
+    let report = laptop.sync(&server, ConflictStrategy::FieldMerge, 110);
+    desktop.sync(&server, ConflictStrategy::FieldMerge, 120);
+
+    assert_eq!(report.duplicates, 1);
+    for device in [&desktop, &laptop] {
+        assert_eq!(device.words.len(), 1);
+        assert_eq!(device.field("学校", "id"), "a");
+    }
+}
+
+#[test]
+fn duplicates_leave_their_tags_and_added_event_behind() {
+    let server = start_server();
+    let mut desktop = Replica::new("desktop");
+    let mut laptop = Replica::new("laptop");
+    let word = |id: &str, tags: Value| {
+        json!({ "id": id, "word": "学校", "tags": tags })
+            .as_object()
+            .unwrap()
+            .clone()
+    };
+    let event = |id: &str, timestamp: u32, kind: Value| {
+        json!({ "id": id, "timestamp": timestamp, "card_kind": "Word", "card": "学校", "kind": kind })
+            .as_object()
+            .unwrap()
+            .clone()
+    };
+    desktop.record_entities(RecordKind::Word, vec![word("a", json!(["jlpt::n5"]))], 100);
+    desktop.record_entities(
+        RecordKind::HistoryEvent,
+        vec![
+            event("h1", 100, json!("Added")),
+            event("h2", 150, json!({ "Reviewed": { "grade": "Good" } })),
+        ],
+        150,
+    );
+    laptop.record_entities(RecordKind::Word, vec![word("b", json!(["genki::5"]))], 110);
+    laptop.record_entities(
+        RecordKind::HistoryEvent,
+        vec![event("h3", 110, json!("Added"))],
+        110,
+    );
+
+    let url = server.url();
+    let strategy = ConflictStrategy::FieldMerge;
+    sync_replica(&mut desktop, &url, TOKEN, strategy, 200).unwrap();
+    sync_replica(&mut laptop, &url, TOKEN, strategy, 210).unwrap();
+    sync_replica(&mut desktop, &url, TOKEN, strategy, 220).unwrap();
+
+    for replica in [&desktop, &laptop] {
+        let words = replica.entities(RecordKind::Word);
+        assert_eq!(words.len(), 1);
+        assert_eq!(words[0]["id"], "a");
+        assert_eq!(words[0]["tags"], json!(["genki::5", "jlpt::n5"]));
+        let events = replica
+            .entities(RecordKind::HistoryEvent)
+            .iter()
+            .map(|event| event["id"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(events, ["h1", "h2"]);
+    }
+}
+
+#[test]
+fn requests_without_the_token_are_refused() {
+    let server = start_server();
+    let mut desktop = Device::new("desktop");
+    desktop.add_word("1", "学校", "school");
+    desktop
+        .replica
+        .record_entities(RecordKind::Word, desktop.words.clone(), 100);
+
+    let result = sync_replica(
+        &mut desktop.replica,
+        &server.url(),
+        "guess",
+        ConflictStrategy::FieldMerge,
+        100,
+    );
+
+    assert!(result.unwrap_err().contains("401"));
+    assert!(LocalSyncServer::start("127.0.0.1:0", "").is_err());
+}
+
+#[test]
+fn devices_push_everything_again_to_a_restarted_server() {
+    let mut desktop = Device::new("desktop");
+    let mut laptop = Device::new("laptop");
+    desktop.add_word("1", "学校", "school");
+    let server = start_server();
+    desktop.sync(&server, ConflictStrategy::FieldMerge, 100);
+    drop(server);
+
+    let server = start_server();
+    desktop.sync(&server, ConflictStrategy::FieldMerge, 200);
+    laptop.sync(&server, ConflictStrategy::FieldMerge, 210);
+
+    assert_eq!(laptop.field("学校", "meaning"), "school");
+}
+
+/// A profile's stores, in a directory of its own, the way the app keeps them.
+struct StoredDevice {
+    app: tauri::App<MockRuntime>,
+    dir: PathBuf,
+}
+
+impl StoredDevice {
+    fn new() -> Self {
+        let app = mock_builder()
+            .plugin(tauri_plugin_store::Builder::new().build())
+            .build(mock_context(noop_assets()))
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("sync-test-{}", uuid::Uuid::new_v4()));
+        StoredDevice { app, dir }
+    }
+
+    fn store(&self, file: &str) -> Arc<Store<MockRuntime>> {
+        self.app.store(self.dir.join(file)).unwrap()
+    }
+
+    fn set(&self, file: &str, key: &str, entities: Value) {
+        self.store(file).set(key, entities);
+    }
+
+    fn get(&self, file: &str, key: &str) -> Value {
+        self.store(file).get(key).unwrap_or(Value::Null)
+    }
+
+    fn ids(&self, file: &str, key: &str) -> Vec<String> {
+        self.get(file, key)
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entity| entity["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    fn sync(&self, server: &LocalSyncServer, now: u32) -> SyncReport {
+        let store = |file: &str| {
+            self.app
+                .store(self.dir.join(file))
+                .map_err(|e| e.to_string())
+        };
+        sync_stores(
+            store,
+            &server.url(),
+            TOKEN,
+            ConflictStrategy::FieldMerge,
+            now,
+        )
+        .unwrap()
+    }
+}
+
+impl Drop for StoredDevice {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[test]
+fn every_synced_store_reaches_the_other_profile() {
+    let server = start_server();
+    let desktop = StoredDevice::new();
+    let laptop = StoredDevice::new();
+    let stores = [
+        (
+            "words.json",
+            "words",
+            json!({ "id": "w", "word": "学校", "created_at": 1 }),
+        ),
+        (
+            "kanjis.json",
+            "entities",
+            json!({ "id": "k", "kanji": "学", "created_at": 1 }),
+        ),
+        (
+            "collections.json",
+            "entities",
+            json!({ "id": "c", "name": "N5", "created_at": 1 }),
+        ),
+        (
+            "mnemonics.json",
+            "entities",
+            json!({ "id": "m", "text": "...", "created_at": 1 }),
+        ),
+        (
+            "reading_sounds.json",
+            "entities",
+            json!({ "id": "s", "sound": "ガク", "created_at": 1 }),
+        ),
+        (
+            "history.json",
+            "events",
+            json!({ "id": "h", "card": "学校", "timestamp": 1 }),
+        ),
+    ];
+    for (file, key, entity) in &stores {
+        desktop.set(file, key, json!([entity]));
+    }
+
+    let report = desktop.sync(&server, 100);
+    assert_eq!(report.pushed, stores.len() as u32);
+    let report = laptop.sync(&server, 110);
+    assert_eq!(report.pulled, stores.len() as u32);
+
+    for (file, key, entity) in &stores {
+        assert_eq!(laptop.get(file, key), json!([entity]), "{}", file);
+    }
+}
+
+#[test]
+fn written_back_entities_keep_creation_order() {
+    let server = start_server();
+    let desktop = StoredDevice::new();
+    let laptop = StoredDevice::new();
+    // The replica orders them by id, the opposite way.
+    desktop.set(
+        "words.json",
+        "words",
+        json!([
+            { "id": "b", "word": "先生", "created_at": 100 },
+            { "id": "a", "word": "学校", "created_at": 200 },
+        ]),
+    );
+    desktop.set(
+        "history.json",
+        "events",
+        json!([
+            { "id": "y", "card": "先生", "timestamp": 100 },
+            { "id": "x", "card": "学校", "timestamp": 200 },
+        ]),
+    );
+
+    desktop.sync(&server, 300);
+    laptop.set(
+        "words.json",
+        "words",
+        json!([{ "id": "c", "word": "大学", "created_at": 150 }]),
+    );
+    laptop.sync(&server, 310);
+    desktop.sync(&server, 320);
+
+    for device in [&desktop, &laptop] {
+        assert_eq!(device.ids("words.json", "words"), ["b", "c", "a"]);
+        assert_eq!(device.ids("history.json", "events"), ["y", "x"]);
+    }
+}
+
+#[test]
+fn replica_is_kept_in_the_sync_store() {
+    let server = start_server();
+    let desktop = StoredDevice::new();
+    desktop.set(
+        "words.json",
+        "words",
+        json!([{ "id": "a", "word": "学校", "created_at": 100 }]),
+    );
+
+    desktop.sync(&server, 200);
+    let replica = serde_json::from_value::<Replica>(desktop.get("sync.json", "replica")).unwrap();
+    assert!(replica.get(RecordKind::Word, "a").is_some());
+    assert!(replica.server.is_some());
+
+    // Nothing is pushed again by a replica that remembers what it sent.
+    let report = desktop.sync(&server, 210);
+    assert_eq!(report.pushed, 0);
+    assert_eq!(
+        serde_json::from_value::<Replica>(desktop.get("sync.json", "replica"))
+            .unwrap()
+            .device,
+        replica.device
+    );
+}
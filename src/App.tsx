@@ -1,5 +1,6 @@
 import { Kanjis } from "./components/Kanjis";
 import { ParsedWords } from "./components/ParsedWords";
+import { Reader } from "./components/Reader";
 import { Tabs } from "./components/Tabs";
 import { Words } from "./components/Words";
 
@@ -7,6 +8,7 @@ const tabs = {
   Words: () => <Words />,
   Kanji: () => <Kanjis />,
   "Parsed Words": () => <ParsedWords />,
+  Reader: () => <Reader />,
 };
 
 function App() {
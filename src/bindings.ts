@@ -32,6 +32,38 @@ async getKanjidic2ByKanji(kanji: string) : Promise<Result<Kanjidic2Entry[], stri
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Pairs of similar kanji for contrast review, at least one of them saved in
+ * the deck. Pairs the user is learning both sides of come first.
+ */
+async getContrastPairs(limit: number) : Promise<Result<ContrastPair[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_contrast_pairs", { limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getStrokeOrder(kanji: string) : Promise<Result<StrokeOrder, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_stroke_order", { kanji }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Kanji matching a drawing, best first. `strokes` are point lists in canvas
+ * coordinates, in the order they were drawn.
+ */
+async recognizeHandwriting(strokes: Point[][], limit: number) : Promise<Result<HandwritingCandidate[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("recognize_handwriting", { strokes, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async parseWord(word: string) : Promise<Result<FuriganaString, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("parse_word", { word }) };
@@ -40,6 +72,42 @@ async parseWord(word: string) : Promise<Result<FuriganaString, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+async getPitchAccents(word: string) : Promise<Result<PitchAccent[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_pitch_accents", { word }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async renderPitchAccent(word: string, reading: string | null, accent: number) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("render_pitch_accent", { word, reading, accent }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Furigana for `text` in `format`. With `hide_known_kanji`, kanji saved in the
+ * kanji deck are rendered without a reading.
+ */
+async renderFurigana(text: string, reading: string | null, format: FuriganaFormat, hideKnownKanji: boolean) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("render_furigana", { text, reading, format, hideKnownKanji }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async parseFurigana(input: string, format: FuriganaFormat) : Promise<Result<FuriganaString, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("parse_furigana", { input, format }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async validateDictionary() : Promise<Result<null, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("validate_dictionary") };
@@ -48,6 +116,62 @@ async validateDictionary() : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+async convertText(text: string, options: ConversionOptions) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("convert_text", { text, options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getTokenizerDictionary() : Promise<Result<TokenizerDictionary, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_tokenizer_dictionary") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setTokenizerDictionary(dictionary: TokenizerDictionary) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_tokenizer_dictionary", { dictionary }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getUserDictionaryEntries() : Promise<Result<UserDictionaryEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_user_dictionary_entries") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async addUserDictionaryEntry(payload: UserDictionaryEntryCreatePayload) : Promise<Result<UserDictionaryEntry, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_user_dictionary_entry", { payload }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateUserDictionaryEntry(entry: UserDictionaryEntry) : Promise<Result<UserDictionaryEntry, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_user_dictionary_entry", { entry }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeUserDictionaryEntry(surface: string) : Promise<Result<UserDictionaryEntry, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_user_dictionary_entry", { surface }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getWordDictEntry(word: string) : Promise<Result<MyEntryDisplay | null, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_word_dict_entry", { word }) };
@@ -64,6 +188,37 @@ async getWordCandidates(word: string) : Promise<Result<MyEntryDisplay[], string>
     else return { status: "error", error: e  as any };
 }
 },
+async getExampleSentences(word: string, limit: number) : Promise<Result<ExampleSentence[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_example_sentences", { word, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The most frequent dictionary words that are not saved yet.
+ */
+async getStudySuggestions(limit: number) : Promise<Result<MyEntryDisplay[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_study_suggestions", { limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Dictionary words starting with `prefix`. Saved words come first, then the
+ * most frequent ones.
+ */
+async autocomplete(prefix: string, limit: number) : Promise<Result<AutocompleteSuggestion[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("autocomplete", { prefix, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getWords() : Promise<Result<Word[], string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_words") };
@@ -72,7 +227,7 @@ async getWords() : Promise<Result<Word[], string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async addWord(payload: WordCreatePayload) : Promise<Result<Word, string>> {
+async addWord(payload: WordCreatePayload) : Promise<Result<AddedWord, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("add_word", { payload }) };
 } catch (e) {
@@ -88,6 +243,35 @@ async hasWord(word: string) : Promise<Result<boolean, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Mines the `token_index`-th token of `sentence`: the word is saved in its
+ * dictionary form with meaning and kanji readings looked up, and the sentence
+ * is kept as its context. `add_missing_kanjis` as in `WordCreatePayload`.
+ */
+async addWordFromSentence(sentence: string, tokenIndex: number, source: string | null, addMissingKanjis: boolean) : Promise<Result<AddedWord, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_word_from_sentence", { sentence, tokenIndex, source, addMissingKanjis }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateWord(word: Word) : Promise<Result<Word, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_word", { word }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeWord(word: string) : Promise<Result<Word, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_word", { word }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getKanjis() : Promise<Result<Kanji[], string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_kanjis") };
@@ -111,6 +295,439 @@ async hasKanji(kanji: string) : Promise<Result<boolean, string>> {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
+},
+async updateKanji(kanji: Kanji) : Promise<Result<Kanji, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_kanji", { kanji }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeKanji(kanji: string) : Promise<Result<Kanji, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_kanji", { kanji }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Every tag in use together with its ancestors, sorted so a tag directly
+ * follows its parent.
+ */
+async getTags() : Promise<Result<TagCount[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_tags") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Returns how many cards changed.
+ */
+async addTags(cardKind: CardKind, cards: string[], tags: string[]) : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_tags", { cardKind, cards, tags }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Returns how many cards changed.
+ */
+async removeTags(cardKind: CardKind, cards: string[], tags: string[]) : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_tags", { cardKind, cards, tags }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Renames a tag on every word, kanji and collection, the tags below it move
+ * along. Returns how many cards changed.
+ */
+async renameTag(from: string, to: string) : Promise<Result<number, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rename_tag", { from, to }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getCollections() : Promise<Result<Collection[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_collections") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async addCollection(payload: CollectionCreatePayload) : Promise<Result<Collection, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_collection", { payload }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateCollection(collection: Collection) : Promise<Result<Collection, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_collection", { collection }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Only the collection goes, its cards are kept.
+ */
+async removeCollection(id: string) : Promise<Result<Collection, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_collection", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async addToCollection(id: string, cardKind: CardKind, cards: string[]) : Promise<Result<Collection, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_to_collection", { id, cardKind, cards }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Cards that belong through a tag stay in the collection, the tag has to be
+ * removed from them or from the collection instead.
+ */
+async removeFromCollection(id: string, cardKind: CardKind, cards: string[]) : Promise<Result<Collection, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_from_collection", { id, cardKind, cards }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getCollectionCards(id: string) : Promise<Result<CollectionCards, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_collection_cards", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Cards of the collection due for review now: reviewed cards first, most
+ * overdue first, then new ones in the order they were added.
+ */
+async getCollectionStudyQueue(id: string, limit: number) : Promise<Result<StudyCard[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_collection_study_queue", { id, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMnemonics(kanji: string) : Promise<Result<Mnemonic[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_mnemonics", { kanji }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMnemonicSuggestions(kanji: string) : Promise<Result<MnemonicSuggestion[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_mnemonic_suggestions", { kanji }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The first mnemonic of its kind for the kanji becomes the active one.
+ */
+async addMnemonic(payload: MnemonicCreatePayload) : Promise<Result<Mnemonic, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_mnemonic", { payload }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Saves `text` as a new version, the previous ones are kept.
+ */
+async editMnemonic(id: string, text: string) : Promise<Result<Mnemonic, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("edit_mnemonic", { id, text }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setActiveMnemonic(id: string) : Promise<Result<Mnemonic, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_active_mnemonic", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Builds the reading mnemonic around the anchor of `sound`, or around none.
+ */
+async setMnemonicSound(id: string, sound: string | null) : Promise<Result<Mnemonic, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_mnemonic_sound", { id, sound }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Removing the active mnemonic leaves the kanji without one of its kind.
+ */
+async removeMnemonic(id: string) : Promise<Result<Mnemonic, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_mnemonic", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getReadingSounds() : Promise<Result<ReadingSound[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_reading_sounds") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async addReadingSound(payload: ReadingSoundCreatePayload) : Promise<Result<ReadingSound, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_reading_sound", { payload }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The anchor and description can be changed, the sound can't: mnemonics
+ * reference it.
+ */
+async updateReadingSound(sound: ReadingSound) : Promise<Result<ReadingSound, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_reading_sound", { sound }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Reading mnemonics referencing the sound keep their text and lose the
+ * reference.
+ */
+async removeReadingSound(sound: string) : Promise<Result<ReadingSound, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_reading_sound", { sound }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The kanji of the deck with `sound` as on'yomi, by their saved readings or
+ * by kanjidic2.
+ */
+async getReadingSoundKanjis(sound: string) : Promise<Result<ReadingSoundKanjis, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_reading_sound_kanjis", { sound }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getHistory() : Promise<Result<HistoryEvent[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_history") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async recordReview(cardKind: CardKind, card: string, grade: ReviewGrade) : Promise<Result<HistoryEvent, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("record_review", { cardKind, card, grade }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getLearningStats(days: number) : Promise<Result<LearningStats, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_learning_stats", { days }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Writes every store of the profile to a single archive at `path`.
+ */
+async exportProfile(path: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_profile", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replaces the profile with the archive at `path`. The current profile is
+ * backed up first, the returned backup restores it. An import failing
+ * partway puts the backup back.
+ */
+async importProfile(path: string) : Promise<Result<ProfileBackup, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_profile", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getProfileBackups() : Promise<Result<ProfileBackup[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_profile_backups") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getProfiles() : Promise<Result<ProfilesPayload, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_profiles") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The new profile starts empty, it is not switched to.
+ */
+async createProfile(name: string) : Promise<Result<Profile, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_profile", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async renameProfile(id: string, name: string) : Promise<Result<Profile, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rename_profile", { id, name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Saves the stores of the current profile and loads the ones of `id` into
+ * the managed states. When loading fails the current profile stays active.
+ */
+async switchProfile(id: string) : Promise<Result<Profile, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("switch_profile", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Deletes the profile together with its stores and backups.
+ */
+async deleteProfile(id: string) : Promise<Result<Profile, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_profile", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Syncs the words, kanji, collections, mnemonics, reading sounds and review
+ * history of the active profile with the server at `url`, which was started
+ * with `token`.
+ */
+async syncKnowledgeBase(url: string, token: string, strategy: ConflictStrategy) : Promise<Result<SyncReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("sync_knowledge_base", { url, token, strategy }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Serves the sync endpoint from this machine, for devices on the same
+ * network. Returns the URL to sync with from here, other devices use this
+ * machine's address with the same port. Anyone on the network can reach it,
+ * only devices syncing with `token` are let in.
+ */
+async startSyncServer(port: number, token: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_sync_server", { port, token }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async stopSyncServer() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_sync_server") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async annotateText(text: string) : Promise<Result<AnnotatedText, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("annotate_text", { text }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async scoreText(text: string) : Promise<Result<TextScore, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("score_text", { text }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async startQuiz(config: QuizConfig) : Promise<Result<QuizSessionPayload, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_quiz", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Grades `answer` against the next unanswered question of the quiz.
+ */
+async answerQuiz(id: string, answer: string) : Promise<Result<QuizAnswerResult, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("answer_quiz", { id, answer }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 }
 }
 
@@ -124,17 +741,296 @@ async hasKanji(kanji: string) : Promise<Result<boolean, string>> {
 
 /** user-defined types **/
 
+export type AddedWord = { word: Word; /**
+ * Added along with the word, see `WordCreatePayload::add_missing_kanjis`.
+ */
+added_kanjis: Kanji[] }
+export type AnnotatedText = { tokens: AnnotatedToken[] }
+export type AnnotatedToken = { surface: string; lemma: string; reading: string | null; furigana: FuriganaString; status: TokenStatus; /**
+ * Prefilled payload for `add_word`, only set for unknown tokens the
+ * dictionary has.
+ */
+add_payload: WordCreatePayload | null }
+export type AnsweredQuestion = { question: QuizQuestion; answer: string; correct: boolean; correct_answers: string[] }
+export type AutocompleteSuggestion = { entry: MyEntryDisplay; saved: boolean }
+export type CardKind = "Word" | "Kanji"
+/**
+ * A user defined deck, e.g. "Genki ch.5". Cards belong to it when listed
+ * explicitly or when tagged with one of its tags.
+ */
+export type Collection = { id: string; name: string; description: string | null; words: string[]; kanjis: string[]; /**
+ * Tags below these count too.
+ */
+tags: string[]; created_at: number }
+export type CollectionCards = { words: Word[]; kanjis: Kanji[] }
+export type CollectionCreatePayload = { name: string; description: string | null; tags: string[] }
+export type ConflictStrategy = /**
+ * The most recently changed side wins the whole entity.
+ */
+"LastWriterWins" | /**
+ * Every field goes to the side that changed it last, so edits to
+ * different fields on two devices are both kept.
+ */
+"FieldMerge"
+export type ContrastPair = { kanji: string; keyword: string; similar_kanji: string; similar_keyword: string; score: number; /**
+ * Both kanji are saved, so the user is learning both of them.
+ */
+both_in_deck: boolean }
+export type ConversionOptions = { target: ConversionTarget; long_vowels: LongVowels; /**
+ * Keep katakana words (mostly loanwords) as written in kana output, `ー`
+ * included.
+ */
+preserve_katakana: boolean }
+export type ConversionTarget = "Hiragana" | "Katakana" | { Romaji: RomajiSystem }
+export type DailyCount = { /**
+ * Start of the day, UTC.
+ */
+day: number; words: number; kanji: number }
+export type ExampleSentence = { id: string; japanese: string; english_id: string; english: string }
 export type Furigana = { Kanji: { character: string; reading: string } } | { Other: string }
+export type FuriganaFormat = /**
+ * `<ruby><rb>漢</rb><rt>かん</rt></ruby>`
+ */
+"Html" | /**
+ * `漢[かん] 字[じ]`, Anki's `{{furigana:...}}` syntax.
+ */
+"Anki" | /**
+ * `｜漢《かん》｜字《じ》`, Aozora Bunko ruby.
+ */
+"Aozora" | /**
+ * `{漢|かん}{字|じ}`, the markdown-it-ruby / DenDenMarkdown syntax.
+ */
+"Markdown" | /**
+ * `かんじ`, the reading without any kanji.
+ */
+"Kana"
 export type FuriganaString = Furigana[]
-export type HeisigKanjiPayload = { id: string; kanji: string; pronunciation: string; primitives: string[]; words: string[]; jlpt_level: number | null; heisig_mnemonic: string | null; koohii_mnemonic_1: string | null; koohii_mnemonic_2: string | null }
+export type HandwritingCandidate = { kanji: string; keyword: string; /**
+ * Lower is closer.
+ */
+distance: number }
+export type HeisigKanjiPayload = { id: string; kanji: string; pronunciation: string; primitives: string[]; words: string[]; jlpt_level: number | null; heisig_mnemonic: string | null; koohii_mnemonic_1: string | null; koohii_mnemonic_2: string | null; /**
+ * Easily confused kanji, most similar first.
+ */
+similar_kanji: SimilarKanji[] }
 export type HeisigKanjiQuery = { Kanji: string } | { Reading: string } | { Keywords: string[] }
-export type Kanji = { id: string; kanji: string; readings: string[]; tags: string[]; writing_mnemonic: string | null; reading_mnemonic: string | null }
+export type HistoryEvent = { id: string; timestamp: number; card_kind: CardKind; /**
+ * The word or kanji itself, ids don't survive deleting and re-adding.
+ */
+card: string; kind: HistoryEventKind }
+export type HistoryEventKind = "Added" | { Reviewed: { grade: ReviewGrade } } | "Edited" | "Deleted"
+export type Kanji = { id: string; kanji: string; readings: string[]; tags: string[]; writing_mnemonic: string | null; reading_mnemonic: string | null; /**
+ * Missing on kanji saved before it was recorded.
+ */
+created_at: number | null }
 export type KanjiCreatePayload = { kanji: string; readings: string[]; writing_mnemonic: string | null; reading_mnemonic: string | null; tags: string[] }
 export type KanjiReading = { reading: string }
-export type Kanjidic2Entry = { literal: string; ja_on: string; ja_kun: string; heisig: string; heisig6: string }
-export type MyEntryDisplay = { word: string; reading: string; translations: string }
-export type Word = { id: string; word: string; meaning: string; kanji_readings: KanjiReading[] }
-export type WordCreatePayload = { word: string; meaning: string; kanji_readings: KanjiReading[] }
+export type KanjiVgStroke = { /**
+ * SVG path data in KanjiVG coordinates.
+ */
+path: string; /**
+ * Where KanjiVG places the stroke number label.
+ */
+number_position: [number, number] | null }
+export type Kanjidic2Entry = { literal: string; ja_on: string; ja_kun: string; heisig: string; heisig6: string; /**
+ * Classical (Kangxi) radical number. Missing in CSVs exported before the
+ * column was added.
+ */
+radical: number | null; stroke_count: number | null }
+export type LearningStats = { /**
+ * Oldest day first, ending today.
+ */
+added_per_day: DailyCount[]; review_count: number; /**
+ * Share of reviews not graded `Again`, `None` before the first review.
+ */
+retention_rate: number | null; /**
+ * Consecutive days with at least one review, up to today. A streak is
+ * only broken once a whole day goes by without reviewing.
+ */
+current_streak: number; longest_streak: number; /**
+ * Reviews due per day starting today, overdue cards count for today.
+ * Cards never reviewed aren't due for review yet.
+ */
+forecast: DailyCount[]; weakest_kanji: WeakKanji[] }
+/**
+ * How lengthened vowels (`おう`, `ー`, ...) are written.
+ */
+export type LongVowels = /**
+ * Spelled out: `toukyou`, `raamen`; `ー` is kept in kana output.
+ */
+"AsWritten" | /**
+ * Macrons for Hepburn, circumflexes for Kunrei: `tōkyō`, `tôkyô`. In kana
+ * output `ー` is replaced by the vowel it lengthens.
+ */
+"Marked" | /**
+ * Dropped: `tokyo`, `ramen`. In kana output `ー` is replaced by the vowel
+ * it lengthens.
+ */
+"Omitted"
+/**
+ * One of possibly several stories for a kanji. The active one of each kind
+ * is also shown as the kanji's `writing_mnemonic` or `reading_mnemonic`.
+ */
+export type Mnemonic = { id: string; kanji: string; kind: MnemonicKind; source: MnemonicSource; /**
+ * Oldest first, the last one is the current text.
+ */
+versions: MnemonicVersion[]; primitives: PrimitiveLink[]; /**
+ * Reading mnemonics can be built around the anchor of one of the
+ * kanji's on'yomi, see `reading_sounds::ReadingSound`.
+ */
+sound: string | null; active: boolean; created_at: number }
+export type MnemonicCreatePayload = { kanji: string; kind: MnemonicKind; source: MnemonicSource; text: string; sound: string | null }
+export type MnemonicKind = "Writing" | "Reading"
+/**
+ * Where the first version of a story came from.
+ */
+export type MnemonicSource = "Own" | "Heisig" | "Koohii"
+/**
+ * A Heisig or Koohii story to start from.
+ */
+export type MnemonicSuggestion = { source: MnemonicSource; text: string; primitives: PrimitiveLink[] }
+export type MnemonicVersion = { text: string; edited_at: number }
+export type MyEntryDisplay = { word: string; reading: string; translations: string; pitch_accents: PitchAccent[]; frequency_rank: number | null }
+/**
+ * `accent` is the mora after which the pitch drops, 0 meaning no drop (heiban).
+ */
+export type PitchAccent = { reading: string; accent: number }
+export type Point = { x: number; y: number }
+/**
+ * A primitive of the kanji the story mentions.
+ */
+export type PrimitiveLink = { /**
+ * As Heisig names it, e.g. "top hat".
+ */
+primitive: string; /**
+ * The kanji with the primitive's name as keyword, if there is one.
+ */
+kanji: string | null }
+export type Profile = { id: string; name: string; created_at: number }
+export type ProfileBackup = { /**
+ * Can be passed to `import_profile` to restore the backup.
+ */
+path: string; created_at: number }
+export type ProfilesPayload = { profiles: Profile[]; active: Profile }
+export type QuestionType = "KeywordToKanji" | "KanjiToReading" | "WordToReading" | "ReadingToWord"
+export type QuizAnswerResult = { answered: AnsweredQuestion; /**
+ * Set once the last question is answered, the session is closed then.
+ */
+results: QuizResults | null }
+export type QuizConfig = { /**
+ * Questions cycle through these types, types without material are skipped.
+ */
+question_types: QuestionType[]; question_count: number; /**
+ * Including the correct one. Defaults to 4.
+ */
+choice_count: number | null }
+export type QuizQuestion = { index: number; question_type: QuestionType; prompt: string; choices: string[] }
+export type QuizResults = { total: number; correct: number; percentage: number; answers: AnsweredQuestion[] }
+export type QuizSessionPayload = { id: string; questions: QuizQuestion[] }
+/**
+ * An on'yomi with the anchor every reading story for it is built around,
+ * e.g. こう → a cowboy, so 校, 交 and 効 all get a cowboy in their story.
+ */
+export type ReadingSound = { id: string; /**
+ * In katakana, the way on'yomi are written.
+ */
+sound: string; /**
+ * The character or actor standing for the sound.
+ */
+anchor: string; description: string | null; created_at: number }
+export type ReadingSoundCreatePayload = { /**
+ * Hiragana is accepted too.
+ */
+sound: string; anchor: string; description: string | null }
+/**
+ * Everything in the deck read with a sound.
+ */
+export type ReadingSoundKanjis = { /**
+ * Missing when no anchor was picked for the sound yet.
+ */
+sound: ReadingSound | null; kanjis: Kanji[]; /**
+ * Reading mnemonics that reference the sound's anchor.
+ */
+mnemonics: Mnemonic[] }
+export type ReviewGrade = "Again" | "Hard" | "Good" | "Easy"
+export type RomajiSystem = "Hepburn" | "Kunrei"
+/**
+ * The sentence a word was mined from. The cloze is given in characters, not
+ * bytes, so the frontend can slice the sentence directly.
+ */
+export type SentenceContext = { sentence: string; cloze_start: number; cloze_length: number; /**
+ * Free text, e.g. "NHK Easy" or the article URL.
+ */
+source: string | null; mined_at: number }
+export type SentenceContextPayload = { sentence: string; cloze_start: number; cloze_length: number; source: string | null }
+export type SimilarKanji = { kanji: string; score: number }
+export type StrokeOrder = { kanji: string; /**
+ * In writing order.
+ */
+strokes: KanjiVgStroke[]; /**
+ * All strokes with their numbers.
+ */
+numbered_svg: string; /**
+ * One SVG per stroke: the strokes written so far, the current one
+ * highlighted and the remaining ones greyed out.
+ */
+frames: string[] }
+export type StudyCard = { card_kind: CardKind; card: string; /**
+ * Never reviewed.
+ */
+new: boolean; due: number }
+export type SyncReport = { pushed: number; pulled: number; /**
+ * Entities changed on both sides, resolved by the strategy.
+ */
+conflicts: number; /**
+ * Entities added on two devices independently, only one is kept.
+ */
+duplicates: number }
+export type TagCount = { tag: string; /**
+ * Cards tagged with the tag or one below it.
+ */
+words: number; kanji: number }
+export type TextScore = { token_count: number; known_token_count: number; known_token_percentage: number; lemma_count: number; known_lemma_count: number; known_lemma_percentage: number; /**
+ * Most common first, words without a rank last. More repeated in the
+ * text wins ties.
+ */
+unknown_words: UnknownWord[]; estimated_jlpt_level: number | null }
+export type TokenStatus = /**
+ * Particles, auxiliaries, punctuation and other non-Japanese text.
+ */
+"Grammar" | /**
+ * The token's lemma (or surface form) is saved in the words deck.
+ */
+"InDeck" | /**
+ * Saved and reviewed successfully often enough to need no furigana.
+ */
+"Known" | "Unknown"
+export type TokenizerDictionary = "Ipadic" | /**
+ * Only available when built with the `unidic` feature.
+ */
+"Unidic"
+export type UnknownWord = { lemma: string; occurrences: number; frequency_rank: number | null }
+export type UserDictionaryEntry = { surface: string; part_of_speech: string; reading: string }
+export type UserDictionaryEntryCreatePayload = { surface: string; /**
+ * Defaults to `カスタム名詞` (custom noun).
+ */
+part_of_speech: string | null; reading: string }
+export type WeakKanji = { kanji: string; reviews: number; lapses: number; retention_rate: number }
+export type Word = { id: string; word: string; meaning: string; kanji_readings: KanjiReading[]; pitch_accents: PitchAccent[]; context: SentenceContext | null; /**
+ * Hierarchical, see `tags::TAG_SEPARATOR`.
+ */
+tags: string[]; /**
+ * Missing on words saved before it was recorded.
+ */
+created_at: number | null }
+export type WordCreatePayload = { word: string; meaning: string; kanji_readings: KanjiReading[]; /**
+ * Looked up in the pitch accent data when left empty.
+ */
+pitch_accents?: PitchAccent[]; context?: SentenceContextPayload | null; tags?: string[]; /**
+ * Also adds the word's kanji that aren't in the knowledge base yet,
+ * pre-filled from kanjidic2 and Heisig.
+ */
+add_missing_kanjis?: boolean }
 
 /** tauri-specta globals **/
 
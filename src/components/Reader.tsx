@@ -0,0 +1,88 @@
+import { useState } from "react";
+import { PlusIcon } from "@heroicons/react/24/outline";
+import AnnotatedText from "./AnnotatedText";
+import { AnnotatedToken, FuriganaString, TokenStatus } from "../bindings";
+import { useAddMyWord, useAnnotatedText } from "../queries";
+
+const statusClassName: Record<TokenStatus, string> = {
+  Grammar: "text-gray-500",
+  InDeck: "bg-yellow-100",
+  Known: "",
+  Unknown: "border-b-2 border-red-400",
+};
+
+// Readings keyed by kanji, the shape AnnotatedText expects.
+const toAnnotations = (furigana: FuriganaString) =>
+  furigana.reduce((acc, f) => {
+    if ("Kanji" in f) {
+      acc[f.Kanji.character] = f.Kanji.reading;
+    }
+    return acc;
+  }, {} as Record<string, string>);
+
+const Token = ({ token }: { token: AnnotatedToken }) => {
+  const { mutateAsync: addWord, isPending } = useAddMyWord();
+  const [error, setError] = useState<string | null>(null);
+  // Known words are read without help.
+  const annotations =
+    token.status === "Known" ? {} : toAnnotations(token.furigana);
+
+  return (
+    <span
+      className={`inline-flex items-end ${statusClassName[token.status]}`}
+      title={error ?? (token.lemma !== token.surface ? token.lemma : undefined)}
+    >
+      <AnnotatedText text={token.surface} annotations={annotations} />
+      {token.add_payload && (
+        <button
+          className="text-gray-400 hover:text-blue-600 disabled:opacity-50"
+          title={`Add ${token.lemma}: ${token.add_payload.meaning}`}
+          disabled={isPending}
+          onClick={async () => {
+            const res = await addWord(token.add_payload!);
+            setError(res.status === "error" ? res.error : null);
+          }}
+        >
+          <PlusIcon className="w-3 h-3" />
+        </button>
+      )}
+    </span>
+  );
+};
+
+export const Reader = () => {
+  const [input, setInput] = useState("");
+  const [text, setText] = useState("");
+  const { data, isFetching } = useAnnotatedText(text);
+
+  return (
+    <div className="h-full w-full flex flex-col gap-2 p-2">
+      <div className="bg-white rounded-lg p-4 flex flex-col gap-2">
+        <textarea
+          className="w-full border border-gray-300 rounded p-2 min-h-[120px]"
+          placeholder="Paste Japanese text..."
+          value={input}
+          onChange={(e) => setInput(e.target.value)}
+        />
+        <div className="flex justify-end">
+          <button
+            className="px-6 py-2 rounded-lg bg-blue-600 text-white font-semibold shadow hover:bg-blue-700 transition disabled:opacity-50"
+            disabled={!input.trim() || isFetching}
+            onClick={() => setText(input)}
+          >
+            Read
+          </button>
+        </div>
+      </div>
+      <div className="bg-white rounded-lg p-4 flex-1 overflow-y-auto text-2xl leading-[2.5]">
+        {data?.status === "ok" &&
+          data.data.tokens.map((token, i) => <Token key={i} token={token} />)}
+        {data?.status === "error" && (
+          <div className="p-3 bg-red-100 text-red-700 border border-red-400 rounded text-base">
+            {data.error}
+          </div>
+        )}
+      </div>
+    </div>
+  );
+};
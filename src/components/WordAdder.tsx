@@ -1,7 +1,13 @@
 import { useMutation } from "@tanstack/react-query";
 import { useEffect, useMemo, useState } from "react";
 import { isKana, isJapanese } from "wanakana";
-import { commands, MyEntryDisplay, WordCreatePayload } from "../bindings";
+import {
+  AddedWord,
+  commands,
+  MyEntryDisplay,
+  Result,
+  WordCreatePayload,
+} from "../bindings";
 import { Autocomplete } from "./Autocomplete";
 
 const useChars = (currentWord: string | null) => {
@@ -57,12 +63,7 @@ export const WordAdder = ({
   initialQuery,
   onSave,
 }: {
-  addWord: (word: WordCreatePayload) => Promise<
-    | {
-        status: "ok";
-      }
-    | { status: "error"; error: string }
-  >;
+  addWord: (word: WordCreatePayload) => Promise<Result<AddedWord, string>>;
   initialQuery?: string;
   onSave?: () => void;
 }) => {
@@ -74,6 +75,8 @@ export const WordAdder = ({
   >([]);
   const [currentChar, setCurrentChar] = useState<null | number>(null);
   const [error, setError] = useState<string | null>(null);
+  const [addMissingKanjis, setAddMissingKanjis] = useState(true);
+  const [addedKanjis, setAddedKanjis] = useState<string[]>([]);
   const [query, setQuery] = useState(initialQuery ?? ""); // <-- add query state
 
   const chars = useChars(dictionaryWord?.word ?? null);
@@ -104,8 +107,10 @@ export const WordAdder = ({
       kanji_readings: kanjiPronunciations.map((kp) => ({
         reading: kp.pronunciation,
       })),
+      add_missing_kanjis: addMissingKanjis,
     }).then((res) => {
       if (res.status === "ok") {
+        setAddedKanjis(res.data.added_kanjis.map((k) => k.kanji));
         setDictionaryWord(null);
         setKanjiPronunciations([]);
         setCurrentChar(null);
@@ -255,6 +260,17 @@ export const WordAdder = ({
         />
       </div>
 
+      <div className="mb-4">
+        <label className="flex items-center gap-2 text-gray-700">
+          <input
+            type="checkbox"
+            checked={addMissingKanjis}
+            onChange={(e) => setAddMissingKanjis(e.target.checked)}
+          />
+          Also add kanji that aren't saved yet
+        </label>
+      </div>
+
       <div className="flex justify-end">
         <button
           disabled={
@@ -270,6 +286,12 @@ export const WordAdder = ({
         </button>
       </div>
 
+      {addedKanjis.length > 0 && (
+        <div className="mt-4 p-3 bg-green-100 text-green-700 border border-green-400 rounded">
+          Added kanji: {addedKanjis.join(" ")}
+        </div>
+      )}
+
       {error && (
         <div className="mt-4 p-3 bg-red-100 text-red-700 border border-red-400 rounded">
           {error}
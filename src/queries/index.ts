@@ -31,12 +31,23 @@ export const useMyWordsModel = (): MyWordsModel | null => {
   }, [data]);
 };
 
+export const useAnnotatedText = (text: string) => {
+  return useQuery({
+    queryKey: ["words", "annotated", text],
+    queryFn: () => commands.annotateText(text),
+    enabled: text.trim() !== "",
+  });
+};
+
 export const useAddMyWord = () => {
   const queryClient = useQueryClient();
   return useMutation({
     mutationFn: commands.addWord,
-    onSuccess: () => {
+    onSuccess: (res) => {
       queryClient.invalidateQueries({ queryKey: ["words"] });
+      if (res.status === "ok" && res.data.added_kanjis.length) {
+        queryClient.invalidateQueries({ queryKey: ["kanjis"] });
+      }
     },
   });
 };